@@ -12,15 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Utility for building HCL address expressions.
+//! Utilities for building and parsing addresses.
 //!
 //! `AddressBuilder` is a pure utility with no state. It builds HCL traversal
-//! expressions from string segments.
+//! expressions from string or typed `AddressSegment`s. `parse_address` goes the other way and
+//! splits an address string such as `module.x.aws_instance.web["k"]` into
+//! segments.
 
+use anyhow::{Context, Result};
+use hcl::edit::Ident;
 use hcl::edit::expr::Expression;
 use hcl::edit::parser::parse_body;
+use std::fmt;
 
 /// Utility for building HCL address expressions from string segments
+#[derive(Default)]
 pub struct AddressBuilder;
 
 impl AddressBuilder {
@@ -34,35 +40,498 @@ impl AddressBuilder {
     ///
     /// This uses string parsing to build the expression, which is the most
     /// reliable way to create hcl::edit::expr::Expression from segments.
-    pub fn build(&self, segments: &[&str]) -> Expression {
-        if segments.is_empty() {
-            // Return a simple variable expression if no segments
-            let expr_str = "x";
-            let attr_str = format!("x = {}", expr_str);
-            let body = parse_body(&attr_str).expect("Failed to parse empty expression");
-            let attr = body.attributes().next().expect("Expected attribute");
-            return attr.value.clone();
+    pub fn build(&self, segments: &[&str]) -> Result<Expression> {
+        self.build_with_index(segments, &[])
+    }
+
+    /// Build an expression from typed segments
+    ///
+    /// Attributes are joined with `.` and indexes are rendered in brackets, in the
+    /// given order, e.g. `module`, `network`, `[0]`, `aws_vpc`, `main` builds
+    /// `module.network[0].aws_vpc.main`. Attributes that are not bare identifiers
+    /// are written with index syntax, as in `format_with_prefix_and_index()`.
+    pub fn build_segments(&self, segments: &[AddressSegment]) -> Result<Expression> {
+        let mut address = String::new();
+        for segment in segments {
+            match segment {
+                AddressSegment::Attribute(name) if address.is_empty() => {
+                    if Ident::try_new(name).is_err() {
+                        anyhow::bail!("Address must start with an identifier, got '{}'", name);
+                    }
+                    address.push_str(name);
+                }
+                AddressSegment::Attribute(name) if Ident::try_new(name).is_ok() => {
+                    address.push('.');
+                    address.push_str(name);
+                }
+                AddressSegment::Attribute(name) => {
+                    address.push_str(&AddressIndex::String(name.clone()).to_string());
+                }
+                AddressSegment::Index(index) if address.is_empty() => {
+                    anyhow::bail!("Address must start with an identifier, got '{}'", index);
+                }
+                AddressSegment::Index(index) => address.push_str(&index.to_string()),
+            }
         }
+        self.parse(&address)
+    }
 
-        // Build expression string: segment0.segment1.segment2...
-        let expr_str = segments.join(".");
+    /// Build an expression from string segments followed by index segments
+    ///
+    /// Index segments are rendered in brackets instead of being joined with `.`,
+    /// e.g. `["aws_instance", "web"]` with `[0]` builds `aws_instance.web[0]`.
+    pub fn build_with_index(
+        &self,
+        segments: &[&str],
+        indices: &[AddressIndex],
+    ) -> Result<Expression> {
+        let segments: Vec<AddressSegment> = segments
+            .iter()
+            .map(|segment| AddressSegment::Attribute(segment.to_string()))
+            .chain(indices.iter().cloned().map(AddressSegment::Index))
+            .collect();
+        self.build_segments(&segments)
+    }
+
+    /// Build an expression from `segments` placed after an already formatted address prefix
+    ///
+    /// An empty prefix builds the segments alone.
+    pub fn build_with_prefix(&self, prefix: &str, segments: &[&str]) -> Result<Expression> {
+        self.build_with_prefix_and_index(prefix, segments, &[])
+    }
+
+    /// Like `build_with_prefix()`, with index segments after the last segment
+    pub fn build_with_prefix_and_index(
+        &self,
+        prefix: &str,
+        segments: &[&str],
+        indices: &[AddressIndex],
+    ) -> Result<Expression> {
+        if prefix.is_empty()
+            && let Some(first) = segments.first()
+            && Ident::try_new(*first).is_err()
+        {
+            anyhow::bail!("Address must start with an identifier, got '{}'", first);
+        }
+        self.parse(&self.format_with_prefix_and_index(prefix, segments, indices))
+    }
+
+    /// Format the address text that `build_with_prefix_and_index()` parses
+    ///
+    /// Segments that are not bare HCL identifiers, e.g. `1web` or `my web`, are
+    /// rendered with index syntax: `aws_instance["1web"]`. The first segment of an
+    /// address without prefix is always written as is.
+    pub fn format_with_prefix_and_index(
+        &self,
+        prefix: &str,
+        segments: &[&str],
+        indices: &[AddressIndex],
+    ) -> String {
+        let mut address = prefix.to_string();
+        for segment in segments {
+            if address.is_empty() {
+                address.push_str(segment);
+            } else if Ident::try_new(*segment).is_ok() {
+                address.push('.');
+                address.push_str(segment);
+            } else {
+                address.push_str(&AddressIndex::String(segment.to_string()).to_string());
+            }
+        }
+        for index in indices {
+            address.push_str(&index.to_string());
+        }
+        address
+    }
 
+    /// Parse a formatted address, e.g. `module.compute.aws_instance.web[0]`, into an expression
+    pub fn parse(&self, address: &str) -> Result<Expression> {
         // Parse the expression by wrapping it in an attribute
-        let attr_str = format!("x = {}", expr_str);
-        let body = parse_body(&attr_str)
-            .unwrap_or_else(|_| panic!("Failed to parse expression: {}", expr_str));
+        let body = parse_body(&format!("x = {}", address))
+            .map_err(|_| anyhow::anyhow!("Invalid address: {}", address))?;
         let attr = body
             .attributes()
             .next()
-            .expect("Expected attribute in parsed body");
-        attr.value.clone()
+            .context("Expected attribute in parsed body")?;
+        Ok(attr.value.clone())
+    }
+}
+
+/// Key inside an index segment, e.g. `[0]` or `["k"]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressIndex {
+    Number(u64),
+    String(String),
+}
+
+impl fmt::Display for AddressIndex {
+    /// Formats the key with its brackets, e.g. `[0]` or `["k"]`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressIndex::Number(number) => write!(f, "[{}]", number),
+            AddressIndex::String(key) => {
+                let escaped = key.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "[\"{}\"]", escaped)
+            }
+        }
+    }
+}
+
+/// One segment of a parsed address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressSegment {
+    /// Dotted name, e.g. `module` or `aws_instance`
+    Attribute(String),
+    /// Instance key in brackets
+    Index(AddressIndex),
+}
+
+/// Parse an address like `module.x.aws_instance.web["k"]` into its segments
+///
+/// Supports dotted names and bracket indices with numbers or double-quoted strings
+/// (with `\"` and `\\` escapes). The address must start with a name.
+pub fn parse_address(address: &str) -> Result<Vec<AddressSegment>> {
+    let mut segments = Vec::new();
+    let mut chars = address.char_indices().peekable();
+    let mut expect_name = true;
+
+    while let Some(&(position, c)) = chars.peek() {
+        if expect_name {
+            let mut name = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_' || c == '-') {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            if name.is_empty() {
+                anyhow::bail!(
+                    "Invalid address '{}': expected a name at position {}",
+                    address,
+                    position
+                );
+            }
+            segments.push(AddressSegment::Attribute(name));
+            expect_name = false;
+            continue;
+        }
+
+        chars.next();
+        match c {
+            '.' => expect_name = true,
+            '[' => {
+                let index = match chars.peek() {
+                    Some(&(_, '"')) => {
+                        chars.next();
+                        let mut key = String::new();
+                        loop {
+                            match chars.next() {
+                                Some((_, '"')) => break,
+                                Some((_, '\\')) => match chars.next() {
+                                    Some((_, escaped @ ('"' | '\\'))) => key.push(escaped),
+                                    _ => anyhow::bail!(
+                                        "Invalid address '{}': unsupported escape in index",
+                                        address
+                                    ),
+                                },
+                                Some((_, c)) => key.push(c),
+                                None => anyhow::bail!(
+                                    "Invalid address '{}': unterminated string index",
+                                    address
+                                ),
+                            }
+                        }
+                        AddressIndex::String(key)
+                    }
+                    _ => {
+                        let mut digits = String::new();
+                        while let Some(&(_, c)) = chars.peek() {
+                            if !c.is_ascii_digit() {
+                                break;
+                            }
+                            digits.push(c);
+                            chars.next();
+                        }
+                        let number = digits.parse().map_err(|_| {
+                            anyhow::anyhow!(
+                                "Invalid address '{}': index at position {} must be a number or a quoted string",
+                                address,
+                                position
+                            )
+                        })?;
+                        AddressIndex::Number(number)
+                    }
+                };
+                if chars.next().map(|(_, c)| c) != Some(']') {
+                    anyhow::bail!("Invalid address '{}': unterminated index", address);
+                }
+                segments.push(AddressSegment::Index(index));
+            }
+            _ => anyhow::bail!(
+                "Invalid address '{}': unexpected '{}' at position {}",
+                address,
+                c,
+                position
+            ),
+        }
+    }
+
+    if expect_name {
+        anyhow::bail!("Invalid address '{}': expected a name at the end", address);
+    }
+    Ok(segments)
+}
+
+/// Check that Terraform 0.11 can run `terraform state mv` with this address
+///
+/// 0.11 writes `count` instances as `[N]` like later versions, but has no `for_each`
+/// string keys and no `count`/`for_each` on modules (added in 0.12.6 and 0.13).
+pub fn check_terraform11_address(address: &str) -> Result<()> {
+    let segments = parse_address(address)?;
+    let mut rest = segments.as_slice();
+    while let [
+        AddressSegment::Attribute(keyword),
+        AddressSegment::Attribute(_),
+        tail @ ..,
+    ] = rest
+    {
+        if keyword != "module" {
+            break;
+        }
+        if let [AddressSegment::Index(_), ..] = tail {
+            anyhow::bail!(
+                "Address '{}' has a module instance key, which Terraform 0.11 does not support",
+                address
+            );
+        }
+        rest = tail;
+    }
+    if rest
+        .iter()
+        .any(|segment| matches!(segment, AddressSegment::Index(AddressIndex::String(_))))
+    {
+        anyhow::bail!(
+            "Address '{}' has a for_each key, which Terraform 0.11 does not support",
+            address
+        );
+    }
+    Ok(())
+}
+
+/// Format segments back into an address string, e.g. `module.x["k"].aws_instance.web`
+pub fn format_address(segments: &[AddressSegment]) -> String {
+    let mut address = String::new();
+    for segment in segments {
+        match segment {
+            AddressSegment::Attribute(name) => {
+                if !address.is_empty() {
+                    address.push('.');
+                }
+                address.push_str(name);
+            }
+            AddressSegment::Index(index) => address.push_str(&index.to_string()),
+        }
+    }
+    address
+}
+
+/// Normalize an address for comparison, e.g. `aws_instance.web[ "a" ]` to `aws_instance.web["a"]`
+///
+/// Whitespace inside the address is only dropped when the address does not parse
+/// with it, so string keys keep their spaces. Addresses that cannot be parsed are
+/// compared as written, without surrounding whitespace.
+pub fn normalize_address(address: &str) -> String {
+    let address = address.trim();
+    let compact: String = address.split_whitespace().collect();
+    parse_address(address)
+        .or_else(|_| parse_address(&compact))
+        .map(|segments| format_address(&segments))
+        .unwrap_or_else(|_| address.to_string())
+}
+
+/// Parse a module path prefix like `module.a.module.b[0]`, returning it formatted
+///
+/// The empty string is the root module. Every step must be `module.<name>`,
+/// optionally followed by one instance key.
+pub fn parse_module_path(path: &str) -> Result<String> {
+    if path.is_empty() {
+        return Ok(String::new());
+    }
+    let segments = parse_address(path)?;
+    let mut rest = segments.as_slice();
+    while !rest.is_empty() {
+        rest = match rest {
+            [
+                AddressSegment::Attribute(module),
+                AddressSegment::Attribute(_),
+                tail @ ..,
+            ] if module == "module" => match tail {
+                [AddressSegment::Index(_), tail @ ..] => tail,
+                _ => tail,
+            },
+            _ => anyhow::bail!(
+                "Invalid module path '{}': expected module.<name> steps, e.g. module.a.module.b",
+                path
+            ),
+        };
     }
+    Ok(format_address(&segments))
+}
+
+/// Validate a module name: Terraform identifiers, optionally nested with `.`
+///
+/// Each segment must start with a letter or underscore, followed by alphanumeric
+/// characters, underscores or hyphens. Hyphens are part of Terraform's identifier
+/// grammar, so `module.my-module` is a valid address and is emitted unquoted.
+/// `platform.compute` names `compute` inside `platform`, see `module_path()`.
+pub fn validate_module_name(module_name: &str) -> Result<()> {
+    if module_name.is_empty() {
+        anyhow::bail!("Module name cannot be empty");
+    }
+    for segment in module_name.split('.') {
+        let mut chars = segment.chars();
+        let Some(first_char) = chars.next() else {
+            anyhow::bail!("Module name contains an empty segment: '{}'", module_name);
+        };
+        if !first_char.is_alphabetic() && first_char != '_' {
+            anyhow::bail!(
+                "Module name must start with a letter or underscore, got: {}",
+                first_char
+            );
+        }
+
+        for c in chars {
+            if !c.is_alphanumeric() && c != '_' && c != '-' {
+                anyhow::bail!(
+                    "Module name contains invalid character: {}. Only alphanumeric characters, underscores, and hyphens are allowed",
+                    c
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Expand a (possibly nested) module name into a module path
+///
+/// `compute` gives `module.compute`, `platform.compute` gives
+/// `module.platform.module.compute`.
+pub fn module_path(module_name: &str) -> String {
+    module_name
+        .split('.')
+        .map(|name| format!("module.{}", name))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Innermost module of a (possibly nested) module name, `compute` for `platform.compute`
+pub fn innermost_module_name(module_name: &str) -> &str {
+    module_name.rsplit('.').next().unwrap_or(module_name)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use hcl::edit::structure::Body;
+    use pretty_assertions::assert_eq;
+
+    fn attribute(name: &str) -> AddressSegment {
+        AddressSegment::Attribute(name.to_string())
+    }
+
+    #[test]
+    fn test_parse_address_module_resource() -> Result<()> {
+        assert_eq!(
+            parse_address("module.x.aws_instance.web")?,
+            vec![
+                attribute("module"),
+                attribute("x"),
+                attribute("aws_instance"),
+                attribute("web"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_address_string_index() -> Result<()> {
+        assert_eq!(
+            parse_address(r#"aws_instance.web["k"]"#)?,
+            vec![
+                attribute("aws_instance"),
+                attribute("web"),
+                AddressSegment::Index(AddressIndex::String("k".to_string())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_address_number_index_in_module_path() -> Result<()> {
+        assert_eq!(
+            parse_address("module.net[0].aws_subnet.a")?,
+            vec![
+                attribute("module"),
+                attribute("net"),
+                AddressSegment::Index(AddressIndex::Number(0)),
+                attribute("aws_subnet"),
+                attribute("a"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_terraform11_address() -> Result<()> {
+        check_terraform11_address("module.a.module.b.aws_instance.web[0]")?;
+        check_terraform11_address("data.aws_ami.ubuntu")?;
+
+        let error = check_terraform11_address(r#"aws_instance.web["k"]"#).unwrap_err();
+        assert!(error.to_string().contains("has a for_each key"));
+        let error = check_terraform11_address("module.net[0].aws_subnet.a").unwrap_err();
+        assert!(error.to_string().contains("has a module instance key"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_address_escaped_string_index() -> Result<()> {
+        assert_eq!(
+            parse_address(r#"aws_instance.web["a.\"b\"]"]"#)?,
+            vec![
+                attribute("aws_instance"),
+                attribute("web"),
+                AddressSegment::Index(AddressIndex::String(r#"a."b"]"#.to_string())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_address_malformed() {
+        let malformed = [
+            "",
+            ".web",
+            "aws_instance.",
+            "aws_instance..web",
+            "aws_instance.web[",
+            "aws_instance.web[0",
+            "aws_instance.web[]",
+            "aws_instance.web[k]",
+            r#"aws_instance.web["k"#,
+            r#"aws_instance.web["\n"]"#,
+            "aws_instance web",
+            "[0].web",
+        ];
+        for address in malformed {
+            assert!(
+                parse_address(address).is_err(),
+                "Expected error for {:?}",
+                address
+            );
+        }
+    }
 
     #[test]
     fn test_address_builder_new() {
@@ -72,24 +541,59 @@ mod tests {
     }
 
     #[test]
-    fn test_address_builder_build_single_segment() {
+    fn test_address_builder_default() -> Result<()> {
+        let builder: AddressBuilder = Default::default();
+        let expr = builder.build(&["aws_instance", "web"])?;
+        assert_eq!(expr.to_string().trim(), "aws_instance.web");
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_builder_build_segments() -> Result<()> {
+        let builder = AddressBuilder::new();
+        let expr = builder.build_segments(&[
+            attribute("module"),
+            attribute("network"),
+            AddressSegment::Index(AddressIndex::String("a".to_string())),
+            attribute("aws_instance"),
+            attribute("web"),
+            AddressSegment::Index(AddressIndex::Number(0)),
+        ])?;
+        assert!(matches!(expr, Expression::Traversal(_)));
+        assert_eq!(
+            expr.to_string().trim(),
+            r#"module.network["a"].aws_instance.web[0]"#
+        );
+
+        assert!(
+            builder
+                .build_segments(&[AddressSegment::Index(AddressIndex::Number(0))])
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_builder_build_single_segment() -> Result<()> {
         let builder = AddressBuilder::new();
-        let expr = builder.build(&["aws_instance"]);
+        let expr = builder.build(&["aws_instance"])?;
         // A single segment creates a Variable, not a Traversal
         assert!(matches!(expr, Expression::Variable(_)));
+        Ok(())
     }
 
     #[test]
-    fn test_address_builder_build_multiple_segments() {
+    fn test_address_builder_build_multiple_segments() -> Result<()> {
         let builder = AddressBuilder::new();
-        let expr = builder.build(&["aws_instance", "web"]);
+        let expr = builder.build(&["aws_instance", "web"])?;
         assert!(matches!(expr, Expression::Traversal(_)));
+        Ok(())
     }
 
     #[test]
-    fn test_address_builder_build_resource_expression() {
+    fn test_address_builder_build_resource_expression() -> Result<()> {
         let builder = AddressBuilder::new();
-        let expr = builder.build(&["aws_instance", "web"]);
+        let expr = builder.build(&["aws_instance", "web"])?;
         assert!(matches!(expr, Expression::Traversal(_)));
         // Verify it can be converted to string representation
         let body = Body::builder()
@@ -101,12 +605,13 @@ mod tests {
         let output = body.to_string();
         assert!(output.contains("aws_instance"));
         assert!(output.contains("web"));
+        Ok(())
     }
 
     #[test]
-    fn test_address_builder_build_module_expression() {
+    fn test_address_builder_build_module_expression() -> Result<()> {
         let builder = AddressBuilder::new();
-        let expr = builder.build(&["module", "web_server"]);
+        let expr = builder.build(&["module", "web_server"])?;
         assert!(matches!(expr, Expression::Traversal(_)));
         let body = Body::builder()
             .attribute(hcl::edit::structure::Attribute::new(
@@ -117,12 +622,33 @@ mod tests {
         let output = body.to_string();
         assert!(output.contains("module"));
         assert!(output.contains("web_server"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_builder_hyphenated_module_name() -> Result<()> {
+        assert!(validate_module_name("my-module").is_ok());
+        assert!(validate_module_name("-module").is_err());
+
+        // A hyphen inside an identifier is not a subtraction
+        let builder = AddressBuilder::new();
+        let expr = builder.parse("module.my-module.aws_instance.web")?;
+        assert!(matches!(expr, Expression::Traversal(_)));
+        assert_eq!(expr.to_string().trim(), "module.my-module.aws_instance.web");
+        assert_eq!(
+            builder
+                .build(&["module", "my-module", "aws_instance", "web"])?
+                .to_string()
+                .trim(),
+            "module.my-module.aws_instance.web"
+        );
+        Ok(())
     }
 
     #[test]
-    fn test_address_builder_build_nested_expression() {
+    fn test_address_builder_build_nested_expression() -> Result<()> {
         let builder = AddressBuilder::new();
-        let expr = builder.build(&["module", "name", "aws_instance", "web"]);
+        let expr = builder.build(&["module", "name", "aws_instance", "web"])?;
         assert!(matches!(expr, Expression::Traversal(_)));
         let body = Body::builder()
             .attribute(hcl::edit::structure::Attribute::new(
@@ -135,5 +661,143 @@ mod tests {
         assert!(output.contains("name"));
         assert!(output.contains("aws_instance"));
         assert!(output.contains("web"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_address_round_trip() -> Result<()> {
+        for address in [
+            "module.x.aws_instance.web",
+            r#"aws_instance.web["k"]"#,
+            "module.net[0].aws_subnet.a",
+            r#"aws_instance.web["a\"b"]"#,
+        ] {
+            assert_eq!(format_address(&parse_address(address)?), address);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path("compute"), "module.compute");
+        assert_eq!(
+            module_path("platform.compute"),
+            "module.platform.module.compute"
+        );
+        assert_eq!(innermost_module_name("platform.compute"), "compute");
+        assert_eq!(innermost_module_name("compute"), "compute");
+    }
+
+    #[test]
+    fn test_validate_nested_module_name() {
+        assert!(validate_module_name("platform.compute").is_ok());
+        for invalid in ["platform.", ".compute", "platform..compute", "platform.1x"] {
+            assert!(
+                validate_module_name(invalid).is_err(),
+                "Expected error for {:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_address() {
+        assert_eq!(
+            normalize_address(" module.compute.aws_instance.web "),
+            "module.compute.aws_instance.web"
+        );
+        assert_eq!(
+            normalize_address(r#"aws_instance.web[ "a" ]"#),
+            r#"aws_instance.web["a"]"#
+        );
+        assert_eq!(
+            normalize_address(r#"aws_instance.web["a b"]"#),
+            r#"aws_instance.web["a b"]"#
+        );
+        assert_eq!(normalize_address(" not an address! "), "not an address!");
+    }
+
+    #[test]
+    fn test_parse_module_path() -> Result<()> {
+        assert_eq!(parse_module_path("")?, "");
+        assert_eq!(parse_module_path("module.a")?, "module.a");
+        assert_eq!(
+            parse_module_path(r#"module.a["x"].module.b"#)?,
+            r#"module.a["x"].module.b"#
+        );
+        for invalid in [
+            "module",
+            "aws_instance.web",
+            "module.a.b",
+            "module.a[0][1]",
+            "a.",
+        ] {
+            assert!(
+                parse_module_path(invalid).is_err(),
+                "Expected error for {:?}",
+                invalid
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_builder_build_with_prefix() -> Result<()> {
+        let builder = AddressBuilder::new();
+        let expr = builder.build_with_prefix("module.a[0]", &["aws_instance", "web"])?;
+        assert_eq!(expr.to_string().trim(), "module.a[0].aws_instance.web");
+        let expr = builder.build_with_prefix("", &["aws_instance", "web"])?;
+        assert_eq!(expr.to_string().trim(), "aws_instance.web");
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_builder_build_with_index() -> Result<()> {
+        let builder = AddressBuilder::new();
+        let expr =
+            builder.build_with_index(&["aws_instance", "web"], &[AddressIndex::Number(0)])?;
+        assert!(expr.to_string().contains("web[0]"));
+        let expr = builder.build_with_index(
+            &["aws_instance", "web"],
+            &[AddressIndex::String("a\"b".to_string())],
+        )?;
+        assert_eq!(expr.to_string().trim(), r#"aws_instance.web["a\"b"]"#);
+        let expr = builder.build_with_prefix_and_index(
+            "module.compute",
+            &["aws_instance", "web"],
+            &[AddressIndex::Number(1)],
+        )?;
+        assert_eq!(
+            expr.to_string().trim(),
+            "module.compute.aws_instance.web[1]"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_builder_escapes_non_identifier_segments() -> Result<()> {
+        let builder = AddressBuilder::new();
+        let expr = builder.build(&["aws_instance", "1web"])?;
+        assert_eq!(expr.to_string().trim(), r#"aws_instance["1web"]"#);
+        let expr = builder.build_with_prefix("module.compute", &["aws_instance", "my web"])?;
+        assert_eq!(
+            expr.to_string().trim(),
+            r#"module.compute.aws_instance["my web"]"#
+        );
+        let expr = builder.build_with_prefix("", &["module", "web-server"])?;
+        assert_eq!(expr.to_string().trim(), "module.web-server");
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_builder_rejects_invalid_first_segment() {
+        let error = AddressBuilder::new()
+            .build(&["1web", "aws_instance"])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Address must start with an identifier, got '1web'"
+        );
+        assert!(AddressBuilder::new().build(&[]).is_err());
     }
 }
@@ -0,0 +1,85 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Address allowlist.
+//!
+//! An allowlist file contains one address per line, e.g. `aws_instance.web`
+//! or `module.database`. Blank lines and lines starting with `#` are ignored.
+
+use crate::address::normalize_address;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Set of `from` addresses that may be moved
+///
+/// Addresses are normalized (see `normalize_address`), so formatting differences
+/// such as spacing inside an index do not matter when comparing.
+#[derive(Debug, Default, Clone)]
+pub struct Allowlist {
+    addresses: HashSet<String>,
+}
+
+impl Allowlist {
+    /// Read and parse an allowlist file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read allowlist: {}", path.display()))?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parse one address per line
+    pub fn parse(content: &str) -> Self {
+        let addresses = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(normalize_address)
+            .collect();
+        Self { addresses }
+    }
+
+    /// Whether `address` is listed
+    pub fn contains(&self, address: &str) -> bool {
+        self.addresses.contains(&normalize_address(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_allowlist_parse() {
+        let allowlist = Allowlist::parse(
+            "# Stage 1\n\naws_instance.web\n  module.database  \naws_instance.api[ 0 ]\n",
+        );
+        assert!(allowlist.contains("aws_instance.web"));
+        assert!(allowlist.contains("module.database"));
+        assert!(allowlist.contains("aws_instance.api[0]"));
+        assert!(!allowlist.contains("aws_instance.api"));
+        assert!(!allowlist.contains("# Stage 1"));
+    }
+
+    #[test]
+    fn test_allowlist_from_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = Allowlist::from_file(&temp_dir.path().join("missing.txt"));
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Failed to read allowlist"));
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Meta-arguments of a source block.
+//!
+//! `BlockMeta` records which meta-arguments (`count`, `for_each`, `provider`)
+//! a `resource` or `module` block sets, and the comments written above it,
+//! so later steps do not need the source block. A `# moved_maker: module=<name>`
+//! comment among them sends the block to another module than `--module-name`.
+//! `MissingKey` decides what happens to such blocks when no instance key is known.
+
+use clap::ValueEnum;
+use hcl::edit::Decorate;
+use hcl::edit::structure::{Block, Body};
+
+/// Instance key emitted by `--missing-key placeholder` as `["REPLACE"]`, to be replaced by hand
+pub const PLACEHOLDER_KEY: &str = "REPLACE";
+
+/// Prefix of the comment that overrides the target module of one block, e.g. `# moved_maker: module=network`
+pub const MODULE_DIRECTIVE: &str = "moved_maker:";
+
+/// What to do with a `count`/`for_each` block when no instance key is known
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingKey {
+    /// Drop the block with a warning
+    Skip,
+    /// Emit the addresses with a `["REPLACE"]` instance key
+    Placeholder,
+    /// Fail the run
+    Error,
+}
+
+/// Meta-arguments read from the body of a source block
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlockMeta {
+    pub has_count: bool,
+    pub has_for_each: bool,
+    /// Provider reference as written, e.g. `aws.west`
+    pub provider: Option<String>,
+    /// Comments above the source block, one per line without surrounding blank lines
+    pub comment: Option<String>,
+    /// Target module named by a `# moved_maker: module=<name>` comment above the block
+    pub target_module: Option<String>,
+}
+
+impl BlockMeta {
+    /// Read the meta-arguments from a block body
+    pub fn from_body(body: &Body) -> Self {
+        Self {
+            has_count: body.get_attribute("count").is_some(),
+            has_for_each: body.get_attribute("for_each").is_some(),
+            provider: body
+                .get_attribute("provider")
+                .map(|attr| attr.value.to_string().trim().to_string()),
+            comment: None,
+            target_module: None,
+        }
+    }
+
+    /// Read the meta-arguments and the leading comments of a block
+    ///
+    /// A module directive is taken out of the comments, so it is not copied to the output.
+    pub fn from_block(block: &Block) -> Self {
+        let mut target_module = None;
+        let mut lines = Vec::new();
+        if let Some(prefix) = block.decor().prefix() {
+            for line in prefix
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+            {
+                match module_directive(line) {
+                    Some(module_name) => target_module = Some(module_name.to_string()),
+                    None => lines.push(line),
+                }
+            }
+        }
+        let comment = (!lines.is_empty()).then(|| format!("{}\n", lines.join("\n")));
+        Self {
+            comment,
+            target_module,
+            ..Self::from_body(&block.body)
+        }
+    }
+
+    /// Whether the block has instances, so a single instance is addressed with a key
+    pub fn expects_instance_key(&self) -> bool {
+        self.has_count || self.has_for_each
+    }
+}
+
+/// Module name of a `# moved_maker: module=<name>` (or `//`) comment line
+fn module_directive(line: &str) -> Option<&str> {
+    line.strip_prefix('#')
+        .or_else(|| line.strip_prefix("//"))?
+        .trim()
+        .strip_prefix(MODULE_DIRECTIVE)?
+        .trim()
+        .strip_prefix("module=")
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn meta(hcl: &str) -> Result<BlockMeta> {
+        let body = hcl::edit::parser::parse_body(hcl)?;
+        let block = body.blocks().next().expect("Expected a block");
+        Ok(BlockMeta::from_body(&block.body))
+    }
+
+    #[test]
+    fn test_block_meta_plain() -> Result<()> {
+        assert_eq!(
+            meta(r#"resource "aws_instance" "web" {}"#)?,
+            BlockMeta::default()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_meta_provider() -> Result<()> {
+        let meta = meta("resource \"aws_instance\" \"web\" {\n  provider = aws.west\n}\n")?;
+        assert_eq!(meta.provider.as_deref(), Some("aws.west"));
+        assert!(!meta.has_count);
+        assert!(!meta.has_for_each);
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_meta_comment() -> Result<()> {
+        let body = hcl::edit::parser::parse_body(
+            "variable \"region\" {}\n\n# TODO: split per region\n  // owned by team-a\n\nresource \"aws_instance\" \"web\" {}\n",
+        )?;
+        let block = body.blocks().nth(1).expect("Expected a block");
+        assert_eq!(
+            BlockMeta::from_block(block).comment.as_deref(),
+            Some("# TODO: split per region\n// owned by team-a\n")
+        );
+
+        let block = body.blocks().next().expect("Expected a block");
+        assert_eq!(BlockMeta::from_block(block).comment, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_meta_module_directive() -> Result<()> {
+        let body = hcl::edit::parser::parse_body(
+            "# Shared VPC\n# moved_maker: module=network\nresource \"aws_vpc\" \"main\" {}\n",
+        )?;
+        let meta = BlockMeta::from_block(body.blocks().next().expect("Expected a block"));
+        assert_eq!(meta.target_module.as_deref(), Some("network"));
+        assert_eq!(meta.comment.as_deref(), Some("# Shared VPC\n"));
+
+        assert_eq!(
+            module_directive("//moved_maker:module=network"),
+            Some("network")
+        );
+        assert_eq!(module_directive("# moved_maker:ignore-file"), None);
+        Ok(())
+    }
+}
@@ -12,70 +12,878 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::Result;
-use clap::Parser;
+use crate::address::{parse_module_path, validate_module_name};
+use crate::block_meta::MissingKey;
+use crate::config::Config;
+use crate::diagnostics::{ErrorFormat, WarningKind};
+use crate::moved_block::{is_module_name_template, resolve_module_name_template};
+use crate::terraform_files::{Extensions, IgnoreGlobs};
+use crate::to_moved_block::{BlockType, CommentPath, CommentStyle, Direction};
+use crate::type_filter::{NameFilter, TypeFilter};
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Parser, Subcommand, ValueEnum};
+use regex::Regex;
 use std::path::PathBuf;
 
-#[derive(Parser, Debug)]
+/// `--src` value that reads the configuration from stdin
+pub const STDIN_SRC: &str = "-";
+
+/// File name standing in for the configuration read from stdin, e.g. in `# From: <stdin>`
+pub const STDIN_FILE_NAME: &str = "<stdin>";
+
+/// Output syntax for the generated moved blocks
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// HCL `moved` blocks
+    #[default]
+    Hcl,
+    /// Terraform JSON syntax (`.tf.json`)
+    Tfjson,
+    /// Shell script of `terraform state mv` commands
+    Statemv,
+    /// JSON array of `{"from", "to", "source_file", "kind"}` records for tooling
+    Json,
+}
+
+/// Address style of the `statemv` output
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AddressStyle {
+    /// Addresses as Terraform 1.x writes them
+    #[default]
+    Modern,
+    /// Reject addresses Terraform 0.11 cannot express (`for_each` keys, module instance keys)
+    Terraform11,
+}
+
+/// Order of the generated moved blocks
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Keep the order in which blocks were discovered
+    None,
+    /// Sort by `from` address
+    #[default]
+    Address,
+    /// Sort by source file, then by `from` address within each file
+    File,
+}
+
+/// Subcommands that work on existing files instead of generating moves
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Normalize an existing file of moved blocks in place: sort, deduplicate and reindent
+    Format {
+        /// File to rewrite, e.g. `moved.tf`
+        file: PathBuf,
+    },
+}
+
+#[derive(Parser, Debug, Default)]
 #[command(name = "moved_maker")]
 #[command(about = "Generate moved blocks for Terraform resources and data sources")]
+#[command(subcommand_negates_reqs = true)]
 pub struct Args {
-    /// Source directory containing Terraform files
-    #[arg(long)]
-    pub src: PathBuf,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Source directory containing Terraform files, or a single `.tf` file (repeatable); `-` reads HCL from stdin
+    #[arg(
+        long,
+        env = "MOVED_MAKER_SRC",
+        required_unless_present_any = ["json_schema", "from_plan", "src_stdin_list"],
+    )]
+    pub src: Vec<PathBuf>,
+
+    /// Also search subdirectories of the source directories (hidden ones, like `.terraform`, are skipped)
+    #[arg(long, env = "MOVED_MAKER_RECURSIVE")]
+    pub recursive: bool,
+
+    /// Comma-separated file extensions that count as Terraform files (default: `tf,tf.json`)
+    #[arg(
+        long,
+        env = "MOVED_MAKER_EXTENSIONS",
+        value_delimiter = ',',
+        value_name = "LIST"
+    )]
+    pub extensions: Vec<String>,
+
+    /// Skip discovered files whose path below `--src` matches this glob, e.g. `**/examples/*.tf` (repeatable)
+    #[arg(long, env = "MOVED_MAKER_IGNORE", value_name = "GLOB")]
+    pub ignore: Vec<String>,
+
+    /// Read newline-delimited file or directory paths to process from stdin, instead of `--src`
+    #[arg(
+        long,
+        env = "MOVED_MAKER_SRC_STDIN_LIST",
+        conflicts_with_all = ["src", "from_plan", "interactive"]
+    )]
+    pub src_stdin_list: bool,
+
+    /// Generate moves from a plan JSON file (`terraform show -json`) instead of `--src`
+    #[arg(
+        long,
+        env = "MOVED_MAKER_FROM_PLAN",
+        value_name = "FILE",
+        conflicts_with = "src"
+    )]
+    pub from_plan: Option<PathBuf>,
+
+    /// Warn about generated `from` addresses that are not in this state file
+    /// (`terraform.tfstate` or `terraform state pull` output); the state is only read
+    #[arg(long, env = "MOVED_MAKER_STATE", value_name = "FILE")]
+    pub state: Option<PathBuf>,
+
+    /// Name of the module to move resources/data into, `platform.compute` for a nested
+    /// module; `{file}` and `{dir}` are replaced by each block's source file stem and directory name
+    #[arg(long, env = "MOVED_MAKER_MODULE_NAME")]
+    pub module_name: Option<String>,
+
+    /// Module path the blocks currently live in, prepended to "from" addresses (default: root module)
+    #[arg(
+        long,
+        env = "MOVED_MAKER_PREFIX_FROM",
+        value_name = "PATH",
+        conflicts_with = "from_plan"
+    )]
+    pub prefix_from: Option<String>,
+
+    /// Module the blocks currently live in, e.g. `existing` for `module.existing.aws_instance.web`;
+    /// shorthand for `--prefix-from module.<name>`
+    #[arg(
+        long,
+        env = "MOVED_MAKER_SOURCE_MODULE",
+        value_name = "NAME",
+        conflicts_with_all = ["prefix_from", "from_plan"]
+    )]
+    pub source_module: Option<String>,
+
+    /// Module path the blocks move to, prepended to "to" addresses (default: `module.<module-name>`)
+    #[arg(long, env = "MOVED_MAKER_PREFIX_TO", value_name = "PATH")]
+    pub prefix_to: Option<String>,
+
+    /// Move blocks into the target module (default) or out of it, swapping "from" and "to"
+    #[arg(
+        long,
+        env = "MOVED_MAKER_DIRECTION",
+        value_enum,
+        default_value_t = Direction::Into,
+        conflicts_with_all = ["from_plan", "scaffold_module"]
+    )]
+    pub direction: Direction,
+
+    /// Warn when a resource/module name equals the target module name
+    #[arg(long, env = "MOVED_MAKER_WARN_NAME_COLLISION")]
+    pub warn_name_collision: bool,
+
+    /// Also move `data` blocks, which are ignored by default
+    #[arg(long, env = "MOVED_MAKER_INCLUDE_DATA")]
+    pub include_data: bool,
+
+    /// Write one file per source file into this directory instead of stdout
+    #[arg(long, env = "MOVED_MAKER_OUTPUT_DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Overwrite existing files in the output directory
+    #[arg(long, env = "MOVED_MAKER_FORCE", requires = "output_dir")]
+    pub force: bool,
+
+    /// Permissions of written output files, in octal (e.g. 664)
+    #[cfg(unix)]
+    #[arg(
+        long,
+        env = "MOVED_MAKER_OUTPUT_MODE",
+        value_name = "OCTAL",
+        value_parser = parse_octal_mode,
+    )]
+    pub output_mode: Option<u32>,
+
+    /// Order output by source file, then by `from` address within each file (same as `--sort file`)
+    #[arg(long, env = "MOVED_MAKER_GROUP_BY_FILE", conflicts_with = "sort")]
+    pub group_by_file: bool,
+
+    /// Put the output in sections per source directory, each under a `# Directory: <dir>` comment
+    #[arg(long, env = "MOVED_MAKER_GROUP_BY_DIR", conflicts_with = "canonical")]
+    pub group_by_dir: bool,
+
+    /// Order of the generated blocks
+    #[arg(
+        long,
+        env = "MOVED_MAKER_SORT",
+        value_enum,
+        default_value_t = SortOrder::Address
+    )]
+    pub sort: SortOrder,
+
+    /// Also emit the target `module` block, with `source = "./modules/<module-name>"` by default
+    #[arg(long, env = "MOVED_MAKER_SCAFFOLD_MODULE")]
+    pub scaffold_module: bool,
+
+    /// `source` of the scaffolded module block, a local path or registry reference
+    #[arg(
+        long,
+        env = "MOVED_MAKER_TARGET_SOURCE",
+        value_name = "PATH",
+        requires = "scaffold_module"
+    )]
+    pub target_source: Option<String>,
+
+    /// Also print the first N generated blocks to stderr
+    #[arg(long, env = "MOVED_MAKER_PREVIEW", value_name = "N")]
+    pub preview: Option<usize>,
+
+    /// Emit the canonical form: moves only, no comments, sorted by `from`
+    #[arg(
+        long,
+        env = "MOVED_MAKER_CANONICAL",
+        conflicts_with = "scaffold_module"
+    )]
+    pub canonical: bool,
+
+    /// Leave out the `# Generated by moved_maker` header above HCL output
+    #[arg(long, env = "MOVED_MAKER_NO_HEADER")]
+    pub no_header: bool,
+
+    /// Skip checking that the generated HCL parses back before it is written
+    #[arg(long, env = "MOVED_MAKER_NO_VALIDATE")]
+    pub no_validate: bool,
+
+    /// Output format (default: hcl); repeat together with `--output` to write several formats
+    #[arg(long, env = "MOVED_MAKER_FORMAT", visible_alias = "mode", value_enum)]
+    pub format: Vec<OutputFormat>,
+
+    /// Address style of the `statemv` output; `terraform11` fails on addresses Terraform 0.11 cannot run
+    #[arg(
+        long,
+        env = "MOVED_MAKER_ADDRESS_STYLE",
+        value_enum,
+        default_value_t = AddressStyle::Modern
+    )]
+    pub address_style: AddressStyle,
+
+    /// Write the output to this file instead of stdout; the n-th `--output` gets the n-th `--format`
+    #[arg(
+        long,
+        env = "MOVED_MAKER_OUTPUT",
+        value_name = "FILE",
+        conflicts_with = "output_dir"
+    )]
+    pub output: Vec<PathBuf>,
+
+    /// Fail instead of overwriting an existing `--output` file
+    #[arg(long, env = "MOVED_MAKER_NO_CLOBBER", requires = "output")]
+    pub no_clobber: bool,
+
+    /// Only move resources whose name matches this regex
+    #[arg(long, env = "MOVED_MAKER_RESOURCE_NAME_REGEX", value_name = "PATTERN")]
+    pub resource_name_regex: Option<String>,
+
+    /// Only move resources whose type matches this glob, e.g. `aws_*` (repeatable)
+    #[arg(long, env = "MOVED_MAKER_INCLUDE_TYPE", value_name = "GLOB")]
+    pub include_type: Vec<String>,
+
+    /// Do not move resources whose type matches this glob (repeatable, wins over `--include-type`)
+    #[arg(long, env = "MOVED_MAKER_EXCLUDE_TYPE", value_name = "GLOB")]
+    pub exclude_type: Vec<String>,
+
+    /// Do not move resources or modules whose name matches this glob, e.g. `*_legacy` (repeatable)
+    #[arg(long, env = "MOVED_MAKER_EXCLUDE_NAME", value_name = "GLOB")]
+    pub exclude_name: Vec<String>,
+
+    /// Only process files whose content matches this regex
+    #[arg(long, env = "MOVED_MAKER_CONTENT_MATCHES", value_name = "PATTERN")]
+    pub content_matches: Option<String>,
+
+    /// Read only the block headers of files that do not parse, e.g. with an unterminated heredoc
+    #[arg(long, env = "MOVED_MAKER_LENIENT_PARSE")]
+    pub lenient_parse: bool,
+
+    /// Print a summary of warnings grouped by reason to stderr at the end (always with `--verbose`)
+    #[arg(long, env = "MOVED_MAKER_SUMMARY")]
+    pub summary: bool,
+
+    /// Print how many moved blocks would be generated to stderr, without emitting them
+    #[arg(
+        long,
+        env = "MOVED_MAKER_DRY_RUN",
+        conflicts_with_all = ["output_dir", "output", "with_rollback", "interactive"]
+    )]
+    pub dry_run: bool,
+
+    /// Fail when no `.tf` files are found, e.g. because `--src` names the wrong directory
+    #[arg(long, env = "MOVED_MAKER_FAIL_ON_EMPTY", conflicts_with = "from_plan")]
+    pub fail_on_empty: bool,
+
+    /// Print only the number of moved blocks that would be generated to stdout, e.g. `12`
+    #[arg(
+        long,
+        env = "MOVED_MAKER_COUNT_ONLY",
+        conflicts_with_all = ["output_dir", "output", "with_rollback", "interactive", "dry_run", "preview"]
+    )]
+    pub count_only: bool,
+
+    /// Read and parse files on this many threads (default: 1); the output order does not depend on it
+    #[arg(long, env = "MOVED_MAKER_JOBS", value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Stop after emitting this many moved blocks
+    #[arg(long, env = "MOVED_MAKER_MAX_BLOCKS", value_name = "N")]
+    pub max_blocks: Option<usize>,
+
+    /// File with `old_type=new_type` lines renaming resource types in target addresses
+    #[arg(long, env = "MOVED_MAKER_RENAME_MAP", value_name = "FILE")]
+    pub rename_map: Option<PathBuf>,
 
-    /// Name of the module to move resources/data into
-    #[arg(long)]
-    pub module_name: String,
+    /// File with one address per line; only blocks whose `from` address is listed are moved
+    #[arg(
+        long,
+        env = "MOVED_MAKER_ONLY_FILE",
+        value_name = "FILE",
+        conflicts_with = "from_plan"
+    )]
+    pub only_file: Option<PathBuf>,
+
+    /// Only emit moves that are not already recorded in this snapshot of a prior output
+    #[arg(long, env = "MOVED_MAKER_ONLY_CHANGED", value_name = "SNAPSHOT")]
+    pub only_changed: Option<PathBuf>,
+
+    /// File of existing moved blocks; blocks whose target is already the `to` of one are skipped
+    #[arg(long, env = "MOVED_MAKER_EXISTING", value_name = "FILE")]
+    pub existing: Option<PathBuf>,
+
+    /// Append the new moves to this file, e.g. a hand-maintained `moved.tf`, skipping moves it already has
+    #[arg(
+        long,
+        env = "MOVED_MAKER_MERGE_INTO",
+        value_name = "FILE",
+        conflicts_with_all = ["output", "output_dir", "scaffold_module"]
+    )]
+    pub merge_into: Option<PathBuf>,
+
+    /// Compare against an existing moved file and print only the moves a re-run would add (`+`)
+    /// or drop (`-`), instead of the generated blocks
+    #[arg(
+        long,
+        env = "MOVED_MAKER_DIFF",
+        value_name = "FILE",
+        conflicts_with_all = ["output", "output_dir", "merge_into", "dry_run", "count_only"]
+    )]
+    pub diff: Option<PathBuf>,
+
+    /// Ledger file of previously emitted moves; recorded moves are skipped and new ones appended
+    #[arg(long, env = "MOVED_MAKER_LEDGER", value_name = "FILE")]
+    pub ledger: Option<PathBuf>,
+
+    /// Also write the reverse moves (`from` and `to` swapped) to this file, to undo the migration
+    #[arg(long, env = "MOVED_MAKER_WITH_ROLLBACK", value_name = "FILE")]
+    pub with_rollback: Option<PathBuf>,
+
+    /// Write a JSON report of resource/module blocks that did not produce a move
+    #[arg(long, env = "MOVED_MAKER_REPORT_UNMOVED", value_name = "FILE")]
+    pub report_unmoved: Option<PathBuf>,
+
+    /// Keep running and regenerate the `--output` files whenever a `.tf` file under `--src` changes
+    #[arg(
+        long,
+        env = "MOVED_MAKER_WATCH",
+        requires = "output",
+        conflicts_with_all = ["interactive", "from_plan", "src_stdin_list"]
+    )]
+    pub watch: bool,
+
+    /// Choose the blocks to move from an interactive checklist (requires a terminal)
+    #[arg(long, env = "MOVED_MAKER_INTERACTIVE")]
+    pub interactive: bool,
+
+    /// Include the block kind in comments, e.g. `# From: main.tf (resource)`
+    #[arg(long, env = "MOVED_MAKER_EMIT_COMMENT_KIND")]
+    pub emit_comment_kind: bool,
+
+    /// Add a `TODO: review this move` comment to moves that rename a type or collide with the target module
+    #[arg(long, env = "MOVED_MAKER_MARK_RISKY")]
+    pub mark_risky: bool,
+
+    /// How to handle `count`/`for_each` blocks, which have no known instance key:
+    /// skip them, emit a `["REPLACE"]` key, or fail (default: move the whole resource)
+    #[arg(
+        long,
+        env = "MOVED_MAKER_MISSING_KEY",
+        value_enum,
+        value_name = "POLICY"
+    )]
+    pub missing_key: Option<MissingKey>,
+
+    /// Comment syntax for the `From:` comment
+    #[arg(
+        long,
+        env = "MOVED_MAKER_COMMENT_STYLE",
+        value_enum,
+        default_value_t = CommentStyle::Hash,
+    )]
+    pub comment_style: CommentStyle,
+
+    /// How the source file is shown in the comment (default: relative with --recursive, else name)
+    #[arg(
+        long,
+        env = "MOVED_MAKER_COMMENT_PATH",
+        value_enum,
+        value_name = "STYLE"
+    )]
+    pub comment_path: Option<CommentPath>,
+
+    /// Text of the comment above each block instead of `From: <file>`, with `{file}`,
+    /// `{path}`, `{type}` and `{name}` placeholders, e.g. `moved from {file}`
+    #[arg(
+        long,
+        env = "MOVED_MAKER_COMMENT_TEMPLATE",
+        value_name = "TEMPLATE",
+        conflicts_with = "emit_comment_kind"
+    )]
+    pub comment_template: Option<String>,
+
+    /// Leave the generated blocks without any comment, including copied source comments
+    #[arg(
+        long,
+        env = "MOVED_MAKER_NO_COMMENT",
+        conflicts_with_all = ["comment_template", "emit_comment_kind", "mark_risky"]
+    )]
+    pub no_comment: bool,
+
+    /// Number of spaces to indent `from`/`to` with inside each block
+    #[arg(
+        long,
+        env = "MOVED_MAKER_INDENT",
+        value_name = "N",
+        default_value_t = 2
+    )]
+    pub indent: usize,
+
+    /// Indent with tabs instead of spaces
+    #[arg(long, env = "MOVED_MAKER_USE_TABS", conflicts_with = "indent")]
+    pub use_tabs: bool,
+
+    /// Kind of block to generate: `moved` into the target module, or `removed` to drop
+    /// the resources from state without destroying them
+    #[arg(
+        long,
+        env = "MOVED_MAKER_BLOCK_TYPE",
+        value_enum,
+        default_value_t = BlockType::Moved,
+    )]
+    pub block_type: BlockType,
+
+    /// Comma-separated warning categories to treat as errors
+    /// (discovery, parse, invalid-labels, unsupported, conversion, collision, rename, duplicate,
+    /// missing-key, degraded-parse, not-in-state)
+    #[arg(
+        long,
+        env = "MOVED_MAKER_ERROR_ON",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "CATEGORIES"
+    )]
+    pub error_on: Vec<WarningKind>,
+
+    /// Skip files and blocks that produce warnings and exit 0 with the remaining output (default)
+    #[arg(long, env = "MOVED_MAKER_BEST_EFFORT")]
+    pub best_effort: bool,
+
+    /// Process everything, but exit with an error and no output if any warning was reported
+    #[arg(long, env = "MOVED_MAKER_STRICT")]
+    pub strict: bool,
+
+    /// Stop at the first warning and exit with an error and no output
+    #[arg(long, env = "MOVED_MAKER_FAIL_FAST")]
+    pub fail_fast: bool,
+
+    /// Log each discovered file, parsed file and generated moved block to stderr
+    #[arg(short, long, env = "MOVED_MAKER_VERBOSE")]
+    pub verbose: bool,
+
+    /// Format of warnings and errors on stderr
+    #[arg(long, env = "MOVED_MAKER_ERROR_FORMAT", value_enum, default_value_t = ErrorFormat::Human)]
+    pub error_format: ErrorFormat,
+
+    /// Print the JSON Schema of the structured output and exit
+    #[arg(long, hide = true, exclusive = true)]
+    pub json_schema: bool,
 }
 
+/// Parse a file mode given in octal, e.g. `644` or `0o644`
+#[cfg(unix)]
+fn parse_octal_mode(value: &str) -> Result<u32> {
+    let digits = value.strip_prefix("0o").unwrap_or(value);
+    let mode =
+        u32::from_str_radix(digits, 8).with_context(|| format!("Invalid octal mode: {}", value))?;
+    if mode > 0o7777 {
+        anyhow::bail!("Octal mode out of range: {}", value);
+    }
+    Ok(mode)
+}
+
+/// Arguments rejected by `Args::validate`, exits with `ExitCode::Usage`
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct InvalidArgs(pub anyhow::Error);
+
+/// A `--src` path that does not exist, exits with `ExitCode::Discovery`
+#[derive(Debug, thiserror::Error)]
+#[error("Source directory does not exist: {}", .0.display())]
+pub struct MissingSource(pub PathBuf);
+
 impl Args {
     /// Validate arguments and return error on invalid input
+    ///
+    /// The error is an `InvalidArgs`, wrapping a `MissingSource` for a `--src`
+    /// path that does not exist.
     pub fn validate(&self) -> Result<()> {
-        // Validate src exists and is a directory
-        if !self.src.exists() {
-            anyhow::bail!("Source directory does not exist: {}", self.src.display());
+        self.check().map_err(|error| InvalidArgs(error).into())
+    }
+
+    /// Fill options not given on the command line or in the environment from a config file
+    ///
+    /// `matches` tells a defaulted `--indent` apart from one that was passed.
+    pub fn merge_config(&mut self, config: &Config, matches: &ArgMatches) {
+        if self.module_name.is_none() {
+            self.module_name = config.module_name.clone();
+        }
+        if !self.recursive {
+            self.recursive = config.recursive.unwrap_or_default();
+        }
+        if self.include_type.is_empty() {
+            self.include_type = config.include_type.clone().unwrap_or_default();
+        }
+        if self.exclude_type.is_empty() {
+            self.exclude_type = config.exclude_type.clone().unwrap_or_default();
+        }
+        if matches.value_source("indent") == Some(ValueSource::DefaultValue) {
+            self.indent = config.indent.unwrap_or(self.indent);
+        }
+    }
+
+    fn check(&self) -> Result<()> {
+        // Validate src exists and is a directory or Terraform file (or is `-` for stdin), or the
+        // plan file exists; paths listed on stdin are reported when they are read
+        if let Some(plan) = &self.from_plan {
+            if !plan.is_file() {
+                anyhow::bail!("Plan file does not exist: {}", plan.display());
+            }
+        } else if !self.src_stdin_list {
+            if self.src.is_empty() {
+                anyhow::bail!("Source directory is required");
+            }
+            if self.src_is_stdin() {
+                // The checklist would compete with the configuration for stdin
+                if self.interactive {
+                    anyhow::bail!("--src - cannot be combined with --interactive");
+                }
+                if self.watch {
+                    anyhow::bail!("--src - cannot be combined with --watch");
+                }
+            } else {
+                let extensions = self.terraform_extensions()?;
+                for src in &self.src {
+                    if src.as_os_str() == STDIN_SRC {
+                        anyhow::bail!("--src - cannot be combined with other --src paths");
+                    }
+                    if !src.exists() {
+                        return Err(MissingSource(src.clone()).into());
+                    }
+                    if !(src.is_dir() || (src.is_file() && extensions.is_match(src))) {
+                        anyhow::bail!(
+                            "Source path is not a directory or a Terraform file: {}",
+                            src.display()
+                        );
+                    }
+                }
+            }
+        }
+
+        // Validate at most one failure mode is selected
+        let modes = [self.best_effort, self.strict, self.fail_fast];
+        if modes.iter().filter(|enabled| **enabled).count() > 1 {
+            anyhow::bail!("--best-effort, --strict and --fail-fast are mutually exclusive");
+        }
+
+        // Validate module scaffolding options
+        if self.target_source.as_deref() == Some("") {
+            anyhow::bail!("--target-source cannot be empty");
+        }
+        let formats = self.formats();
+        let hcl_only = formats.iter().all(|format| *format == OutputFormat::Hcl);
+        if self.scaffold_module && !hcl_only {
+            anyhow::bail!("--scaffold-module is only supported with --format hcl");
+        }
+
+        if self.canonical && !hcl_only {
+            anyhow::bail!("--canonical is only supported with --format hcl");
+        }
+
+        if self.merge_into.is_some() && !hcl_only {
+            anyhow::bail!("--merge-into is only supported with --format hcl");
         }
-        if !self.src.is_dir() {
-            anyhow::bail!("Source path is not a directory: {}", self.src.display());
+
+        let statemv_only = formats
+            .iter()
+            .all(|format| *format == OutputFormat::Statemv);
+        if self.address_style != AddressStyle::Modern && !statemv_only {
+            anyhow::bail!("--address-style terraform11 is only supported with --format statemv");
         }
 
-        // Validate module_name is non-empty
-        if self.module_name.is_empty() {
-            anyhow::bail!("Module name cannot be empty");
+        if let Some(diff) = &self.diff {
+            if !diff.is_file() {
+                anyhow::bail!("Diff file does not exist: {}", diff.display());
+            }
+            if self.block_type == BlockType::Removed {
+                anyhow::bail!("--diff is only supported with --block-type moved");
+            }
         }
 
-        // Validate module_name is a valid Terraform identifier
-        // Must start with letter or underscore, followed by alphanumeric, underscore, or hyphen
-        let chars: Vec<char> = self.module_name.chars().collect();
-        if chars.is_empty() {
-            anyhow::bail!("Module name cannot be empty");
+        // Directory sections are comments, which only the HCL output has
+        if self.group_by_dir && !hcl_only {
+            anyhow::bail!("--group-by-dir is only supported with --format hcl");
+        }
+
+        // Removed blocks have no target, so only the HCL output without module or moves applies
+        if self.block_type == BlockType::Removed {
+            if !hcl_only {
+                anyhow::bail!("--block-type removed is only supported with --format hcl");
+            }
+            let unsupported = [
+                (self.scaffold_module, "--scaffold-module"),
+                (self.canonical, "--canonical"),
+                (self.with_rollback.is_some(), "--with-rollback"),
+                (self.ledger.is_some(), "--ledger"),
+            ];
+            if let Some((_, option)) = unsupported.iter().find(|(enabled, _)| *enabled) {
+                anyhow::bail!("{} cannot be combined with --block-type removed", option);
+            }
         }
 
-        let first_char = chars[0];
-        if !first_char.is_alphabetic() && first_char != '_' {
+        // Validate every format has its own output target
+        if self.output.is_empty() && formats.len() > 1 {
+            anyhow::bail!("Multiple --format values need one --output each");
+        }
+        if !self.output.is_empty() && self.output.len() != formats.len() {
             anyhow::bail!(
-                "Module name must start with a letter or underscore, got: {}",
-                first_char
+                "Got {} --output files for {} --format values, each format needs its own --output",
+                self.output.len(),
+                formats.len()
             );
         }
+        for (index, output) in self.output.iter().enumerate() {
+            if self.output[..index].contains(output) {
+                anyhow::bail!("--output {} is given more than once", output.display());
+            }
+            // Checked up front, so no output is written when one of them would fail
+            if let Some(parent) = output.parent()
+                && !parent.as_os_str().is_empty()
+                && !parent.is_dir()
+            {
+                anyhow::bail!("Output directory does not exist: {}", parent.display());
+            }
+            if self.no_clobber && output.exists() {
+                anyhow::bail!(
+                    "Output file already exists: {} (--no-clobber)",
+                    output.display()
+                );
+            }
+        }
 
-        for c in chars.iter().skip(1) {
-            if !c.is_alphanumeric() && *c != '_' && *c != '-' {
+        // Validate prefixes parse as module paths
+        if let Some(prefix) = &self.prefix_from {
+            parse_module_path(prefix).context("Invalid --prefix-from")?;
+        }
+        if let Some(prefix) = &self.prefix_to {
+            parse_module_path(prefix).context("Invalid --prefix-to")?;
+        }
+        if let Some(source_module) = &self.source_module {
+            validate_module_name(source_module).context("Invalid --source-module")?;
+            // `module.` is added by --source-module itself, a module address would be doubled
+            if source_module.split('.').any(|segment| segment == "module") {
                 anyhow::bail!(
-                    "Module name contains invalid character: {}. Only alphanumeric characters, underscores, and hyphens are allowed",
-                    c
+                    "Invalid --source-module '{}': give module names without `module.`, e.g. `existing` or `platform.network`",
+                    source_module
                 );
             }
         }
 
+        self.validate_module_name()?;
+
+        // A line break would end a `#` or `//` comment early
+        if self
+            .comment_template
+            .as_deref()
+            .is_some_and(|template| template.contains('\n'))
+        {
+            anyhow::bail!("--comment-template must be a single line");
+        }
+
+        if let Some(state) = &self.state
+            && !state.is_file()
+        {
+            anyhow::bail!("State file does not exist: {}", state.display());
+        }
+
+        // Validate max_blocks is positive
+        if self.max_blocks == Some(0) {
+            anyhow::bail!("--max-blocks must be greater than 0");
+        }
+
+        // Validate jobs is positive
+        if self.jobs == Some(0) {
+            anyhow::bail!("--jobs must be greater than 0");
+        }
+
+        // Validate preview is positive
+        if self.preview == Some(0) {
+            anyhow::bail!("--preview must be greater than 0");
+        }
+
+        // Validate resource_name_regex compiles
+        if let Some(pattern) = &self.resource_name_regex {
+            Regex::new(pattern)
+                .with_context(|| format!("Invalid --resource-name-regex: {}", pattern))?;
+        }
+
+        // Validate include_type/exclude_type, exclude_name and ignore globs compile
+        TypeFilter::new(&self.include_type, &self.exclude_type)?;
+        NameFilter::new(&self.exclude_name)?;
+        IgnoreGlobs::new(&self.ignore)?;
+
+        // Validate content_matches compiles
+        if let Some(pattern) = &self.content_matches {
+            Regex::new(pattern)
+                .with_context(|| format!("Invalid --content-matches: {}", pattern))?;
+        }
+
         Ok(())
     }
+
+    /// Whether the configuration is read from stdin, with `--src -`
+    pub fn src_is_stdin(&self) -> bool {
+        matches!(self.src.as_slice(), [src] if src.as_os_str() == STDIN_SRC)
+    }
+
+    /// Order of the generated blocks, `--group-by-file` being `--sort file`
+    pub fn sort_order(&self) -> SortOrder {
+        if self.group_by_file {
+            SortOrder::File
+        } else {
+            self.sort
+        }
+    }
+
+    /// Extensions of the files to search for; `tf` and `tf.json` when none are given
+    pub fn terraform_extensions(&self) -> Result<Extensions> {
+        if self.extensions.is_empty() {
+            Ok(Extensions::default())
+        } else {
+            Extensions::new(&self.extensions)
+        }
+    }
+
+    /// Whether HCL blocks are written to stdout as they are generated instead of collected first
+    ///
+    /// Only when nothing needs every block before writing: no sorting, header, interactive
+    /// selection or canonical form, no output files, and no option that fails a run
+    /// "without output" after the blocks were generated.
+    pub fn streams_output(&self) -> bool {
+        self.sort_order() == SortOrder::None
+            && self.formats() == [OutputFormat::Hcl]
+            && self.no_header
+            && self.output.is_empty()
+            && self.output_dir.is_none()
+            && self.merge_into.is_none()
+            && self.diff.is_none()
+            && self.preview.is_none()
+            && self.with_rollback.is_none()
+            && self.ledger.is_none()
+            && self.error_on.is_empty()
+            && self.missing_key != Some(MissingKey::Error)
+            && !(self.interactive
+                || self.dry_run
+                || self.count_only
+                || self.canonical
+                || self.group_by_dir
+                || self.scaffold_module
+                || self.strict
+                || self.fail_fast)
+    }
+
+    /// Files and directories a run writes to, which `--watch` must not react to
+    pub fn written_paths(&self) -> Vec<PathBuf> {
+        self.output
+            .iter()
+            .chain(&self.output_dir)
+            .chain(&self.merge_into)
+            .chain(&self.with_rollback)
+            .chain(&self.ledger)
+            .chain(&self.report_unmoved)
+            .cloned()
+            .collect()
+    }
+
+    /// Permissions for written output files, `None` for the platform default
+    ///
+    /// Always `None` where `--output-mode` is not supported.
+    pub fn output_file_mode(&self) -> Option<u32> {
+        #[cfg(unix)]
+        {
+            self.output_mode
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    }
+
+    /// Requested output formats, in `--format` order; `hcl` when none is given
+    pub fn formats(&self) -> Vec<OutputFormat> {
+        if self.format.is_empty() {
+            vec![OutputFormat::default()]
+        } else {
+            self.format.clone()
+        }
+    }
+
+    /// Validate the module name; it may only be omitted when `--prefix-to` is given
+    fn validate_module_name(&self) -> Result<()> {
+        if self.module_name.is_none() {
+            if self.prefix_to.is_some() {
+                return Ok(());
+            }
+            anyhow::bail!(
+                "--module-name is required, unless --prefix-to is given or module_name is set in {}",
+                crate::config::CONFIG_FILE_NAME
+            );
+        }
+
+        let module_name = self.module_name.as_deref().unwrap_or_default();
+        if !is_module_name_template(module_name) {
+            return validate_module_name(module_name);
+        }
+
+        // Templates are resolved per block, so there is no single target module
+        if self.prefix_from.is_some() || self.prefix_to.is_some() || self.source_module.is_some() {
+            anyhow::bail!(
+                "--module-name placeholders cannot be combined with --prefix-from, --prefix-to or --source-module"
+            );
+        }
+        if self.scaffold_module {
+            anyhow::bail!("--module-name placeholders cannot be combined with --scaffold-module");
+        }
+        if self.from_plan.is_some() {
+            anyhow::bail!("--module-name placeholders cannot be combined with --from-plan");
+        }
+        // Check the static parts; the resolved name is validated again per block
+        validate_module_name(&resolve_module_name_template(module_name, "x", "x"))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clap::{CommandFactory, FromArgMatches};
     use pretty_assertions::assert_eq;
     use std::fs;
     use tempfile::TempDir;
@@ -84,18 +892,20 @@ mod tests {
     fn test_args_struct_creation() {
         let temp_dir = TempDir::new().unwrap();
         let args = Args {
-            src: temp_dir.path().to_path_buf(),
-            module_name: "test_module".to_string(),
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            ..Default::default()
         };
-        assert_eq!(args.module_name, "test_module");
+        assert_eq!(args.module_name.as_deref(), Some("test_module"));
     }
 
     #[test]
     fn test_valid_cli_arguments() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
         let args = Args {
-            src: temp_dir.path().to_path_buf(),
-            module_name: "test_module".to_string(),
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            ..Default::default()
         };
         args.validate()?;
         Ok(())
@@ -104,8 +914,9 @@ mod tests {
     #[test]
     fn test_missing_src_argument() {
         let args = Args {
-            src: PathBuf::from("/nonexistent/path"),
-            module_name: "test_module".to_string(),
+            src: vec![PathBuf::from("/nonexistent/path")],
+            module_name: Some("test_module".to_string()),
+            ..Default::default()
         };
         let result = args.validate();
         assert!(result.is_err());
@@ -113,12 +924,103 @@ mod tests {
         assert!(error_msg.contains("Source directory does not exist"));
     }
 
+    #[test]
+    fn test_module_name_required() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("--module-name is required"));
+    }
+
+    #[test]
+    fn test_merge_config_fills_unset_options() -> Result<()> {
+        let config = Config::parse(
+            "module_name = \"compute\"\nrecursive = true\nexclude_type = [\"aws_iam_*\"]\nindent = 4\n",
+        )?;
+        let matches = Args::command().try_get_matches_from(["moved_maker", "--src", "."])?;
+        let mut args = Args::from_arg_matches(&matches)?;
+        args.merge_config(&config, &matches);
+        assert_eq!(args.module_name.as_deref(), Some("compute"));
+        assert!(args.recursive);
+        assert_eq!(args.exclude_type, vec!["aws_iam_*".to_string()]);
+        assert_eq!(args.indent, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_config_command_line_wins() -> Result<()> {
+        let config = Config::parse("module_name = \"compute\"\nindent = 4\n")?;
+        let matches = Args::command().try_get_matches_from([
+            "moved_maker",
+            "--src",
+            ".",
+            "--module-name",
+            "network",
+            "--indent",
+            "2",
+        ])?;
+        let mut args = Args::from_arg_matches(&matches)?;
+        args.merge_config(&config, &matches);
+        assert_eq!(args.module_name.as_deref(), Some("network"));
+        assert_eq!(args.indent, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_written_paths() {
+        let args = Args {
+            output: vec![PathBuf::from("moved.tf")],
+            with_rollback: Some(PathBuf::from("src/rollback.tf")),
+            ledger: Some(PathBuf::from("ledger.json")),
+            ..Default::default()
+        };
+        assert_eq!(
+            args.written_paths(),
+            vec![
+                PathBuf::from("moved.tf"),
+                PathBuf::from("src/rollback.tf"),
+                PathBuf::from("ledger.json")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_streams_output_only_without_sorting() {
+        let args = Args {
+            module_name: Some("test_module".to_string()),
+            sort: SortOrder::None,
+            no_header: true,
+            ..Default::default()
+        };
+        assert!(args.streams_output());
+        assert!(
+            !Args {
+                sort: SortOrder::Address,
+                no_header: true,
+                ..Default::default()
+            }
+            .streams_output()
+        );
+        // --missing-key error fails the run after earlier blocks would have been written
+        assert!(
+            !Args {
+                missing_key: Some(MissingKey::Error),
+                ..args
+            }
+            .streams_output()
+        );
+    }
+
     #[test]
     fn test_missing_module_name_argument() {
         let temp_dir = TempDir::new().unwrap();
         let args = Args {
-            src: temp_dir.path().to_path_buf(),
-            module_name: String::new(),
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some(String::new()),
+            ..Default::default()
         };
         let result = args.validate();
         assert!(result.is_err());
@@ -133,8 +1035,9 @@ mod tests {
         fs::write(&file_path, "test").unwrap();
 
         let args = Args {
-            src: file_path,
-            module_name: "test_module".to_string(),
+            src: vec![file_path],
+            module_name: Some("test_module".to_string()),
+            ..Default::default()
         };
         let result = args.validate();
         assert!(result.is_err());
@@ -142,12 +1045,46 @@ mod tests {
         assert!(error_msg.contains("Source path is not a directory"));
     }
 
+    #[test]
+    fn test_single_file_path() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("main.tf");
+        fs::write(&file_path, "").unwrap();
+
+        let args = Args {
+            src: vec![file_path],
+            module_name: Some("test_module".to_string()),
+            ..Default::default()
+        };
+        args.validate()
+    }
+
+    #[test]
+    fn test_stdin_src() -> Result<()> {
+        let args = Args {
+            src: vec![PathBuf::from(STDIN_SRC)],
+            module_name: Some("test_module".to_string()),
+            ..Default::default()
+        };
+        args.validate()?;
+
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![PathBuf::from(STDIN_SRC), temp_dir.path().to_path_buf()],
+            ..args
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("--src - cannot be combined with other --src paths"));
+        Ok(())
+    }
+
     #[test]
     fn test_module_name_starts_with_number() {
         let temp_dir = TempDir::new().unwrap();
         let args = Args {
-            src: temp_dir.path().to_path_buf(),
-            module_name: "123invalid".to_string(),
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("123invalid".to_string()),
+            ..Default::default()
         };
         let result = args.validate();
         assert!(result.is_err());
@@ -159,8 +1096,9 @@ mod tests {
     fn test_module_name_with_invalid_characters() {
         let temp_dir = TempDir::new().unwrap();
         let args = Args {
-            src: temp_dir.path().to_path_buf(),
-            module_name: "test@module".to_string(),
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test@module".to_string()),
+            ..Default::default()
         };
         let result = args.validate();
         assert!(result.is_err());
@@ -168,6 +1106,23 @@ mod tests {
         assert!(error_msg.contains("Module name contains invalid character"));
     }
 
+    #[test]
+    fn test_nested_module_name_rejects_empty_segment() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let args = |name: &str| Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some(name.to_string()),
+            ..Default::default()
+        };
+        args("platform.compute").validate()?;
+        let error_msg = args("platform..compute")
+            .validate()
+            .unwrap_err()
+            .to_string();
+        assert!(error_msg.contains("Module name contains an empty segment"));
+        Ok(())
+    }
+
     #[test]
     fn test_valid_module_name_formats() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
@@ -181,11 +1136,405 @@ mod tests {
         ];
         for name in valid_names {
             let args = Args {
-                src: temp_dir.path().to_path_buf(),
-                module_name: name.to_string(),
+                src: vec![temp_dir.path().to_path_buf()],
+                module_name: Some(name.to_string()),
+                ..Default::default()
             };
             args.validate()?;
         }
         Ok(())
     }
+
+    #[test]
+    fn test_invalid_resource_name_regex() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            resource_name_regex: Some("web(".to_string()),
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Invalid --resource-name-regex"));
+    }
+
+    #[test]
+    fn test_invalid_exclude_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            exclude_type: vec!["google_*".to_string(), "aws_{s3".to_string()],
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Invalid --exclude-type: aws_{s3"));
+    }
+
+    #[test]
+    fn test_invalid_ignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            ignore: vec!["examples/{a".to_string()],
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("Invalid --ignore: examples/{a"));
+    }
+
+    #[test]
+    fn test_max_blocks_zero() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            max_blocks: Some(0),
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("--max-blocks must be greater than 0"));
+    }
+
+    #[test]
+    fn test_address_style_requires_statemv() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            address_style: AddressStyle::Terraform11,
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(
+            error_msg
+                .contains("--address-style terraform11 is only supported with --format statemv")
+        );
+
+        let args = Args {
+            format: vec![OutputFormat::Statemv],
+            ..args
+        };
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_block_type_removed_requires_hcl() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            block_type: BlockType::Removed,
+            format: vec![OutputFormat::Statemv],
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("--block-type removed is only supported with --format hcl"));
+
+        let args = Args {
+            format: Vec::new(),
+            with_rollback: Some(temp_dir.path().join("rollback.tf")),
+            ..args
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("--with-rollback cannot be combined with --block-type removed"));
+    }
+
+    #[test]
+    fn test_group_by_dir_requires_hcl() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            group_by_dir: true,
+            format: vec![OutputFormat::Json],
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("--group-by-dir is only supported with --format hcl"));
+    }
+
+    #[test]
+    fn test_comment_template_single_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            comment_template: Some("moved from {file}\nmoved {".to_string()),
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("--comment-template must be a single line"));
+    }
+
+    #[test]
+    fn test_state_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            state: Some(PathBuf::from("/nonexistent/terraform.tfstate")),
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("State file does not exist"));
+    }
+
+    #[test]
+    fn test_jobs_zero() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            jobs: Some(0),
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("--jobs must be greater than 0"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_octal_mode() -> Result<()> {
+        assert_eq!(parse_octal_mode("664")?, 0o664);
+        assert_eq!(parse_octal_mode("0o640")?, 0o640);
+        assert_eq!(parse_octal_mode("0755")?, 0o755);
+        assert!(parse_octal_mode("8").is_err());
+        assert!(parse_octal_mode("17777").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_plan_missing_file() {
+        let args = Args {
+            from_plan: Some(PathBuf::from("/nonexistent/plan.json")),
+            module_name: Some("test_module".to_string()),
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Plan file does not exist"));
+    }
+
+    #[test]
+    fn test_invalid_content_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            content_matches: Some("provider(".to_string()),
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Invalid --content-matches"));
+    }
+
+    #[test]
+    fn test_invalid_prefix_to() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            prefix_to: Some("aws_instance.web".to_string()),
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Invalid --prefix-to"));
+    }
+
+    #[test]
+    fn test_invalid_source_module() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            source_module: Some("module.existing".to_string()),
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("Invalid --source-module"));
+    }
+
+    #[test]
+    fn test_prefix_to_without_module_name() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            prefix_from: Some("module.old".to_string()),
+            prefix_to: Some(String::new()),
+            ..Default::default()
+        };
+        args.validate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_failure_modes_are_mutually_exclusive() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            strict: true,
+            fail_fast: true,
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("mutually exclusive"));
+    }
+
+    #[test]
+    fn test_empty_target_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            scaffold_module: true,
+            target_source: Some(String::new()),
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("--target-source cannot be empty"));
+    }
+
+    #[test]
+    fn test_preview_zero() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            preview: Some(0),
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("--preview must be greater than 0"));
+    }
+
+    #[test]
+    fn test_canonical_requires_hcl() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            canonical: true,
+            format: vec![OutputFormat::Tfjson],
+            ..Default::default()
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("--canonical is only supported with --format hcl"));
+    }
+
+    #[test]
+    fn test_module_name_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("mod_{dir}".to_string()),
+            ..Default::default()
+        };
+        assert!(args.validate().is_ok());
+
+        let args = Args {
+            module_name: Some("1{file}".to_string()),
+            ..args
+        };
+        assert!(args.validate().is_err());
+
+        let args = Args {
+            module_name: Some("mod_{dir}".to_string()),
+            prefix_from: Some("module.old".to_string()),
+            ..args
+        };
+        let result = args.validate();
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("cannot be combined with --prefix-from"));
+    }
+
+    #[test]
+    fn test_formats_need_distinct_outputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            format: vec![OutputFormat::Hcl, OutputFormat::Tfjson],
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("need one --output each"));
+
+        let args = Args {
+            output: vec![PathBuf::from("moved.tf")],
+            ..args
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("Got 1 --output files for 2 --format values"));
+
+        let args = Args {
+            output: vec![PathBuf::from("moved.tf"), PathBuf::from("moved.tf")],
+            ..args
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("--output moved.tf is given more than once"));
+
+        let args = Args {
+            output: vec![PathBuf::from("moved.tf"), PathBuf::from("moved.tf.json")],
+            ..args
+        };
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_output_parent_must_exist() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            output: vec![temp_dir.path().join("missing").join("moved.tf")],
+            ..Default::default()
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("Output directory does not exist"));
+    }
+
+    #[test]
+    fn test_output_no_clobber() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let existing = temp_dir.path().join("moved.tf");
+        fs::write(&existing, "")?;
+        let args = Args {
+            src: vec![temp_dir.path().to_path_buf()],
+            module_name: Some("test_module".to_string()),
+            output: vec![existing],
+            ..Default::default()
+        };
+        // Existing files are overwritten by default
+        assert!(args.validate().is_ok());
+
+        let args = Args {
+            no_clobber: true,
+            ..args
+        };
+        let error_msg = args.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("already exists"));
+        Ok(())
+    }
 }
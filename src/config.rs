@@ -0,0 +1,112 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Project-level defaults from a `.moved_maker.toml` file.
+//!
+//! The file is looked up in the `--src` directories, then in the current
+//! directory. Options given on the command line or through environment
+//! variables win over the file, see `Args::merge_config`.
+//!
+//! ```toml
+//! module_name = "compute"
+//! recursive = true
+//! exclude_type = ["aws_iam_*"]
+//! indent = 4
+//! ```
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the config file
+pub const CONFIG_FILE_NAME: &str = ".moved_maker.toml";
+
+/// Defaults for command-line options; unset fields leave the option alone
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub module_name: Option<String>,
+    pub recursive: Option<bool>,
+    pub include_type: Option<Vec<String>>,
+    pub exclude_type: Option<Vec<String>>,
+    pub indent: Option<usize>,
+}
+
+impl Config {
+    /// Read and parse a config file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config: {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+    }
+
+    /// Parse TOML config content
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Config file in the first of `srcs` that has one, or in the current directory
+    pub fn find(srcs: &[PathBuf]) -> Option<PathBuf> {
+        srcs.iter()
+            .filter(|src| src.is_dir())
+            .map(PathBuf::as_path)
+            .chain(std::iter::once(Path::new(".")))
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_config_parse() -> Result<()> {
+        let config = Config::parse(
+            "module_name = \"compute\"\nrecursive = true\nexclude_type = [\"aws_iam_*\"]\nindent = 4\n",
+        )?;
+        assert_eq!(
+            config,
+            Config {
+                module_name: Some("compute".to_string()),
+                recursive: Some(true),
+                include_type: None,
+                exclude_type: Some(vec!["aws_iam_*".to_string()]),
+                indent: Some(4),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_parse_unknown_option() {
+        assert!(Config::parse("modul_name = \"compute\"\n").is_err());
+    }
+
+    #[test]
+    fn test_config_find_in_src() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config = temp_dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&config, "recursive = true\n")?;
+        let other = TempDir::new()?;
+        assert_eq!(
+            Config::find(&[other.path().to_path_buf(), temp_dir.path().to_path_buf()]),
+            Some(config)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,499 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collection of non-fatal warnings raised while processing.
+//!
+//! `Diagnostics` prints warnings as they happen and tallies them by
+//! `WarningKind`, so a grouped summary can be reported once the run completes.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How warnings and fatal errors are written to stderr
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// `Warning: ...` / `Error: ...` lines
+    #[default]
+    Human,
+    /// One JSON object per line: `{"level", "category", "file", "message"}`
+    Json,
+}
+
+/// Category of a non-fatal warning
+///
+/// The `value` names are the category names accepted by `--error-on`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// A file could not be discovered
+    #[value(name = "discovery")]
+    DiscoveryFailed,
+    /// A file could not be read or parsed as HCL
+    #[value(name = "parse")]
+    ParseFailed,
+    /// A resource/module block had invalid labels
+    #[value(name = "invalid-labels")]
+    InvalidBlock,
+    /// A block type that does not produce moved blocks (provider, data without `--include-data`, ...);
+    /// `variable`, `output`, `moved` and the other known non-movable types are not counted
+    #[value(name = "unsupported")]
+    UnsupportedBlock,
+    /// A moved block could not be converted to HCL
+    #[value(name = "conversion")]
+    ConversionFailed,
+    /// A resource/module name equals the target module name
+    #[value(name = "collision")]
+    NameCollision,
+    /// A resource type is renamed in the target address
+    #[value(name = "rename")]
+    TypeRename,
+    /// The same source address is defined in more than one file
+    #[value(name = "duplicate")]
+    DuplicateAddress,
+    /// A `count`/`for_each` block was skipped because no instance key is known
+    #[value(name = "missing-key")]
+    MissingKey,
+    /// A file did not parse and only its block headers were read (`--lenient-parse`)
+    #[value(name = "degraded-parse")]
+    DegradedParse,
+    /// A generated `from` address is not in the `--state` file
+    #[value(name = "not-in-state")]
+    NotInState,
+}
+
+impl WarningKind {
+    /// Describe `count` occurrences of this kind for the summary
+    pub fn describe(self, count: usize) -> String {
+        let plural = |singular: &str, plural: &str| {
+            if count == 1 {
+                singular.to_string()
+            } else {
+                plural.to_string()
+            }
+        };
+        match self {
+            WarningKind::DiscoveryFailed => {
+                format!(
+                    "{} {} could not be discovered",
+                    count,
+                    plural("file", "files")
+                )
+            }
+            WarningKind::ParseFailed => {
+                format!("{} {} failed to parse", count, plural("file", "files"))
+            }
+            WarningKind::InvalidBlock => format!(
+                "{} {} skipped (invalid labels)",
+                count,
+                plural("block", "blocks")
+            ),
+            WarningKind::UnsupportedBlock => {
+                format!("{} unsupported block {}", count, plural("type", "types"))
+            }
+            WarningKind::ConversionFailed => {
+                format!("{} {} failed to convert", count, plural("block", "blocks"))
+            }
+            WarningKind::NameCollision => format!(
+                "{} name {} with the target module",
+                count,
+                plural("collision", "collisions")
+            ),
+            WarningKind::TypeRename => {
+                format!("{} resource type {}", count, plural("rename", "renames"))
+            }
+            WarningKind::DuplicateAddress => format!(
+                "{} {} defined in multiple files",
+                count,
+                plural("address", "addresses")
+            ),
+            WarningKind::MissingKey => format!(
+                "{} {} skipped (no instance key)",
+                count,
+                plural("block", "blocks")
+            ),
+            WarningKind::DegradedParse => format!(
+                "{} {} read for block headers only",
+                count,
+                plural("file", "files")
+            ),
+            WarningKind::NotInState => {
+                format!("{} {} not in state", count, plural("address", "addresses"))
+            }
+        }
+    }
+
+    /// Category name as accepted by `--error-on`, e.g. "parse"
+    pub fn category(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Severity of a stderr line
+#[derive(Debug, Clone, Copy)]
+enum Level {
+    Warning,
+    Error,
+}
+
+/// Format one stderr line in the given format
+///
+/// `category` and `file` are `null` in JSON when not known.
+fn format_line(
+    format: ErrorFormat,
+    level: Level,
+    category: Option<String>,
+    file: Option<&Path>,
+    message: &str,
+) -> String {
+    match format {
+        ErrorFormat::Human => match level {
+            Level::Warning => format!("Warning: {}", message),
+            Level::Error => format!("Error: {}", message),
+        },
+        ErrorFormat::Json => json!({
+            "level": match level {
+                Level::Warning => "warning",
+                Level::Error => "error",
+            },
+            "category": category,
+            "file": file.map(|file| file.display().to_string()),
+            "message": message,
+        })
+        .to_string(),
+    }
+}
+
+/// Print a fatal error to stderr
+pub fn report_error(format: ErrorFormat, error: &anyhow::Error) {
+    eprintln!(
+        "{}",
+        format_line(format, Level::Error, None, None, &format!("{:#}", error))
+    );
+}
+
+/// Fatal error for a run where warnings left no moved blocks to write
+///
+/// The binary exits with `ExitCode::PartialFailure` (4) for this error, so scripts
+/// can tell "nothing usable was found" apart from invalid arguments or I/O failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NothingGenerated {
+    pub failed_files: usize,
+}
+
+impl std::fmt::Display for NothingGenerated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No moved blocks generated; {} {} failed",
+            self.failed_files,
+            if self.failed_files == 1 {
+                "file"
+            } else {
+                "files"
+            }
+        )
+    }
+}
+
+impl std::error::Error for NothingGenerated {}
+
+/// Collects warnings by kind
+#[derive(Debug, Default, Clone)]
+pub struct Diagnostics {
+    counts: BTreeMap<WarningKind, usize>,
+    format: ErrorFormat,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collector that prints warnings in `format`
+    pub fn with_format(format: ErrorFormat) -> Self {
+        Self {
+            format,
+            ..Self::default()
+        }
+    }
+
+    /// Print a warning to stderr and record it
+    pub fn warn(&mut self, kind: WarningKind, message: impl std::fmt::Display) {
+        self.emit(kind, None, &message.to_string());
+    }
+
+    /// Print a warning about `file` to stderr and record it
+    pub fn warn_in(&mut self, kind: WarningKind, file: &Path, message: impl std::fmt::Display) {
+        self.emit(kind, Some(file), &message.to_string());
+    }
+
+    fn emit(&mut self, kind: WarningKind, file: Option<&Path>, message: &str) {
+        eprintln!(
+            "{}",
+            format_line(
+                self.format,
+                Level::Warning,
+                Some(kind.category()),
+                file,
+                message
+            )
+        );
+        self.record(kind);
+    }
+
+    /// Record an occurrence without printing it
+    pub fn record(&mut self, kind: WarningKind) {
+        *self.counts.entry(kind).or_insert(0) += 1;
+    }
+
+    /// Number of recorded occurrences of `kind`
+    pub fn count(&self, kind: WarningKind) -> usize {
+        self.counts.get(&kind).copied().unwrap_or(0)
+    }
+
+    /// Recorded occurrences per kind
+    pub fn counts(&self) -> &BTreeMap<WarningKind, usize> {
+        &self.counts
+    }
+
+    /// Add all counts from `other`
+    pub fn merge(&mut self, other: &Diagnostics) {
+        for (kind, count) in &other.counts {
+            *self.counts.entry(*kind).or_insert(0) += count;
+        }
+    }
+
+    /// Fail if any of the `promoted` kinds was recorded
+    ///
+    /// Used to treat selected warning categories as fatal (`--error-on`).
+    pub fn check_promoted(&self, promoted: &[WarningKind]) -> Result<()> {
+        let failures: Vec<String> = promoted
+            .iter()
+            .filter(|kind| self.count(**kind) > 0)
+            .map(|kind| kind.describe(self.count(*kind)))
+            .collect();
+        if !failures.is_empty() {
+            anyhow::bail!("{} (promoted to error by --error-on)", failures.join(", "));
+        }
+        Ok(())
+    }
+
+    /// Fail if any warning was printed
+    ///
+    /// Unsupported blocks are only counted, never printed, so they do not fail.
+    /// `option` names the flag that asked for this check, e.g. "--strict".
+    pub fn check_no_warnings(&self, option: &str) -> Result<()> {
+        let failures: Vec<String> = self
+            .counts
+            .iter()
+            .filter(|(kind, _)| **kind != WarningKind::UnsupportedBlock)
+            .map(|(kind, count)| kind.describe(*count))
+            .collect();
+        if !failures.is_empty() {
+            anyhow::bail!("{} ({})", failures.join(", "), option);
+        }
+        Ok(())
+    }
+
+    /// Whether any warning was printed (unsupported blocks are only counted)
+    pub fn has_warnings(&self) -> bool {
+        self.counts
+            .iter()
+            .any(|(kind, count)| *kind != WarningKind::UnsupportedBlock && *count > 0)
+    }
+
+    /// Number of files that could not be discovered or parsed
+    pub fn failed_files(&self) -> usize {
+        self.count(WarningKind::DiscoveryFailed) + self.count(WarningKind::ParseFailed)
+    }
+
+    /// Fail with `NothingGenerated` if no blocks were produced and warnings were printed
+    pub fn check_produced(&self, produced: usize) -> Result<()> {
+        if produced == 0 && self.has_warnings() {
+            return Err(NothingGenerated {
+                failed_files: self.failed_files(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Grouped one-line summary, e.g. "3 files failed to parse, 1 unsupported block type"
+    pub fn summary(&self) -> String {
+        if self.counts.is_empty() {
+            return "no warnings".to_string();
+        }
+        self.counts
+            .iter()
+            .map(|(kind, count)| kind.describe(*count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_line_human() {
+        assert_eq!(
+            format_line(
+                ErrorFormat::Human,
+                Level::Warning,
+                Some(WarningKind::ParseFailed.category()),
+                Some(Path::new("main.tf")),
+                "Failed to parse main.tf"
+            ),
+            "Warning: Failed to parse main.tf"
+        );
+        assert_eq!(
+            format_line(ErrorFormat::Human, Level::Error, None, None, "boom"),
+            "Error: boom"
+        );
+    }
+
+    #[test]
+    fn test_format_line_json() -> Result<()> {
+        let line = format_line(
+            ErrorFormat::Json,
+            Level::Warning,
+            Some(WarningKind::ParseFailed.category()),
+            Some(Path::new("main.tf")),
+            "Failed to parse main.tf",
+        );
+        let value: serde_json::Value = serde_json::from_str(&line)?;
+        assert_eq!(
+            value,
+            json!({
+                "level": "warning",
+                "category": "parse",
+                "file": "main.tf",
+                "message": "Failed to parse main.tf",
+            })
+        );
+
+        let line = format_line(ErrorFormat::Json, Level::Error, None, None, "boom");
+        let value: serde_json::Value = serde_json::from_str(&line)?;
+        assert_eq!(
+            value,
+            json!({ "level": "error", "category": null, "file": null, "message": "boom" })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagnostics_empty_summary() {
+        let diagnostics = Diagnostics::new();
+        assert_eq!(diagnostics.summary(), "no warnings");
+    }
+
+    #[test]
+    fn test_diagnostics_counts_by_kind() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.record(WarningKind::ParseFailed);
+        diagnostics.record(WarningKind::ParseFailed);
+        diagnostics.record(WarningKind::InvalidBlock);
+        assert_eq!(diagnostics.count(WarningKind::ParseFailed), 2);
+        assert_eq!(diagnostics.count(WarningKind::InvalidBlock), 1);
+        assert_eq!(diagnostics.count(WarningKind::UnsupportedBlock), 0);
+    }
+
+    #[test]
+    fn test_diagnostics_summary_grouped_and_pluralized() {
+        let mut diagnostics = Diagnostics::new();
+        for _ in 0..3 {
+            diagnostics.record(WarningKind::ParseFailed);
+        }
+        for _ in 0..2 {
+            diagnostics.record(WarningKind::InvalidBlock);
+        }
+        diagnostics.record(WarningKind::UnsupportedBlock);
+        assert_eq!(
+            diagnostics.summary(),
+            "3 files failed to parse, 2 blocks skipped (invalid labels), 1 unsupported block type"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_check_promoted() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.record(WarningKind::UnsupportedBlock);
+        assert!(
+            diagnostics
+                .check_promoted(&[WarningKind::ParseFailed])
+                .is_ok()
+        );
+
+        diagnostics.record(WarningKind::ParseFailed);
+        let result = diagnostics.check_promoted(&[WarningKind::ParseFailed]);
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert_eq!(
+            error_msg,
+            "1 file failed to parse (promoted to error by --error-on)"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_merge() {
+        let mut first = Diagnostics::new();
+        first.record(WarningKind::ParseFailed);
+        let mut second = Diagnostics::new();
+        second.record(WarningKind::ParseFailed);
+        second.record(WarningKind::ConversionFailed);
+        first.merge(&second);
+        assert_eq!(first.count(WarningKind::ParseFailed), 2);
+        assert_eq!(first.count(WarningKind::ConversionFailed), 1);
+    }
+
+    #[test]
+    fn test_check_no_warnings() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.record(WarningKind::UnsupportedBlock);
+        assert!(diagnostics.check_no_warnings("--strict").is_ok());
+
+        diagnostics.record(WarningKind::ParseFailed);
+        let error = diagnostics.check_no_warnings("--strict").unwrap_err();
+        assert_eq!(error.to_string(), "1 file failed to parse (--strict)");
+    }
+
+    #[test]
+    fn test_check_produced() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.record(WarningKind::UnsupportedBlock);
+        assert!(diagnostics.check_produced(0).is_ok());
+
+        diagnostics.record(WarningKind::ParseFailed);
+        diagnostics.record(WarningKind::ParseFailed);
+        assert!(diagnostics.check_produced(1).is_ok());
+        let error = diagnostics.check_produced(0).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<NothingGenerated>(),
+            Some(&NothingGenerated { failed_files: 2 })
+        );
+        assert_eq!(
+            error.to_string(),
+            "No moved blocks generated; 2 files failed"
+        );
+    }
+}
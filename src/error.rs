@@ -0,0 +1,98 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed errors for reading Terraform files.
+//!
+//! Discovery, parsing and block conversion return `MovedMakerError`, so library
+//! callers can tell a missing directory from invalid HCL or an unusable block.
+//! The messages match what the binary prints; it converts them to `anyhow`.
+
+use crate::parser::is_terraform_json;
+use std::path::{Path, PathBuf};
+
+/// Error reading or converting Terraform files
+#[derive(Debug, thiserror::Error)]
+pub enum MovedMakerError {
+    /// A source directory could not be listed
+    #[error("Failed to read directory: {}", .path.display())]
+    Discovery {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A Terraform file could not be read
+    #[error("Failed to read file: {}", .path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A Terraform file is not valid HCL (or Terraform JSON for `.tf.json`)
+    #[error("Failed to parse {} file: {}", syntax(.path), .path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A block cannot be turned into a move, e.g. a resource with one label
+    #[error("{reason}")]
+    InvalidBlock { path: PathBuf, reason: String },
+}
+
+impl MovedMakerError {
+    /// File or directory the error is about
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Discovery { path, .. }
+            | Self::Read { path, .. }
+            | Self::Parse { path, .. }
+            | Self::InvalidBlock { path, .. } => path,
+        }
+    }
+}
+
+fn syntax(path: &Path) -> &'static str {
+    if is_terraform_json(path) {
+        "JSON"
+    } else {
+        "HCL"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_terraform_file;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_errors_are_distinguishable() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.tf");
+        let invalid = temp_dir.path().join("invalid.tf");
+        fs::write(&invalid, "resource \"aws_instance\" {").unwrap();
+
+        let error = parse_terraform_file(&missing).unwrap_err();
+        assert!(matches!(error, MovedMakerError::Read { .. }));
+        assert_eq!(error.path(), missing);
+
+        let error = parse_terraform_file(&invalid).unwrap_err();
+        assert!(matches!(error, MovedMakerError::Parse { .. }));
+        assert_eq!(
+            error.to_string(),
+            format!("Failed to parse HCL file: {}", invalid.display())
+        );
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Moves recorded in existing `moved` blocks.
+//!
+//! `ExistingMoves` reads the `from`/`to` addresses of every `moved` block in
+//! an HCL file, e.g. a previously generated output, so new moves can be
+//! compared against it.
+
+use crate::address::normalize_address;
+use crate::parser::parse_terraform_file;
+use anyhow::Result;
+use hcl::edit::structure::Body;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Set of `(from, to)` address pairs from existing `moved` blocks
+///
+/// Addresses are normalized (see `normalize_address`), so formatting differences
+/// such as spacing inside an index do not matter when comparing.
+#[derive(Debug, Default, Clone)]
+pub struct ExistingMoves {
+    moves: HashSet<(String, String)>,
+}
+
+impl ExistingMoves {
+    /// Read moves from an HCL file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Ok(Self::from_body(&parse_terraform_file(path)?))
+    }
+
+    /// Read moves from several files, skipping files that cannot be read or parsed
+    ///
+    /// Used to find moves already written into the source files, which are
+    /// reported by the regular file processing when they fail to parse.
+    pub fn from_files<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Self {
+        let mut moves = Self::default();
+        for path in paths {
+            if let Ok(body) = parse_terraform_file(path) {
+                moves.merge(&Self::from_body(&body));
+            }
+        }
+        moves
+    }
+
+    /// Collect moves from all `moved` blocks in a parsed body
+    ///
+    /// Blocks missing a `from` or `to` attribute are ignored.
+    pub fn from_body(body: &Body) -> Self {
+        let moves = body
+            .blocks()
+            .filter(|block| block.ident.value().as_str() == "moved")
+            .filter_map(|block| {
+                let address = |key: &str| {
+                    block
+                        .body
+                        .get_attribute(key)
+                        .map(|attr| normalize_address(&attr.value.to_string()))
+                };
+                Some((address("from")?, address("to")?))
+            })
+            .collect();
+        Self { moves }
+    }
+
+    /// Whether a move from `from` to `to` is already recorded
+    pub fn contains(&self, from: &str, to: &str) -> bool {
+        self.moves
+            .contains(&(normalize_address(from), normalize_address(to)))
+    }
+
+    /// Whether any recorded move already targets `to`
+    pub fn contains_target(&self, to: &str) -> bool {
+        let to = normalize_address(to);
+        self.moves.iter().any(|(_, target)| *target == to)
+    }
+
+    /// Add all moves from `other`
+    pub fn merge(&mut self, other: &ExistingMoves) {
+        self.moves.extend(other.moves.iter().cloned());
+    }
+
+    /// Moves recorded here but not in `other`
+    pub fn difference(&self, other: &ExistingMoves) -> ExistingMoves {
+        Self {
+            moves: self.moves.difference(&other.moves).cloned().collect(),
+        }
+    }
+
+    /// All moves, sorted by `from` then `to`
+    pub fn sorted(&self) -> Vec<(String, String)> {
+        let mut moves: Vec<(String, String)> = self.moves.iter().cloned().collect();
+        moves.sort();
+        moves
+    }
+
+    /// Number of recorded moves
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// Whether no moves are recorded
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+}
+
+impl FromIterator<(String, String)> for ExistingMoves {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self {
+            moves: iter
+                .into_iter()
+                .map(|(from, to)| (normalize_address(&from), normalize_address(&to)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hcl::edit::parser::parse_body;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_existing_moves_from_body() -> Result<()> {
+        let body = parse_body(
+            r#"
+# From: main.tf
+moved {
+  from = aws_instance.web
+  to   = module.compute.aws_instance.web
+}
+
+moved {
+  from = module.web_server
+  to   = module.compute.module.web_server
+}
+
+resource "aws_instance" "api" {}
+"#,
+        )?;
+        let existing = ExistingMoves::from_body(&body);
+        assert_eq!(existing.len(), 2);
+        assert!(existing.contains("aws_instance.web", "module.compute.aws_instance.web"));
+        assert!(existing.contains("module.web_server", "module.compute.module.web_server"));
+        assert!(!existing.contains("aws_instance.api", "module.compute.aws_instance.api"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_moves_ignores_incomplete_blocks() -> Result<()> {
+        let body = parse_body("moved {\n  from = aws_instance.web\n}\n")?;
+        let existing = ExistingMoves::from_body(&body);
+        assert!(existing.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_moves_merge() {
+        let mut first: ExistingMoves = [("a.b".to_string(), "module.m.a.b".to_string())]
+            .into_iter()
+            .collect();
+        let second: ExistingMoves = [("c.d".to_string(), "module.m.c.d".to_string())]
+            .into_iter()
+            .collect();
+        first.merge(&second);
+        assert_eq!(first.len(), 2);
+        assert!(first.contains("c.d", "module.m.c.d"));
+    }
+
+    #[test]
+    fn test_existing_moves_difference() {
+        let first: ExistingMoves = [
+            ("a.b".to_string(), "module.m.a.b".to_string()),
+            ("c.d".to_string(), "module.m.c.d".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let second: ExistingMoves = [("a.b".to_string(), "module.m.a.b".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            first.difference(&second).sorted(),
+            vec![("c.d".to_string(), "module.m.c.d".to_string())]
+        );
+        assert!(second.difference(&first).is_empty());
+    }
+
+    #[test]
+    fn test_existing_moves_from_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("moved.tf");
+        fs::write(
+            &file,
+            "moved {\n  from = aws_instance.web\n  to = module.compute.aws_instance.web\n}\n",
+        )?;
+        let existing = ExistingMoves::from_file(&file)?;
+        assert!(existing.contains("aws_instance.web", "module.compute.aws_instance.web"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_moves_contains_target_normalized() -> Result<()> {
+        let body = parse_body(
+            "moved {\n  from = aws_instance.web[ \"a\" ]\n  to   = module.compute.aws_instance.web[ \"a\" ]\n}\n",
+        )?;
+        let existing = ExistingMoves::from_body(&body);
+        assert!(existing.contains_target(r#"module.compute.aws_instance.web["a"]"#));
+        assert!(existing.contains(
+            r#"aws_instance.web["a"]"#,
+            r#"module.compute.aws_instance.web["a"]"#
+        ));
+        assert!(!existing.contains_target(r#"aws_instance.web["a"]"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_moves_from_files_skips_unparsable() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let moved = temp_dir.path().join("moved.tf");
+        let broken = temp_dir.path().join("broken.tf");
+        fs::write(
+            &moved,
+            "moved {\n  from = aws_instance.web\n  to = module.compute.aws_instance.web\n}\n",
+        )?;
+        fs::write(&broken, "resource {")?;
+        let existing = ExistingMoves::from_files([moved.as_path(), broken.as_path()]);
+        assert_eq!(existing.len(), 1);
+        assert!(existing.contains_target("module.compute.aws_instance.web"));
+        Ok(())
+    }
+}
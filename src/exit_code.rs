@@ -0,0 +1,85 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exit codes of the `moved_maker` binary.
+//!
+//! Scripts can tell invalid arguments, a missing source directory and a run
+//! that produced nothing apart by the exit code alone.
+
+use crate::cli::{InvalidArgs, MissingSource};
+use crate::diagnostics::NothingGenerated;
+
+/// Process exit code, see `ExitCode::from_error` for how errors are mapped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Moves were written, or there was nothing to move
+    Success = 0,
+    /// Any other error, e.g. an output file that cannot be written
+    Failure = 1,
+    /// Invalid arguments; clap uses the same code for unknown flags
+    Usage = 2,
+    /// A `--src` path does not exist
+    Discovery = 3,
+    /// Warnings left no moved blocks to write
+    PartialFailure = 4,
+}
+
+impl ExitCode {
+    /// Exit code for an error returned by a run
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        if let Some(InvalidArgs(error)) = error.downcast_ref::<InvalidArgs>() {
+            if error.is::<MissingSource>() {
+                ExitCode::Discovery
+            } else {
+                ExitCode::Usage
+            }
+        } else if error.is::<NothingGenerated>() {
+            ExitCode::PartialFailure
+        } else {
+            ExitCode::Failure
+        }
+    }
+
+    /// Numeric code for `std::process::exit`
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_exit_code_from_error() {
+        let usage = anyhow::Error::from(InvalidArgs(anyhow::anyhow!("Invalid module name")));
+        assert_eq!(ExitCode::from_error(&usage), ExitCode::Usage);
+
+        let missing =
+            anyhow::Error::from(InvalidArgs(MissingSource(PathBuf::from("missing")).into()));
+        assert_eq!(ExitCode::from_error(&missing), ExitCode::Discovery);
+        assert_eq!(
+            missing.to_string(),
+            "Source directory does not exist: missing"
+        );
+
+        let nothing = anyhow::Error::from(NothingGenerated { failed_files: 1 });
+        assert_eq!(ExitCode::from_error(&nothing), ExitCode::PartialFailure);
+        assert_eq!(ExitCode::PartialFailure.code(), 4);
+
+        let other = anyhow::anyhow!("Failed to write moved.tf");
+        assert_eq!(ExitCode::from_error(&other), ExitCode::Failure);
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interactive selection of the moves to generate.
+//!
+//! The checklist is rendered on stderr so stdout stays reserved for the
+//! generated output, which keeps `--interactive > moved.tf` working.
+
+use crate::moved_block::MovedBlock;
+use anyhow::{Context, Result};
+use dialoguer::MultiSelect;
+use std::io::IsTerminal;
+
+/// Fail unless both stdin and stderr are attached to a terminal
+pub fn ensure_terminal() -> Result<()> {
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        anyhow::bail!("--interactive requires a terminal (stdin and stderr must be a tty)");
+    }
+    Ok(())
+}
+
+/// Checklist label for a move, e.g. `aws_instance.web -> module.compute.aws_instance.web (main.tf)`
+fn item_label(moved_block: &MovedBlock) -> String {
+    let file_name = moved_block
+        .file_path()
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!(
+        "{} -> {} ({})",
+        moved_block.from_address(),
+        moved_block.to_address(),
+        file_name
+    )
+}
+
+/// Let the user pick moves from a checklist, returning the selected indices in order
+///
+/// Nothing is selected initially. Space toggles an entry, `a` toggles all, Enter confirms.
+pub fn select<'a>(moved_blocks: impl Iterator<Item = &'a MovedBlock>) -> Result<Vec<usize>> {
+    let items: Vec<String> = moved_blocks.map(item_label).collect();
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+    MultiSelect::new()
+        .with_prompt("Select blocks to move")
+        .items(&items)
+        .interact()
+        .context("Interactive selection failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moved_resource::MovedResource;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_item_label() -> Result<()> {
+        let moved_block = MovedBlock::Resource(MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("/src/main.tf"),
+            "compute".to_string(),
+        )?);
+        assert_eq!(
+            item_label(&moved_block),
+            "aws_instance.web -> module.compute.aws_instance.web (main.tf)"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistent ledger of emitted moves.
+//!
+//! The ledger is a JSON Lines file with one `{"from": ..., "to": ...}` object
+//! per move ever emitted. It lets incremental runs skip moves that an earlier
+//! run already generated.
+
+use crate::existing_moves::ExistingMoves;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One recorded move
+#[derive(Debug, Serialize, Deserialize)]
+struct LedgerEntry {
+    from: String,
+    to: String,
+}
+
+/// Moves emitted by previous runs, backed by a file
+#[derive(Debug)]
+pub struct Ledger {
+    path: PathBuf,
+    moves: ExistingMoves,
+}
+
+impl Ledger {
+    /// Open a ledger, starting empty if the file does not exist
+    ///
+    /// Fails if the file exists but cannot be read or contains invalid entries.
+    pub fn open(path: &Path) -> Result<Self> {
+        let moves = if path.exists() {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read ledger: {}", path.display()))?;
+            Self::parse(&content).with_context(|| format!("Corrupt ledger: {}", path.display()))?
+        } else {
+            ExistingMoves::default()
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            moves,
+        })
+    }
+
+    fn parse(content: &str) -> Result<ExistingMoves> {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str::<LedgerEntry>(line)
+                    .map(|entry| (entry.from, entry.to))
+                    .with_context(|| format!("Line {}: invalid entry", index + 1))
+            })
+            .collect()
+    }
+
+    /// Moves recorded so far
+    pub fn moves(&self) -> &ExistingMoves {
+        &self.moves
+    }
+
+    /// Append `(from, to)` pairs that are not recorded yet
+    pub fn append<'a>(&self, moves: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<()> {
+        let mut lines = String::new();
+        for (from, to) in moves {
+            if self.moves.contains(from, to) {
+                continue;
+            }
+            let entry = LedgerEntry {
+                from: from.to_string(),
+                to: to.to_string(),
+            };
+            lines.push_str(&serde_json::to_string(&entry)?);
+            lines.push('\n');
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open ledger: {}", self.path.display()))?;
+        file.write_all(lines.as_bytes())
+            .with_context(|| format!("Failed to write ledger: {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ledger_missing_file_starts_empty() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let ledger = Ledger::open(&temp_dir.path().join("ledger.jsonl"))?;
+        assert_eq!(ledger.moves().len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ledger_append_and_reopen() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("ledger.jsonl");
+
+        let ledger = Ledger::open(&path)?;
+        ledger.append([("aws_instance.web", "module.compute.aws_instance.web")])?;
+
+        let reopened = Ledger::open(&path)?;
+        assert!(
+            reopened
+                .moves()
+                .contains("aws_instance.web", "module.compute.aws_instance.web")
+        );
+
+        // Already recorded pairs are not written twice
+        reopened.append([
+            ("aws_instance.web", "module.compute.aws_instance.web"),
+            ("aws_s3_bucket.data", "module.compute.aws_s3_bucket.data"),
+        ])?;
+        assert_eq!(fs::read_to_string(&path)?.lines().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ledger_corrupt_file_errors() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("ledger.jsonl");
+        fs::write(
+            &path,
+            "{\"from\":\"aws_instance.web\",\"to\":\"module.compute.aws_instance.web\"}\nnot json\n",
+        )?;
+
+        let result = Ledger::open(&path);
+        assert!(result.is_err());
+        let error_msg = format!("{:#}", result.unwrap_err());
+        assert!(error_msg.contains("Corrupt ledger"));
+        assert!(error_msg.contains("Line 2"));
+        Ok(())
+    }
+}
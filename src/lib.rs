@@ -0,0 +1,70 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![forbid(unsafe_code)]
+
+//! Generate Terraform `moved` blocks for refactoring resources into a module.
+//!
+//! The `moved_maker` binary is a thin CLI over this library. To build moved
+//! blocks programmatically:
+//!
+//! ```no_run
+//! use moved_maker::{MovedBlockBuilder, build_output_body};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let blocks = MovedBlockBuilder::new("./infra".into(), "compute".to_string())
+//!     .moved_blocks()
+//!     .map(|moved_block| moved_block?.to_block())
+//!     .collect::<anyhow::Result<Vec<_>>>()?;
+//! println!("{}", build_output_body(&blocks));
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod address;
+pub mod allowlist;
+pub mod block_meta;
+pub mod cli;
+pub mod config;
+pub mod diagnostics;
+pub mod error;
+pub mod existing_moves;
+pub mod exit_code;
+pub mod interactive;
+pub mod ledger;
+pub mod moved_block;
+pub mod moved_data;
+pub mod moved_module;
+pub mod moved_resource;
+pub mod output;
+pub mod output_dir;
+pub mod parser;
+pub mod pipeline;
+pub mod rename_map;
+pub mod structured;
+pub mod terraform_files;
+pub mod terraform_plan;
+pub mod terraform_state;
+pub mod to_moved_block;
+pub mod type_filter;
+pub mod watch;
+
+pub use address::AddressBuilder;
+pub use error::MovedMakerError;
+pub use moved_block::MovedBlock;
+pub use moved_data::MovedData;
+pub use moved_module::MovedModule;
+pub use moved_resource::MovedResource;
+pub use output::build_output_body;
+pub use pipeline::{MovedBlockBuilder, MovedBlocks};
+pub use to_moved_block::ToMovedBlock;
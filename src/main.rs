@@ -13,52 +13,617 @@
 // limitations under the License.
 #![forbid(unsafe_code)]
 
-mod address;
-mod cli;
-mod moved_block;
-mod moved_module;
-mod moved_resource;
-mod output;
-mod parser;
-mod pipeline;
-mod terraform_files;
-mod to_moved_block;
-
-use anyhow::Result;
-use clap::Parser;
-use cli::Args;
-use output::build_output_body;
-use pipeline::MovedBlockBuilder;
+use anyhow::{Context, Result};
+use clap::{CommandFactory, FromArgMatches};
+use moved_maker::address::{module_path, parse_module_path};
+use moved_maker::allowlist::Allowlist;
+use moved_maker::cli::{Args, Command, InvalidArgs, OutputFormat, STDIN_FILE_NAME, SortOrder};
+use moved_maker::config::Config;
+use moved_maker::diagnostics::{Diagnostics, WarningKind, report_error};
+use moved_maker::existing_moves::ExistingMoves;
+use moved_maker::exit_code::ExitCode;
+use moved_maker::interactive;
+use moved_maker::ledger::Ledger;
+use moved_maker::moved_block::{MovePrefixes, duplicate_source_addresses};
+use moved_maker::output::{
+    BlockStream, build_diff_output, build_dry_run_summary, build_header, build_module_stub,
+    build_output_body, build_output_body_by_dir, build_rollback_output, canonicalize,
+    format_moved_file, group_by_source_file, merge_output, render_output, sort_by_address,
+    sort_by_file_then_address, validate_hcl, with_trailing_newline,
+};
+use moved_maker::output_dir::{OutputDir, write_file};
+use moved_maker::pipeline::MovedBlockBuilder;
+use moved_maker::rename_map::RenameMap;
+use moved_maker::structured::output_schema;
+use moved_maker::terraform_files::{IgnoreGlobs, read_path_list};
+use moved_maker::terraform_plan::TerraformPlan;
+use moved_maker::terraform_state::TerraformState;
+use moved_maker::to_moved_block::{BlockType, CommentOptions, CommentPath, Indent};
+use moved_maker::type_filter::{NameFilter, TypeFilter};
+use moved_maker::watch;
+use regex::Regex;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
 
 fn main() {
-    if let Err(e) = run() {
-        eprintln!("Error: {:#}", e);
-        std::process::exit(1);
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    init_logging(args.verbose);
+    let result = load_config(&mut args, &matches).and_then(|()| {
+        if args.watch {
+            run_watch(&args)
+        } else {
+            run(&args)
+        }
+    });
+    if let Err(e) = result {
+        report_error(args.error_format, &e);
+        std::process::exit(ExitCode::from_error(&e).code());
     }
 }
 
-fn run() -> Result<()> {
-    let args = Args::parse();
+/// Log progress to stderr as plain lines with `--verbose`; `RUST_LOG` can override the level
+fn init_logging(verbose: bool) {
+    let level = if verbose {
+        log::LevelFilter::Info
+    } else {
+        log::LevelFilter::Warn
+    };
+    env_logger::Builder::new()
+        .filter_module("moved_maker", level)
+        .parse_default_env()
+        .format(|buf, record| writeln!(buf, "{}", record.args()))
+        .init();
+}
+
+/// Run once, then again after every change to the source files until interrupted
+///
+/// Errors from a single run are reported and the watch goes on, so a file saved
+/// half-way through an edit does not end the session.
+fn run_watch(args: &Args) -> Result<()> {
+    args.validate()?;
+    eprintln!("Watching for changes, press Ctrl+C to stop");
+    let mut first_run = true;
+    watch::watch(
+        &args.src,
+        args.recursive,
+        &args.terraform_extensions()?,
+        &args.written_paths(),
+        watch::DEBOUNCE,
+        || {
+            if !first_run {
+                eprintln!("Change detected, regenerating");
+            }
+            first_run = false;
+            if let Err(e) = run(args) {
+                report_error(args.error_format, &e);
+            }
+        },
+    )
+}
+
+/// Apply `.moved_maker.toml` from a source directory or the current directory, if there is one
+fn load_config(args: &mut Args, matches: &clap::ArgMatches) -> Result<()> {
+    if args.command.is_some() || args.json_schema {
+        return Ok(());
+    }
+    if let Some(path) = Config::find(&args.src) {
+        log::info!("Using config {}", path.display());
+        let config = Config::from_file(&path).map_err(InvalidArgs)?;
+        args.merge_config(&config, matches);
+    }
+    Ok(())
+}
+
+fn run(args: &Args) -> Result<()> {
+    if args.json_schema {
+        println!("{}", serde_json::to_string_pretty(&output_schema())?);
+        return Ok(());
+    }
+    if let Some(Command::Format { file }) = &args.command {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        return fs::write(file, format_moved_file(&content)?)
+            .with_context(|| format!("Failed to write {}", file.display()));
+    }
     args.validate()?;
+    if args.interactive {
+        // Fail before doing any work when there is no one to ask
+        interactive::ensure_terminal()?;
+    }
+    let prefix_from = match (&args.prefix_from, &args.source_module) {
+        (Some(prefix), _) => Some(parse_module_path(prefix)?),
+        (None, Some(source_module)) => Some(module_path(source_module)),
+        (None, None) => None,
+    };
+    let prefixes = if prefix_from.is_some() || args.prefix_to.is_some() {
+        let from = prefix_from.unwrap_or_default();
+        let to = match (&args.prefix_to, &args.module_name) {
+            (Some(prefix), _) => parse_module_path(prefix)?,
+            (None, Some(module_name)) => module_path(module_name),
+            (None, None) => unreachable!("validate() ensures module_name or prefix_to is set"),
+        };
+        Some(MovePrefixes { from, to })
+    } else {
+        None
+    };
+    let module_name = match (&args.module_name, &prefixes) {
+        (Some(module_name), _) => module_name.clone(),
+        (None, Some(prefixes)) => prefixes.target_module_name(),
+        (None, None) => unreachable!("validate() ensures module_name or prefix_to is set"),
+    };
+
+    let module_stub = if args.scaffold_module {
+        let target = prefixes
+            .as_ref()
+            .map(|prefixes| prefixes.to.clone())
+            .unwrap_or_else(|| module_path(&module_name));
+        if target != format!("module.{}", module_name) {
+            anyhow::bail!(
+                "--scaffold-module needs a root-level target module, got '{}'",
+                target
+            );
+        }
+        let source = args
+            .target_source
+            .clone()
+            .unwrap_or_else(|| format!("./modules/{}", module_name));
+        Some(build_module_stub(&module_name, &source))
+    } else {
+        None
+    };
 
-    let builder = MovedBlockBuilder::new(args.src, args.module_name);
+    let resource_name_regex = args
+        .resource_name_regex
+        .as_deref()
+        .map(Regex::new)
+        .transpose()?;
+    let type_filter = if args.include_type.is_empty() && args.exclude_type.is_empty() {
+        None
+    } else {
+        Some(TypeFilter::new(&args.include_type, &args.exclude_type)?)
+    };
+    let name_filter = if args.exclude_name.is_empty() {
+        None
+    } else {
+        Some(NameFilter::new(&args.exclude_name)?)
+    };
+    let content_matches = args
+        .content_matches
+        .as_deref()
+        .map(Regex::new)
+        .transpose()?;
+    let rename_map = args
+        .rename_map
+        .as_deref()
+        .map(RenameMap::from_file)
+        .transpose()?;
+    let allowlist = args
+        .only_file
+        .as_deref()
+        .map(Allowlist::from_file)
+        .transpose()?;
+    let mut skip_existing = args
+        .only_changed
+        .as_deref()
+        .map(ExistingMoves::from_file)
+        .transpose()?;
+    let existing_targets = args
+        .existing
+        .as_deref()
+        .map(ExistingMoves::from_file)
+        .transpose()?;
+    // Moves already in the merge target are left as they are
+    if let Some(path) = args.merge_into.as_deref().filter(|path| path.exists()) {
+        skip_existing
+            .get_or_insert_with(ExistingMoves::default)
+            .merge(&ExistingMoves::from_file(path)?);
+    }
+    let ledger = args.ledger.as_deref().map(Ledger::open).transpose()?;
+    if let Some(ledger) = &ledger {
+        skip_existing
+            .get_or_insert_with(ExistingMoves::default)
+            .merge(ledger.moves());
+    }
+    let state = args
+        .state
+        .as_deref()
+        .map(TerraformState::from_file)
+        .transpose()?;
+    // With --from-plan there are no files to scan, the plan supplies all moves
+    let plan_blocks = match &args.from_plan {
+        Some(plan) => TerraformPlan::from_file(plan)?
+            .moved_blocks(plan, &module_name)?
+            .into_iter()
+            .map(|moved_block| match &prefixes {
+                Some(prefixes) => moved_block.with_prefixes(prefixes),
+                None => moved_block,
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    let builder = if args.src_stdin_list {
+        let paths = read_path_list(std::io::stdin().lock())?;
+        MovedBlockBuilder::from_listed_paths(paths, module_name.clone())
+    } else if args.src_is_stdin() {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read configuration from stdin")?;
+        MovedBlockBuilder::from_content(
+            PathBuf::from(STDIN_FILE_NAME),
+            content,
+            module_name.clone(),
+        )
+    } else if args.src.is_empty() {
+        MovedBlockBuilder::from_paths(std::iter::empty(), module_name.clone())
+    } else {
+        MovedBlockBuilder::from_dirs(args.src.clone(), module_name.clone())
+    };
+    let builder = builder
+        .warn_name_collision(args.warn_name_collision)
+        .include_data(args.include_data)
+        .resource_name_regex(resource_name_regex)
+        .type_filter(type_filter)
+        .name_filter(name_filter)
+        .content_matches(content_matches)
+        .lenient_parse(args.lenient_parse)
+        .rename_map(rename_map)
+        .skip_existing(skip_existing)
+        .existing_targets(existing_targets)
+        .allowlist(allowlist)
+        .prefixes(prefixes)
+        .direction(args.direction)
+        .recursive(args.recursive)
+        .ignore(IgnoreGlobs::new(&args.ignore)?)
+        .extensions(args.terraform_extensions()?)
+        .jobs(args.jobs.unwrap_or(1))
+        .error_format(args.error_format);
+    let comment_options = CommentOptions {
+        include_kind: args.emit_comment_kind,
+        style: args.comment_style,
+        mark_risky: args.mark_risky,
+        // Nested files are only told apart by their path below --src
+        relative_to: args
+            .src
+            .iter()
+            .filter(|src| src.is_dir())
+            .cloned()
+            .collect(),
+        path: args.comment_path.unwrap_or(if args.recursive {
+            CommentPath::Relative
+        } else {
+            CommentPath::Name
+        }),
+        indent: if args.use_tabs {
+            Indent::Tab
+        } else {
+            Indent::Spaces(args.indent)
+        },
+        template: args.comment_template.clone(),
+        omit: args.no_comment,
+    };
     let mut moved_blocks = Vec::new();
+    // Streamed blocks are written right away, only their from-address and source file are
+    // kept for the duplicate and state checks below
+    let mut stream = args
+        .streams_output()
+        .then(|| BlockStream::new(std::io::stdout().lock(), !args.no_validate));
+    let mut streamed_sources: Vec<(String, PathBuf)> = Vec::new();
+    let mut diagnostics = Diagnostics::with_format(args.error_format);
 
-    for moved_block_result in builder.moved_blocks() {
+    let mut pipeline = builder.moved_blocks();
+    let mut plan_blocks = plan_blocks.into_iter();
+    // Not a `for` loop, --fail-fast inspects the pipeline between items
+    while let Some(moved_block_result) = plan_blocks.next().map(Ok).or_else(|| pipeline.next()) {
+        // Streamed blocks are capped as they are written, buffered ones after sorting. The
+        // cap is checked once another result is available, so the note is only printed
+        // when blocks were actually left out
+        if let Some(max_blocks) = args.max_blocks
+            && stream.is_some()
+            && streamed_sources.len() >= max_blocks
+        {
+            eprintln!(
+                "Note: Stopped after {} blocks (--max-blocks), more may exist",
+                max_blocks
+            );
+            break;
+        }
+        if args.fail_fast {
+            diagnostics.check_no_warnings("--fail-fast")?;
+            pipeline.diagnostics().check_no_warnings("--fail-fast")?;
+        }
+        let moved_block_result = match (moved_block_result, args.missing_key) {
+            (Ok(moved_block), Some(policy)) => {
+                let source_address = moved_block.from_address();
+                let file_path = moved_block.file_path().to_path_buf();
+                match moved_block.apply_missing_key(policy)? {
+                    Some(moved_block) => Ok(moved_block),
+                    None => {
+                        diagnostics.warn_in(
+                            WarningKind::MissingKey,
+                            &file_path,
+                            format_args!(
+                                "Skipping {} in {}: count/for_each without an instance key",
+                                source_address,
+                                file_path.display()
+                            ),
+                        );
+                        continue;
+                    }
+                }
+            }
+            (moved_block_result, _) => moved_block_result,
+        };
         match moved_block_result {
-            Ok(moved_block) => match moved_block.to_block() {
-                Ok(block) => moved_blocks.push(block),
-                Err(e) => {
-                    eprintln!("Warning: Failed to convert moved block: {}", e);
+            Ok(moved_block) => {
+                let block = match args.block_type {
+                    BlockType::Moved => moved_block.to_block_with(&comment_options),
+                    BlockType::Removed => moved_block.to_removed_block_with(&comment_options),
+                };
+                match block {
+                    Ok(block) => {
+                        log::info!("Generated moved block: {}", moved_block);
+                        if let Some(stream) = &mut stream {
+                            stream.write(&block)?;
+                            streamed_sources.push((
+                                moved_block.from_address(),
+                                moved_block.file_path().to_path_buf(),
+                            ));
+                        } else {
+                            moved_blocks.push((moved_block, block));
+                        }
+                    }
+                    Err(e) => {
+                        diagnostics.warn_in(
+                            WarningKind::ConversionFailed,
+                            moved_block.file_path(),
+                            format_args!("Failed to convert moved block: {}", e),
+                        );
+                    }
                 }
-            },
+            }
             Err(e) => {
-                eprintln!("Warning: {}", e);
+                diagnostics.warn(WarningKind::InvalidBlock, e);
+            }
+        }
+    }
+    diagnostics.merge(&pipeline.diagnostics());
+    if args.fail_on_empty && pipeline.files_seen() == 0 {
+        let searched = if args.src_stdin_list {
+            "the paths read from stdin".to_string()
+        } else {
+            args.src
+                .iter()
+                .map(|src| src.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        anyhow::bail!("No .tf files found in {} (--fail-on-empty)", searched);
+    }
+    let generated_sources = || {
+        moved_blocks
+            .iter()
+            .map(|(m, _)| (m.from_address(), m.file_path()))
+            .chain(
+                streamed_sources
+                    .iter()
+                    .map(|(address, file)| (address.clone(), file.as_path())),
+            )
+    };
+    for (address, files) in duplicate_source_addresses(generated_sources()) {
+        let files: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+        diagnostics.warn(
+            WarningKind::DuplicateAddress,
+            format_args!(
+                "{} is defined in multiple files: {}",
+                address,
+                files.join(", ")
+            ),
+        );
+    }
+    // A `from` missing from state is a typo or a resource that was already moved
+    if let Some(state) = &state {
+        for (address, file_path) in generated_sources() {
+            if !state.contains(&address) {
+                diagnostics.warn_in(
+                    WarningKind::NotInState,
+                    file_path,
+                    format_args!("{} in {} is not in state", address, file_path.display()),
+                );
             }
         }
     }
+    if let Some(report) = &args.report_unmoved {
+        let unmoved = serde_json::to_string_pretty(&pipeline.unmoved())?;
+        fs::write(report, unmoved)
+            .with_context(|| format!("Failed to write report: {}", report.display()))?;
+    }
+    if args.summary || args.verbose {
+        eprintln!("Summary: {}", diagnostics.summary());
+    }
+    diagnostics.check_promoted(&args.error_on)?;
+    if args.strict || args.fail_fast {
+        let option = if args.strict {
+            "--strict"
+        } else {
+            "--fail-fast"
+        };
+        diagnostics.check_no_warnings(option)?;
+    }
+    diagnostics.check_produced(moved_blocks.len() + streamed_sources.len())?;
+    if let Some(stream) = stream {
+        let _ = stream.finish()?;
+        return Ok(());
+    }
+
+    match args.sort_order() {
+        SortOrder::None => {}
+        SortOrder::Address => sort_by_address(&mut moved_blocks),
+        SortOrder::File => sort_by_file_then_address(&mut moved_blocks),
+    }
+    // Keep the first blocks in output order, not in discovery order
+    if let Some(max_blocks) = args.max_blocks
+        && moved_blocks.len() > max_blocks
+    {
+        eprintln!(
+            "Note: Stopped after {} blocks (--max-blocks), {} more left out",
+            max_blocks,
+            moved_blocks.len() - max_blocks
+        );
+        moved_blocks.truncate(max_blocks);
+    }
+    if args.interactive {
+        let selected = interactive::select(moved_blocks.iter().map(|(m, _)| m))?;
+        let mut moved_block_slots: Vec<_> = moved_blocks.into_iter().map(Some).collect();
+        moved_blocks = selected
+            .into_iter()
+            .filter_map(|index| moved_block_slots[index].take())
+            .collect();
+    }
+
+    if let Some(path) = &args.diff {
+        let generated: ExistingMoves = moved_blocks
+            .iter()
+            .map(|(m, _)| (m.from_address(), m.to_address()))
+            .collect();
+        print!(
+            "{}",
+            build_diff_output(
+                &ExistingMoves::from_file(path)?,
+                &generated,
+                &path.display().to_string()
+            )
+        );
+        return Ok(());
+    }
+    if args.dry_run {
+        eprintln!(
+            "{}",
+            build_dry_run_summary(moved_blocks.iter().map(|(m, _)| m))
+        );
+        return Ok(());
+    }
+    if args.count_only {
+        println!("{}", moved_blocks.len());
+        return Ok(());
+    }
+
+    let addresses: Vec<(String, String)> = moved_blocks
+        .iter()
+        .map(|(m, _)| (m.from_address(), m.to_address()))
+        .collect();
+
+    let render = |format: OutputFormat, blocks: &[_]| -> Result<String> {
+        // validate() only allows --group-by-dir with the HCL format
+        let mut output = if args.group_by_dir {
+            build_output_body_by_dir(blocks, &args.src).to_string()
+        } else {
+            render_output(format, blocks, args.address_style)?
+        };
+        // A merged file gets new blocks on every run, a per-run count would be wrong there
+        if format == OutputFormat::Hcl
+            && !args.no_header
+            && args.merge_into.is_none()
+            && !blocks.is_empty()
+        {
+            output.insert_str(0, &build_header(blocks));
+        }
+        if format == OutputFormat::Hcl && !args.no_validate {
+            validate_hcl(&output)?;
+        }
+        let output = if args.canonical {
+            canonicalize(&output)?
+        } else {
+            output
+        };
+        Ok(with_trailing_newline(&output))
+    };
+    let formats = args.formats();
+
+    if let Some(preview) = args.preview {
+        let shown = preview.min(moved_blocks.len());
+        eprintln!("Preview ({} of {} blocks):", shown, moved_blocks.len());
+        eprint!("{}", render(formats[0], &moved_blocks[..shown])?);
+    }
+
+    if let Some(rollback) = &args.with_rollback {
+        write_file(
+            rollback,
+            &build_rollback_output(&moved_blocks)?,
+            args.output_file_mode(),
+        )
+        .with_context(|| format!("Failed to write rollback: {}", rollback.display()))?;
+    }
 
-    let output_body = build_output_body(&moved_blocks);
-    println!("{}", output_body);
+    if let Some(dir) = &args.output_dir {
+        let output_dir = OutputDir::create(dir, args.force)?.with_mode(args.output_file_mode());
+        // Render everything and check for conflicts first, so a refused run writes nothing
+        let mut files = Vec::new();
+        if let Some(stub) = &module_stub {
+            files.push((
+                format!("module_{}.tf", module_name),
+                build_output_body(std::slice::from_ref(stub)).to_string(),
+            ));
+        }
+        let extensions = args.terraform_extensions()?;
+        for (file_name, blocks) in group_by_source_file(
+            moved_blocks,
+            formats[0],
+            &extensions,
+            &comment_options.relative_to,
+        )? {
+            files.push((file_name, render(formats[0], &blocks)?));
+        }
+        output_dir.check_conflicts(files.iter().map(|(file_name, _)| file_name.as_str()))?;
+        for (file_name, content) in &files {
+            output_dir.write(file_name, content)?;
+        }
+    } else if let Some(path) = &args.merge_into {
+        let existing = if path.exists() {
+            fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?
+        } else {
+            String::new()
+        };
+        let mut content = String::new();
+        if let Some(stub) = &module_stub {
+            content.push_str(&build_output_body(std::slice::from_ref(stub)).to_string());
+        }
+        content.push_str(&render(formats[0], &moved_blocks)?);
+        write_file(
+            path,
+            &merge_output(&existing, &content),
+            args.output_file_mode(),
+        )
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    } else if !args.output.is_empty() {
+        // The blocks are collected once and rendered per format, validate() pairs them up
+        for (format, path) in formats.iter().zip(&args.output) {
+            let mut content = String::new();
+            if let Some(stub) = &module_stub {
+                content.push_str(&build_output_body(std::slice::from_ref(stub)).to_string());
+            }
+            content.push_str(&render(*format, &moved_blocks)?);
+            write_file(path, &content, args.output_file_mode())
+                .with_context(|| format!("Failed to write output: {}", path.display()))?;
+        }
+    } else {
+        // The stub is emitted once, ahead of all moves that target it
+        if let Some(stub) = &module_stub {
+            print!("{}", build_output_body(std::slice::from_ref(stub)));
+        }
+        print!("{}", render(formats[0], &moved_blocks)?);
+    }
+
+    // Only record moves once they were written successfully
+    if let Some(ledger) = &ledger {
+        ledger.append(
+            addresses
+                .iter()
+                .map(|(from, to)| (from.as_str(), to.as_str())),
+        )?;
+    }
     Ok(())
 }
@@ -18,14 +18,136 @@
 //! (resources, modules, etc.) and includes a factory method for creating instances
 //! from HCL blocks.
 
+use crate::address::{AddressIndex, AddressSegment, parse_address, validate_module_name};
+use crate::block_meta::{BlockMeta, MissingKey, PLACEHOLDER_KEY};
+use crate::error::MovedMakerError;
+use crate::moved_data::MovedData;
 use crate::moved_module::MovedModule;
 use crate::moved_resource::MovedResource;
-use crate::to_moved_block::ToMovedBlock;
-use anyhow::Result;
+use crate::to_moved_block::{CommentOptions, Direction, ToMovedBlock};
+use anyhow::{Context, Result};
 use hcl::edit::structure::Block;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-/// Enum wrapper for moved blocks (Resource or Module)
+/// Module paths placed in front of the "from" and "to" addresses
+///
+/// Both are formatted module paths (see `parse_module_path`), `""` being the root module.
+/// The default move is `from: ""`, `to: "module.<target>"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovePrefixes {
+    pub from: String,
+    pub to: String,
+}
+
+impl MovePrefixes {
+    /// Name of the innermost module in the "to" prefix, or `""` for the root module
+    pub fn target_module_name(&self) -> String {
+        parse_address(&self.to)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .find_map(|segment| match segment {
+                AddressSegment::Attribute(name) => Some(name),
+                AddressSegment::Index(_) => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Placeholder in `--module-name` replaced by the source file name, without extensions
+const FILE_PLACEHOLDER: &str = "{file}";
+/// Placeholder in `--module-name` replaced by the name of the source file's directory
+const DIR_PLACEHOLDER: &str = "{dir}";
+
+/// Whether a module name contains `{file}` or `{dir}` placeholders
+pub fn is_module_name_template(module_name: &str) -> bool {
+    module_name.contains(FILE_PLACEHOLDER) || module_name.contains(DIR_PLACEHOLDER)
+}
+
+/// Replace the `{file}` and `{dir}` placeholders of a module name template
+pub fn resolve_module_name_template(template: &str, file: &str, dir: &str) -> String {
+    template
+        .replace(FILE_PLACEHOLDER, file)
+        .replace(DIR_PLACEHOLDER, dir)
+}
+
+/// Resolve a module name template for a block read from `file_path`
+///
+/// `{file}` is the file name up to the first `.` (`network.tf.json` gives `network`),
+/// `{dir}` the name of the directory containing the file. The result must be a
+/// valid module name.
+pub fn resolve_module_name(template: &str, file_path: &Path) -> Result<String> {
+    if !is_module_name_template(template) {
+        return Ok(template.to_string());
+    }
+    let file = file_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .unwrap_or_default();
+    let dir = file_path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let module_name = resolve_module_name_template(template, file, dir);
+    validate_module_name(&module_name).with_context(|| {
+        format!(
+            "Module name '{}' resolved for {} is invalid",
+            module_name,
+            file_path.display()
+        )
+    })?;
+    Ok(module_name)
+}
+
+/// Concise `from -> to` summary, e.g. `aws_instance.web -> module.compute.aws_instance.web`
+impl fmt::Display for MovedBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovedBlock::Resource(r) => write!(f, "{}", r),
+            MovedBlock::Module(m) => write!(f, "{}", m),
+            MovedBlock::Data(d) => write!(f, "{}", d),
+        }
+    }
+}
+
+/// Whether a top-level block type is known to never produce moves
+///
+/// `moved`, `removed`, `import` and `check` blocks, `output`, `variable` and `locals`
+/// blocks and the `terraform` settings block are skipped on purpose and do not count
+/// as unsupported blocks. Any other type besides `resource`, `module` and `data` does.
+pub fn is_skipped_block_type(ident: &str) -> bool {
+    matches!(
+        ident,
+        "moved" | "removed" | "import" | "check" | "output" | "variable" | "locals" | "terraform"
+    )
+}
+
+/// Source addresses that come from more than one file, with the files in first-seen order
+///
+/// Two files defining the same address is invalid Terraform and would produce
+/// duplicate moves. Repeats within a single file are not reported here.
+/// Takes `(from address, source file)` pairs, so streamed blocks need not be kept.
+pub fn duplicate_source_addresses<'a>(
+    sources: impl Iterator<Item = (String, &'a Path)>,
+) -> Vec<(String, Vec<PathBuf>)> {
+    let mut files_by_address: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for (address, file_path) in sources {
+        let files = files_by_address.entry(address).or_default();
+        if !files.iter().any(|file| file == file_path) {
+            files.push(file_path.to_path_buf());
+        }
+    }
+    files_by_address
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .collect()
+}
+
+/// Enum wrapper for moved blocks (Resource, Module or Data)
 ///
 /// This enum does not implement the `ToMovedBlock` trait because no code requires it as a trait bound.
 /// The `from_block()` method encapsulates the logic for creating a `MovedBlock` from an HCL `Block`,
@@ -34,70 +156,249 @@ use std::path::Path;
 pub enum MovedBlock {
     Resource(MovedResource),
     Module(MovedModule),
+    Data(MovedData),
 }
 
 impl MovedBlock {
     /// Create a `MovedBlock` from an HCL Block
     ///
     /// Determines the block type from the block's identifier and creates
-    /// the appropriate variant (Resource or Module)
+    /// the appropriate variant (Resource, Module or Data)
+    ///
+    /// A `# moved_maker: module=<name>` comment above the block replaces `module_name`.
     ///
     /// Returns:
-    /// - `None` if the block type is not supported (resource/module) - should be skipped silently
+    /// - `None` if the block type is not supported (resource/module/data) - should be skipped,
+    ///   see `is_skipped_block_type` for the types skipped on purpose
     /// - `Some(Ok(MovedBlock))` if successfully converted
-    /// - `Some(Err(e))` if supported type but conversion failed (e.g., invalid labels)
-    pub fn from_block(block: &Block, file_path: &Path, module_name: &str) -> Option<Result<Self>> {
+    /// - `Some(Err(MovedMakerError::InvalidBlock))` if supported type but conversion failed
+    ///   (e.g., invalid labels)
+    pub fn from_block(
+        block: &Block,
+        file_path: &Path,
+        module_name: &str,
+    ) -> Option<Result<Self, MovedMakerError>> {
         let ident = block.ident.value().to_string();
-        let labels: Vec<String> = block
-            .labels
-            .iter()
-            .map(|l| l.as_str().to_string())
-            .collect();
+        if is_skipped_block_type(&ident) {
+            log::debug!("Skipping {} block in {}", ident, file_path.display());
+            return None;
+        }
+        let labels = label_values(block);
+        let invalid = |reason: String| MovedMakerError::InvalidBlock {
+            path: file_path.to_path_buf(),
+            reason,
+        };
+        let meta = BlockMeta::from_block(block);
+        let module_name = match &meta.target_module {
+            Some(target_module) => {
+                if let Err(e) = validate_module_name(target_module) {
+                    return Some(Err(invalid(format!(
+                        "Invalid module directive in {}: {}",
+                        file_path.display(),
+                        e
+                    ))));
+                }
+                target_module.clone()
+            }
+            None => module_name.to_string(),
+        };
 
-        match ident.as_str() {
+        let moved_block = match ident.as_str() {
             "resource" => {
                 if labels.len() < 2 {
-                    return Some(Err(anyhow::anyhow!(
+                    return Some(Err(invalid(format!(
                         "Resource block in {} has fewer than 2 labels",
                         file_path.display()
-                    )));
+                    ))));
                 }
-                Some(
-                    MovedResource::new(labels, file_path.to_path_buf(), module_name.to_string())
-                        .map(Self::Resource),
-                )
+                resolve_module_name(&module_name, file_path)
+                    .and_then(|module_name| {
+                        MovedResource::new(labels, file_path.to_path_buf(), module_name)
+                    })
+                    .map(|resource| resource.with_meta(meta))
+                    .map(Self::Resource)
             }
             "module" => {
                 if labels.is_empty() {
-                    return Some(Err(anyhow::anyhow!(
+                    return Some(Err(invalid(format!(
                         "Module block in {} has no labels",
                         file_path.display()
-                    )));
+                    ))));
+                }
+                resolve_module_name(&module_name, file_path)
+                    .and_then(|module_name| {
+                        MovedModule::new(labels, file_path.to_path_buf(), module_name)
+                    })
+                    .map(|module| module.with_meta(meta))
+                    .map(Self::Module)
+            }
+            "data" => {
+                if labels.len() < 2 {
+                    return Some(Err(invalid(format!(
+                        "Data block in {} has fewer than 2 labels",
+                        file_path.display()
+                    ))));
                 }
-                Some(
-                    MovedModule::new(labels, file_path.to_path_buf(), module_name.to_string())
-                        .map(Self::Module),
-                )
+                resolve_module_name(&module_name, file_path)
+                    .and_then(|module_name| {
+                        MovedData::new(labels, file_path.to_path_buf(), module_name)
+                    })
+                    .map(|data| data.with_meta(meta))
+                    .map(Self::Data)
             }
-            _ => None, // Unsupported block type, skip silently
+            _ => return None, // Unsupported block type, skip silently
+        };
+        Some(moved_block.map_err(|e| invalid(e.to_string())))
+    }
+
+    /// Replace the default address prefixes
+    pub fn with_prefixes(self, prefixes: &MovePrefixes) -> Self {
+        match self {
+            MovedBlock::Resource(r) => {
+                MovedBlock::Resource(r.with_prefixes(prefixes.from.clone(), prefixes.to.clone()))
+            }
+            MovedBlock::Module(m) => {
+                MovedBlock::Module(m.with_prefixes(prefixes.from.clone(), prefixes.to.clone()))
+            }
+            MovedBlock::Data(d) => {
+                MovedBlock::Data(d.with_prefixes(prefixes.from.clone(), prefixes.to.clone()))
+            }
+        }
+    }
+
+    /// Swap the "from" and "to" prefixes for `Direction::Outof`
+    pub fn with_direction(self, direction: Direction) -> Self {
+        match self {
+            MovedBlock::Resource(r) => MovedBlock::Resource(r.with_direction(direction)),
+            MovedBlock::Module(m) => MovedBlock::Module(m.with_direction(direction)),
+            MovedBlock::Data(d) => MovedBlock::Data(d.with_direction(direction)),
+        }
+    }
+
+    /// Name of the module the block moves into
+    pub fn target_module_name(&self) -> &str {
+        match self {
+            MovedBlock::Resource(r) => r.target_module_name(),
+            MovedBlock::Module(m) => m.target_module_name(),
+            MovedBlock::Data(d) => d.target_module_name(),
+        }
+    }
+
+    /// Apply the `--missing-key` policy to a block whose source uses `count`/`for_each`
+    ///
+    /// Blocks without `count`/`for_each` are returned unchanged. Returns `Ok(None)`
+    /// when the block should be skipped.
+    pub fn apply_missing_key(self, policy: MissingKey) -> Result<Option<Self>> {
+        if !self.meta().expects_instance_key() {
+            return Ok(Some(self));
+        }
+        match policy {
+            MissingKey::Skip => Ok(None),
+            MissingKey::Placeholder => {
+                let key = AddressIndex::String(PLACEHOLDER_KEY.to_string());
+                Ok(Some(match self {
+                    MovedBlock::Resource(r) => MovedBlock::Resource(r.with_instance_key(key)),
+                    MovedBlock::Module(m) => MovedBlock::Module(m.with_instance_key(key)),
+                    MovedBlock::Data(d) => MovedBlock::Data(d.with_instance_key(key)),
+                }))
+            }
+            MissingKey::Error => anyhow::bail!(
+                "{} in {} uses count/for_each, but no instance key is known (--missing-key error)",
+                self.from_address(),
+                self.file_path().display()
+            ),
+        }
+    }
+
+    /// Whether the block's name label equals the target module name
+    pub fn name_collides_with_target(&self) -> bool {
+        match self {
+            MovedBlock::Resource(r) => r.name_collides_with_target(),
+            MovedBlock::Module(m) => m.name_collides_with_target(),
+            MovedBlock::Data(d) => d.name_collides_with_target(),
+        }
+    }
+
+    /// The `from` address of the generated block, rendered without building the block
+    pub fn from_address(&self) -> String {
+        match self {
+            MovedBlock::Resource(r) => r.from_address(),
+            MovedBlock::Module(m) => m.from_address(),
+            MovedBlock::Data(d) => d.from_address(),
+        }
+    }
+
+    /// The `to` address of the generated block, rendered without building the block
+    pub fn to_address(&self) -> String {
+        match self {
+            MovedBlock::Resource(r) => r.to_address(),
+            MovedBlock::Module(m) => m.to_address(),
+            MovedBlock::Data(d) => d.to_address(),
+        }
+    }
+
+    /// Meta-arguments of the source block
+    pub fn meta(&self) -> &BlockMeta {
+        match self {
+            MovedBlock::Resource(r) => r.meta(),
+            MovedBlock::Module(m) => m.meta(),
+            MovedBlock::Data(d) => d.meta(),
+        }
+    }
+
+    /// Source file the block was read from
+    pub fn file_path(&self) -> &Path {
+        match self {
+            MovedBlock::Resource(r) => r.file_path(),
+            MovedBlock::Module(m) => m.file_path(),
+            MovedBlock::Data(d) => d.file_path(),
         }
     }
 
     /// Convert to HCL Block by delegating to the inner type
     pub fn to_block(&self) -> Result<Block> {
+        self.to_block_with(&CommentOptions::default())
+    }
+
+    /// Convert to HCL Block with custom comment options
+    pub fn to_block_with(&self, options: &CommentOptions) -> Result<Block> {
+        match self {
+            MovedBlock::Resource(r) => r.to_block_with(options),
+            MovedBlock::Module(m) => m.to_block_with(options),
+            MovedBlock::Data(d) => d.to_block_with(options),
+        }
+    }
+
+    /// Convert to a `removed` HCL Block with custom comment options
+    pub fn to_removed_block_with(&self, options: &CommentOptions) -> Result<Block> {
         match self {
-            MovedBlock::Resource(r) => r.to_block(),
-            MovedBlock::Module(m) => m.to_block(),
+            MovedBlock::Resource(r) => r.to_removed_block_with(options),
+            MovedBlock::Module(m) => m.to_removed_block_with(options),
+            MovedBlock::Data(d) => d.to_removed_block_with(options),
         }
     }
 }
 
+/// Extract the label values of a block
+///
+/// Labels can be written quoted (`"web"`) or as bare identifiers (`web`).
+/// `BlockLabel::as_str()` returns the inner value for both, without quotes,
+/// so both styles produce identical addresses.
+pub fn label_values(block: &Block) -> Vec<String> {
+    block
+        .labels
+        .iter()
+        .map(|label| label.as_str().to_string())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::moved_module::MovedModule;
     use crate::moved_resource::MovedResource;
     use anyhow::Result;
+    use hcl::edit::structure::Body;
     use std::fs;
     use tempfile::TempDir;
 
@@ -123,6 +424,41 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_moved_block_addresses() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let resource = MovedBlock::Resource(MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            path.clone(),
+            "compute".to_string(),
+        )?);
+        assert_eq!(resource.from_address(), "aws_instance.web");
+        assert_eq!(resource.to_address(), "module.compute.aws_instance.web");
+
+        let module = MovedBlock::Module(MovedModule::new(
+            vec!["web_server".to_string()],
+            path,
+            "compute".to_string(),
+        )?);
+        assert_eq!(module.from_address(), "module.web_server");
+        assert_eq!(module.to_address(), "module.compute.module.web_server");
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_display() -> Result<()> {
+        let moved_block = MovedBlock::Resource(MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?);
+        assert_eq!(
+            moved_block.to_string(),
+            "aws_instance.web -> module.compute.aws_instance.web"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_moved_block_from_block_resource() -> Result<()> {
         use crate::parser::parse_terraform_file;
@@ -172,6 +508,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_label_values_quoted_and_unquoted_are_identical() -> Result<()> {
+        let quoted = hcl::edit::parser::parse_body(r#"resource "aws_instance" "web" {}"#)?;
+        let unquoted = hcl::edit::parser::parse_body("resource aws_instance web {}")?;
+        let mixed = hcl::edit::parser::parse_body(r#"resource "aws_instance" web {}"#)?;
+
+        let expected = vec!["aws_instance".to_string(), "web".to_string()];
+        for body in [&quoted, &unquoted, &mixed] {
+            let block = body.blocks().next().expect("Expected a block");
+            assert_eq!(label_values(block), expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_from_block_unquoted_labels() -> Result<()> {
+        let body = hcl::edit::parser::parse_body("module web_server {}")?;
+        let block = body.blocks().next().expect("Expected a block");
+        let path = std::path::PathBuf::from("main.tf");
+
+        let moved_block = MovedBlock::from_block(block, &path, "a").expect("Expected Some")?;
+        assert_eq!(moved_block.from_address(), "module.web_server");
+        assert_eq!(moved_block.to_address(), "module.a.module.web_server");
+        Ok(())
+    }
+
     #[test]
     fn test_moved_block_from_block_invalid_resource() -> Result<()> {
         use crate::parser::parse_terraform_file;
@@ -222,6 +584,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_moved_block_from_block_skipped_types() -> Result<()> {
+        let body: Body = r#"
+moved {}
+removed {}
+import {}
+check "health" {}
+output "id" {}
+variable "region" {}
+locals {}
+terraform {}
+"#
+        .parse()?;
+        let file = PathBuf::from("main.tf");
+        for block in body.blocks() {
+            let ident = block.ident.value().as_str();
+            assert!(is_skipped_block_type(ident), "{} should be skipped", ident);
+            assert!(MovedBlock::from_block(block, &file, "compute").is_none());
+        }
+        assert!(!is_skipped_block_type("provider"));
+        assert!(!is_skipped_block_type("resource"));
+        Ok(())
+    }
+
     #[test]
     fn test_moved_block_to_block_resource() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
@@ -243,4 +629,183 @@ mod tests {
         assert_eq!(block.ident.value().to_string(), "moved");
         Ok(())
     }
+
+    #[test]
+    fn test_moved_block_from_block_notes_provider() -> Result<()> {
+        use hcl::edit::Decorate;
+
+        let body = hcl::edit::parser::parse_body(
+            "resource \"aws_instance\" \"web\" {\n  provider = aws.west\n}\n",
+        )?;
+        let block = body.blocks().next().expect("Expected a block");
+        let moved_block = MovedBlock::from_block(block, Path::new("main.tf"), "compute")
+            .expect("Expected a moved block")?;
+
+        let block = moved_block.to_block()?;
+        assert_eq!(
+            block.decor().prefix().map(|prefix| prefix.to_string()),
+            Some("# From: main.tf (provider aws.west)\n".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_from_block_module_directive() -> Result<()> {
+        let body = hcl::edit::parser::parse_body(
+            "resource \"aws_instance\" \"web\" {}\n\n# moved_maker: module=network\nresource \"aws_vpc\" \"main\" {}\n",
+        )?;
+        let targets: Vec<String> = body
+            .blocks()
+            .map(|block| -> Result<String> {
+                let moved_block = MovedBlock::from_block(block, Path::new("main.tf"), "compute")
+                    .expect("Expected a moved block")?;
+                Ok(moved_block.to_address())
+            })
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            targets,
+            vec![
+                "module.compute.aws_instance.web",
+                "module.network.aws_vpc.main"
+            ]
+        );
+
+        let body = hcl::edit::parser::parse_body(
+            "# moved_maker: module=1net\nresource \"aws_vpc\" \"main\" {}\n",
+        )?;
+        let block = body.blocks().next().expect("Expected a block");
+        let error = MovedBlock::from_block(block, Path::new("main.tf"), "compute")
+            .expect("Expected a moved block")
+            .unwrap_err();
+        assert!(matches!(error, MovedMakerError::InvalidBlock { .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_prefixes_target_module_name() {
+        let prefixes = |to: &str| MovePrefixes {
+            from: String::new(),
+            to: to.to_string(),
+        };
+        assert_eq!(prefixes("module.a.module.b").target_module_name(), "b");
+        assert_eq!(prefixes("module.a[0]").target_module_name(), "a");
+        assert_eq!(prefixes("").target_module_name(), "");
+    }
+
+    #[test]
+    fn test_duplicate_source_addresses_across_files() -> Result<()> {
+        let resource = |name: &str, file: &str| -> Result<MovedBlock> {
+            Ok(MovedBlock::Resource(MovedResource::new(
+                vec!["aws_instance".to_string(), name.to_string()],
+                PathBuf::from(file),
+                "compute".to_string(),
+            )?))
+        };
+        let moved_blocks = [
+            resource("web", "a.tf")?,
+            resource("api", "a.tf")?,
+            resource("api", "a.tf")?,
+            resource("web", "b.tf")?,
+        ];
+        assert_eq!(
+            duplicate_source_addresses(
+                moved_blocks
+                    .iter()
+                    .map(|m| (m.from_address(), m.file_path()))
+            ),
+            vec![(
+                "aws_instance.web".to_string(),
+                vec![PathBuf::from("a.tf"), PathBuf::from("b.tf")]
+            )]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_module_name() -> Result<()> {
+        let path = Path::new("stacks/network/vpc.tf.json");
+        assert_eq!(resolve_module_name("compute", path)?, "compute");
+        assert_eq!(resolve_module_name("mod_{dir}", path)?, "mod_network");
+        assert_eq!(resolve_module_name("{dir}_{file}", path)?, "network_vpc");
+        // The resolved name must still be a valid identifier
+        assert!(resolve_module_name("{dir}", Path::new("1st/main.tf")).is_err());
+        assert!(resolve_module_name("{dir}", Path::new("main.tf")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_meta_from_fixtures() -> Result<()> {
+        use crate::parser::parse_terraform_file;
+
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let meta = |fixture: &str| -> Result<BlockMeta> {
+            let file = fixtures.join(fixture);
+            let body = parse_terraform_file(&file)?;
+            let block = body.blocks().next().expect("Expected a block");
+            let moved_block =
+                MovedBlock::from_block(block, &file, "compute").expect("Expected Some")?;
+            Ok(moved_block.meta().clone())
+        };
+
+        let count = meta("count_resource.tf")?;
+        assert!(count.has_count);
+        assert!(!count.has_for_each);
+
+        let for_each = meta("for_each_resource.tf")?;
+        assert!(!for_each.has_count);
+        assert!(for_each.has_for_each);
+
+        assert_eq!(meta("single_resource.tf")?, BlockMeta::default());
+        assert_eq!(meta("single_module.tf")?, BlockMeta::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_apply_missing_key() -> Result<()> {
+        use crate::parser::parse_terraform_file;
+
+        let file =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/for_each_resource.tf");
+        let body = parse_terraform_file(&file)?;
+        let block = body.blocks().next().expect("Expected a block");
+        let moved_block =
+            MovedBlock::from_block(block, &file, "compute").expect("Expected Some")?;
+
+        assert!(
+            moved_block
+                .clone()
+                .apply_missing_key(MissingKey::Skip)?
+                .is_none()
+        );
+
+        let placeholder = moved_block
+            .clone()
+            .apply_missing_key(MissingKey::Placeholder)?
+            .expect("Expected a block");
+        assert_eq!(placeholder.from_address(), r#"aws_instance.web["REPLACE"]"#);
+        assert_eq!(
+            placeholder.to_address(),
+            r#"module.compute.aws_instance.web["REPLACE"]"#
+        );
+
+        let error = moved_block
+            .apply_missing_key(MissingKey::Error)
+            .expect_err("Expected an error");
+        assert!(error.to_string().contains("aws_instance.web"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_apply_missing_key_without_instances() -> Result<()> {
+        let body = hcl::edit::parser::parse_body(r#"module "web_server" {}"#)?;
+        let block = body.blocks().next().expect("Expected a block");
+        let path = PathBuf::from("main.tf");
+        let moved_block = MovedBlock::from_block(block, &path, "a").expect("Expected Some")?;
+
+        let unchanged = moved_block
+            .apply_missing_key(MissingKey::Error)?
+            .expect("Expected a block");
+        assert_eq!(unchanged.from_address(), "module.web_server");
+        Ok(())
+    }
 }
@@ -0,0 +1,241 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Data source block model for moved blocks.
+//!
+//! `MovedData` encapsulates all logic related to data blocks,
+//! including validation, expression building, and block conversion.
+
+use crate::address::{AddressBuilder, AddressIndex, innermost_module_name, module_path};
+use crate::block_meta::BlockMeta;
+use crate::to_moved_block::{Direction, ToMovedBlock};
+use anyhow::Result;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Represents a data block that needs to be moved to a module
+#[derive(Debug, Clone)]
+pub struct MovedData {
+    labels: Vec<String>,
+    file_path: PathBuf,
+    target_module_name: String,
+    from_prefix: String,
+    to_prefix: String,
+    meta: BlockMeta,
+    instance_key: Option<AddressIndex>,
+}
+
+impl MovedData {
+    /// Create a new MovedData
+    ///
+    /// # Arguments
+    /// * `labels` - All labels from the block (must have at least 2 for data sources)
+    /// * `file_path` - Source file path (for comment)
+    /// * `target_module_name` - Target module name
+    pub fn new(
+        labels: Vec<String>,
+        file_path: PathBuf,
+        target_module_name: String,
+    ) -> Result<Self> {
+        if labels.len() < 2 {
+            return Err(anyhow::anyhow!("Data blocks must have at least 2 labels"));
+        }
+        let to_prefix = module_path(&target_module_name);
+        Ok(Self {
+            labels,
+            file_path,
+            target_module_name,
+            from_prefix: String::new(),
+            to_prefix,
+            meta: BlockMeta::default(),
+            instance_key: None,
+        })
+    }
+
+    /// Use formatted module paths (`""` for the root module) in front of the
+    /// "from" and "to" addresses instead of the default `""` and `module.<target>`
+    pub fn with_prefixes(mut self, from_prefix: String, to_prefix: String) -> Self {
+        self.from_prefix = from_prefix;
+        self.to_prefix = to_prefix;
+        self
+    }
+
+    /// Move out of the target module instead of into it, swapping the "from" and "to" prefixes
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        if direction == Direction::Outof {
+            std::mem::swap(&mut self.from_prefix, &mut self.to_prefix);
+        }
+        self
+    }
+
+    /// Use the meta-arguments read from the source block body
+    pub fn with_meta(mut self, meta: BlockMeta) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Address a single instance, e.g. `aws_instance.web[0]` or `aws_instance.web["a"]`
+    pub fn with_instance_key(mut self, key: AddressIndex) -> Self {
+        self.instance_key = Some(key);
+        self
+    }
+
+    /// Meta-arguments of the source block
+    pub fn meta(&self) -> &BlockMeta {
+        &self.meta
+    }
+
+    /// Convenience accessor for data source type (labels[0])
+    pub fn data_type(&self) -> &str {
+        &self.labels[0]
+    }
+
+    /// Convenience accessor for data source name (labels[1])
+    pub fn data_name(&self) -> &str {
+        &self.labels[1]
+    }
+
+    /// Name of the module the data source moves into
+    pub fn target_module_name(&self) -> &str {
+        &self.target_module_name
+    }
+
+    /// Whether the data source name equals the target module name
+    pub fn name_collides_with_target(&self) -> bool {
+        self.labels[1] == innermost_module_name(&self.target_module_name)
+    }
+
+    /// Format the "from" address (private method)
+    fn format_from_address(&self) -> String {
+        AddressBuilder::new().format_with_prefix_and_index(
+            &self.from_prefix,
+            &["data", &self.labels[0], &self.labels[1]],
+            self.instance_key.as_slice(),
+        )
+    }
+
+    /// Format the "to" address (private method)
+    fn format_to_address(&self) -> String {
+        AddressBuilder::new().format_with_prefix_and_index(
+            &self.to_prefix,
+            &["data", &self.labels[0], &self.labels[1]],
+            self.instance_key.as_slice(),
+        )
+    }
+}
+
+impl ToMovedBlock for MovedData {
+    fn from_address(&self) -> String {
+        self.format_from_address()
+    }
+
+    fn to_address(&self) -> String {
+        self.format_to_address()
+    }
+
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    fn kind(&self) -> &'static str {
+        "data"
+    }
+
+    fn risk(&self) -> Option<&'static str> {
+        self.name_collides_with_target()
+            .then_some("name collides with target module")
+    }
+
+    fn source_comment(&self) -> Option<&str> {
+        self.meta.comment.as_deref()
+    }
+
+    fn provider(&self) -> Option<&str> {
+        self.meta.provider.as_deref()
+    }
+
+    fn source_type(&self) -> &str {
+        &self.labels[0]
+    }
+
+    fn source_name(&self) -> &str {
+        &self.labels[1]
+    }
+
+    // to_block() uses the default implementation from the trait
+}
+
+/// Concise `from -> to` summary for logs and messages
+impl fmt::Display for MovedData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from_address(), self.to_address())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_moved_data_new() -> Result<()> {
+        let path = PathBuf::from("main.tf");
+        let labels = vec!["aws_ami".to_string(), "ubuntu".to_string()];
+        let data = MovedData::new(labels, path, "compute".to_string())?;
+        assert_eq!(data.data_type(), "aws_ami");
+        assert_eq!(data.data_name(), "ubuntu");
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_data_new_invalid_labels() {
+        let path = PathBuf::from("main.tf");
+        let labels = vec!["aws_ami".to_string()]; // Only 1 label, need 2+
+        assert!(MovedData::new(labels, path, "compute".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_moved_data_addresses() -> Result<()> {
+        let path = PathBuf::from("main.tf");
+        let labels = vec!["aws_ami".to_string(), "ubuntu".to_string()];
+        let data = MovedData::new(labels, path, "compute".to_string())?;
+        assert_eq!(
+            data.from_expression()?.to_string().trim(),
+            "data.aws_ami.ubuntu"
+        );
+        assert_eq!(
+            data.to_expression()?.to_string().trim(),
+            "module.compute.data.aws_ami.ubuntu"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_data_to_block_comment_with_kind() -> Result<()> {
+        use crate::to_moved_block::CommentOptions;
+        use hcl::edit::structure::Body;
+
+        let path = PathBuf::from("main.tf");
+        let labels = vec!["aws_ami".to_string(), "ubuntu".to_string()];
+        let data = MovedData::new(labels, path, "compute".to_string())?;
+        let block = data.to_block_with(&CommentOptions {
+            include_kind: true,
+            ..Default::default()
+        })?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.contains("# From: main.tf (data)\n"));
+        Ok(())
+    }
+}
@@ -17,10 +17,11 @@
 //! `MovedModule` encapsulates all logic related to module blocks,
 //! including validation, expression building, and block conversion.
 
-use crate::address::AddressBuilder;
-use crate::to_moved_block::ToMovedBlock;
+use crate::address::{AddressBuilder, AddressIndex, innermost_module_name, module_path};
+use crate::block_meta::BlockMeta;
+use crate::to_moved_block::{Direction, ToMovedBlock};
 use anyhow::Result;
-use hcl::edit::expr::Expression;
+use std::fmt;
 use std::path::{Path, PathBuf};
 
 /// Represents a module block that needs to be moved to a module
@@ -29,6 +30,10 @@ pub struct MovedModule {
     labels: Vec<String>,
     file_path: PathBuf,
     target_module_name: String,
+    from_prefix: String,
+    to_prefix: String,
+    meta: BlockMeta,
+    instance_key: Option<AddressIndex>,
 }
 
 impl MovedModule {
@@ -46,65 +51,161 @@ impl MovedModule {
         if labels.is_empty() {
             return Err(anyhow::anyhow!("Module blocks must have at least 1 label"));
         }
+        let to_prefix = module_path(&target_module_name);
         Ok(Self {
             labels,
             file_path,
             target_module_name,
+            from_prefix: String::new(),
+            to_prefix,
+            meta: BlockMeta::default(),
+            instance_key: None,
         })
     }
 
+    /// Use formatted module paths (`""` for the root module) in front of the
+    /// "from" and "to" addresses instead of the default `""` and `module.<target>`
+    pub fn with_prefixes(mut self, from_prefix: String, to_prefix: String) -> Self {
+        self.from_prefix = from_prefix;
+        self.to_prefix = to_prefix;
+        self
+    }
+
+    /// Move out of the target module instead of into it, swapping the "from" and "to" prefixes
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        if direction == Direction::Outof {
+            std::mem::swap(&mut self.from_prefix, &mut self.to_prefix);
+        }
+        self
+    }
+
+    /// Use the meta-arguments read from the source block body
+    pub fn with_meta(mut self, meta: BlockMeta) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Address a single instance, e.g. `aws_instance.web[0]` or `aws_instance.web["a"]`
+    pub fn with_instance_key(mut self, key: AddressIndex) -> Self {
+        self.instance_key = Some(key);
+        self
+    }
+
+    /// Meta-arguments of the source block
+    pub fn meta(&self) -> &BlockMeta {
+        &self.meta
+    }
+
     /// Access all labels
-    #[allow(dead_code)] // Used in tests
     pub fn labels(&self) -> &[String] {
         &self.labels
     }
 
     /// Convenience accessor for module name (labels[0])
-    #[allow(dead_code)] // Used in tests
     pub fn module_name_local(&self) -> &str {
         &self.labels[0]
     }
 
-    /// Build the "from" expression (private method)
-    fn build_from_expression(&self) -> Expression {
-        AddressBuilder::new().build(&["module", &self.labels[0]])
+    /// Name of the module the module moves into
+    pub fn target_module_name(&self) -> &str {
+        &self.target_module_name
+    }
+
+    /// Whether the module name equals the target module name
+    ///
+    /// `module.web.module.web` is a valid address but easy to misread.
+    pub fn name_collides_with_target(&self) -> bool {
+        self.labels[0] == innermost_module_name(&self.target_module_name)
+    }
+
+    /// Format the "from" address (private method)
+    fn format_from_address(&self) -> String {
+        AddressBuilder::new().format_with_prefix_and_index(
+            &self.from_prefix,
+            &["module", &self.labels[0]],
+            self.instance_key.as_slice(),
+        )
     }
 
-    /// Build the "to" expression (private method)
-    fn build_to_expression(&self) -> Expression {
-        AddressBuilder::new().build(&[
-            "module",
-            &self.target_module_name,
-            "module",
-            &self.labels[0],
-        ])
+    /// Format the "to" address (private method)
+    fn format_to_address(&self) -> String {
+        AddressBuilder::new().format_with_prefix_and_index(
+            &self.to_prefix,
+            &["module", &self.labels[0]],
+            self.instance_key.as_slice(),
+        )
     }
 }
 
 impl ToMovedBlock for MovedModule {
-    fn from_expression(&self) -> Expression {
-        self.build_from_expression()
+    fn from_address(&self) -> String {
+        self.format_from_address()
     }
 
-    fn to_expression(&self) -> Expression {
-        self.build_to_expression()
+    fn to_address(&self) -> String {
+        self.format_to_address()
     }
 
     fn file_path(&self) -> &Path {
         &self.file_path
     }
 
+    fn kind(&self) -> &'static str {
+        "module"
+    }
+
+    fn risk(&self) -> Option<&'static str> {
+        self.name_collides_with_target()
+            .then_some("name collides with target module")
+    }
+
+    fn source_comment(&self) -> Option<&str> {
+        self.meta.comment.as_deref()
+    }
+
+    fn provider(&self) -> Option<&str> {
+        self.meta.provider.as_deref()
+    }
+
+    fn source_type(&self) -> &str {
+        "module"
+    }
+
+    fn source_name(&self) -> &str {
+        &self.labels[0]
+    }
+
     // to_block() uses the default implementation from the trait
 }
 
+/// Concise `from -> to` summary for logs and messages
+impl fmt::Display for MovedModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from_address(), self.to_address())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::to_moved_block::CommentOptions;
     use anyhow::Result;
     use hcl::edit::Decorate;
+    use hcl::edit::expr::Expression;
     use hcl::edit::structure::Body;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn test_moved_module_display() -> Result<()> {
+        let labels = vec!["web_server".to_string()];
+        let module = MovedModule::new(labels, PathBuf::from("main.tf"), "compute".to_string())?;
+        assert_eq!(
+            module.to_string(),
+            "module.web_server -> module.compute.module.web_server"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_moved_module_new() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
@@ -123,12 +224,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_moved_module_name_collides_with_target() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["web_server".to_string()];
+        let colliding = MovedModule::new(labels.clone(), path.clone(), "web_server".to_string())?;
+        let distinct = MovedModule::new(labels, path, "a".to_string())?;
+        assert!(colliding.name_collides_with_target());
+        assert!(!distinct.name_collides_with_target());
+        Ok(())
+    }
+
     #[test]
     fn test_moved_module_build_from_expression() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
         let labels = vec!["web_server".to_string()];
         let module = MovedModule::new(labels, path, "a".to_string())?;
-        let expr = module.from_expression();
+        let expr = module.from_expression()?;
         assert!(matches!(expr, Expression::Traversal(_)));
         Ok(())
     }
@@ -138,11 +250,53 @@ mod tests {
         let path = std::path::PathBuf::from("main.tf");
         let labels = vec!["web_server".to_string()];
         let module = MovedModule::new(labels, path, "a".to_string())?;
-        let expr = module.to_expression();
+        let expr = module.to_expression()?;
         assert!(matches!(expr, Expression::Traversal(_)));
         Ok(())
     }
 
+    #[test]
+    fn test_moved_module_with_instance_key() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["web_server".to_string()];
+        let module = MovedModule::new(labels.clone(), path.clone(), "compute".to_string())?
+            .with_instance_key(AddressIndex::String("a".to_string()));
+        assert_eq!(
+            module.from_expression()?.to_string().trim(),
+            r#"module.web_server["a"]"#
+        );
+        assert_eq!(
+            module.to_expression()?.to_string().trim(),
+            r#"module.compute.module.web_server["a"]"#
+        );
+
+        let module = MovedModule::new(labels, path, "compute".to_string())?
+            .with_instance_key(AddressIndex::Number(0));
+        let output = Body::builder()
+            .block(module.to_block()?)
+            .build()
+            .to_string();
+        assert!(output.contains("  from = module.web_server[0]\n"));
+        assert!(output.contains("  to   = module.compute.module.web_server[0]\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_module_direction() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["web_server".to_string()];
+        let module = MovedModule::new(labels, path, "compute".to_string())?;
+
+        let into = module.clone().with_direction(Direction::Into);
+        assert_eq!(into.from_address(), "module.web_server");
+        assert_eq!(into.to_address(), "module.compute.module.web_server");
+
+        let outof = module.with_direction(Direction::Outof);
+        assert_eq!(outof.from_address(), "module.compute.module.web_server");
+        assert_eq!(outof.to_address(), "module.web_server");
+        Ok(())
+    }
+
     #[test]
     fn test_moved_module_to_block() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
@@ -167,6 +321,20 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_moved_module_to_block_comment_with_kind() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["web_server".to_string()];
+        let module = MovedModule::new(labels, path, "a".to_string())?;
+        let block = module.to_block_with(&CommentOptions {
+            include_kind: true,
+            ..Default::default()
+        })?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.contains("# From: main.tf (module)\n"));
+        Ok(())
+    }
+
     #[test]
     fn test_moved_module_to_block_has_indented_attributes() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
@@ -191,7 +359,7 @@ mod tests {
         assert!(output.contains("# From: main.tf"));
         assert!(output.contains("moved {"));
         assert!(output.contains("from = module.web_server"));
-        assert!(output.contains("to = module.a.module.web_server"));
+        assert!(output.contains("to   = module.a.module.web_server"));
         Ok(())
     }
 }
@@ -17,10 +17,11 @@
 //! `MovedResource` encapsulates all logic related to resource blocks,
 //! including validation, expression building, and block conversion.
 
-use crate::address::AddressBuilder;
-use crate::to_moved_block::ToMovedBlock;
+use crate::address::{AddressBuilder, AddressIndex, innermost_module_name, module_path};
+use crate::block_meta::BlockMeta;
+use crate::to_moved_block::{Direction, ToMovedBlock};
 use anyhow::Result;
-use hcl::edit::expr::Expression;
+use std::fmt;
 use std::path::{Path, PathBuf};
 
 /// Represents a resource block that needs to be moved to a module
@@ -29,6 +30,11 @@ pub struct MovedResource {
     labels: Vec<String>,
     file_path: PathBuf,
     target_module_name: String,
+    target_type: Option<String>,
+    from_prefix: String,
+    to_prefix: String,
+    meta: BlockMeta,
+    instance_key: Option<AddressIndex>,
 }
 
 impl MovedResource {
@@ -48,71 +54,185 @@ impl MovedResource {
                 "Resource blocks must have at least 2 labels"
             ));
         }
+        let to_prefix = module_path(&target_module_name);
         Ok(Self {
             labels,
             file_path,
             target_module_name,
+            target_type: None,
+            from_prefix: String::new(),
+            to_prefix,
+            meta: BlockMeta::default(),
+            instance_key: None,
         })
     }
 
+    /// Use `target_type` instead of the original type in the "to" address
+    ///
+    /// The "from" address keeps the original type, since that is what is in state.
+    pub fn with_target_type(mut self, target_type: String) -> Self {
+        self.target_type = Some(target_type);
+        self
+    }
+
+    /// Use formatted module paths (`""` for the root module) in front of the
+    /// "from" and "to" addresses instead of the default `""` and `module.<target>`
+    pub fn with_prefixes(mut self, from_prefix: String, to_prefix: String) -> Self {
+        self.from_prefix = from_prefix;
+        self.to_prefix = to_prefix;
+        self
+    }
+
+    /// Move out of the target module instead of into it, swapping the "from" and "to" prefixes
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        if direction == Direction::Outof {
+            std::mem::swap(&mut self.from_prefix, &mut self.to_prefix);
+        }
+        self
+    }
+
+    /// Use the meta-arguments read from the source block body
+    pub fn with_meta(mut self, meta: BlockMeta) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Address a single instance, e.g. `aws_instance.web[0]` or `aws_instance.web["a"]`
+    pub fn with_instance_key(mut self, key: AddressIndex) -> Self {
+        self.instance_key = Some(key);
+        self
+    }
+
+    /// Meta-arguments of the source block
+    pub fn meta(&self) -> &BlockMeta {
+        &self.meta
+    }
+
     /// Access all labels
-    #[allow(dead_code)] // Used in tests
     pub fn labels(&self) -> &[String] {
         &self.labels
     }
 
     /// Convenience accessor for resource type (labels[0])
-    #[allow(dead_code)] // Used in tests
     pub fn resource_type(&self) -> &str {
         &self.labels[0]
     }
 
     /// Convenience accessor for resource name (labels[1])
-    #[allow(dead_code)] // Used in tests
     pub fn resource_name(&self) -> &str {
         &self.labels[1]
     }
 
-    /// Build the "from" expression (private method)
-    fn build_from_expression(&self) -> Expression {
-        AddressBuilder::new().build(&[&self.labels[0], &self.labels[1]])
+    /// Name of the module the resource moves into
+    pub fn target_module_name(&self) -> &str {
+        &self.target_module_name
     }
 
-    /// Build the "to" expression (private method)
-    fn build_to_expression(&self) -> Expression {
-        AddressBuilder::new().build(&[
-            "module",
-            &self.target_module_name,
-            &self.labels[0],
-            &self.labels[1],
-        ])
+    /// Whether the resource name equals the target module name
+    ///
+    /// `module.web.aws_instance.web` is a valid address but easy to misread.
+    pub fn name_collides_with_target(&self) -> bool {
+        self.labels[1] == innermost_module_name(&self.target_module_name)
+    }
+
+    /// Format the "from" address (private method)
+    fn format_from_address(&self) -> String {
+        AddressBuilder::new().format_with_prefix_and_index(
+            &self.from_prefix,
+            &[&self.labels[0], &self.labels[1]],
+            self.instance_key.as_slice(),
+        )
+    }
+
+    /// Format the "to" address (private method)
+    fn format_to_address(&self) -> String {
+        let resource_type = self.target_type.as_deref().unwrap_or(&self.labels[0]);
+        AddressBuilder::new().format_with_prefix_and_index(
+            &self.to_prefix,
+            &[resource_type, &self.labels[1]],
+            self.instance_key.as_slice(),
+        )
     }
 }
 
 impl ToMovedBlock for MovedResource {
-    fn from_expression(&self) -> Expression {
-        self.build_from_expression()
+    fn from_address(&self) -> String {
+        self.format_from_address()
     }
 
-    fn to_expression(&self) -> Expression {
-        self.build_to_expression()
+    fn to_address(&self) -> String {
+        self.format_to_address()
     }
 
     fn file_path(&self) -> &Path {
         &self.file_path
     }
 
+    fn kind(&self) -> &'static str {
+        "resource"
+    }
+
+    fn risk(&self) -> Option<&'static str> {
+        if self
+            .target_type
+            .as_ref()
+            .is_some_and(|target_type| *target_type != self.labels[0])
+        {
+            Some("renames resource type")
+        } else if self.name_collides_with_target() {
+            Some("name collides with target module")
+        } else {
+            None
+        }
+    }
+
+    fn source_comment(&self) -> Option<&str> {
+        self.meta.comment.as_deref()
+    }
+
+    fn provider(&self) -> Option<&str> {
+        self.meta.provider.as_deref()
+    }
+
+    fn source_type(&self) -> &str {
+        &self.labels[0]
+    }
+
+    fn source_name(&self) -> &str {
+        &self.labels[1]
+    }
+
     // to_block() uses the default implementation from the trait
 }
 
+/// Concise `from -> to` summary for logs and messages
+impl fmt::Display for MovedResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from_address(), self.to_address())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::to_moved_block::CommentOptions;
     use anyhow::Result;
     use hcl::edit::Decorate;
+    use hcl::edit::expr::Expression;
     use hcl::edit::structure::Body;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn test_moved_resource_display() -> Result<()> {
+        let labels = vec!["aws_instance".to_string(), "web".to_string()];
+        let resource = MovedResource::new(labels, PathBuf::from("main.tf"), "compute".to_string())?;
+        assert_eq!(
+            resource.to_string(),
+            "aws_instance.web -> module.compute.aws_instance.web"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_moved_resource_new() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
@@ -132,12 +252,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_moved_resource_name_collides_with_target() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_instance".to_string(), "web".to_string()];
+        let colliding = MovedResource::new(labels.clone(), path.clone(), "web".to_string())?;
+        let distinct = MovedResource::new(labels, path, "compute".to_string())?;
+        assert!(colliding.name_collides_with_target());
+        assert!(!distinct.name_collides_with_target());
+        Ok(())
+    }
+
     #[test]
     fn test_moved_resource_build_from_expression() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
         let labels = vec!["aws_instance".to_string(), "web".to_string()];
         let resource = MovedResource::new(labels, path, "compute".to_string())?;
-        let expr = resource.from_expression();
+        let expr = resource.from_expression()?;
         assert!(matches!(expr, Expression::Traversal(_)));
         Ok(())
     }
@@ -147,11 +278,90 @@ mod tests {
         let path = std::path::PathBuf::from("main.tf");
         let labels = vec!["aws_instance".to_string(), "web".to_string()];
         let resource = MovedResource::new(labels, path, "compute".to_string())?;
-        let expr = resource.to_expression();
+        let expr = resource.to_expression()?;
         assert!(matches!(expr, Expression::Traversal(_)));
         Ok(())
     }
 
+    #[test]
+    fn test_moved_resource_with_target_type_is_asymmetric() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_alb".to_string(), "web".to_string()];
+        let resource = MovedResource::new(labels, path, "compute".to_string())?
+            .with_target_type("aws_lb".to_string());
+        let block = resource.to_block()?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.contains("from = aws_alb.web"));
+        assert!(output.contains("to   = module.compute.aws_lb.web"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_resource_direction() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_instance".to_string(), "web".to_string()];
+        let resource = MovedResource::new(labels, path, "compute".to_string())?;
+
+        let into = resource.clone().with_direction(Direction::Into);
+        assert_eq!(into.from_address(), "aws_instance.web");
+        assert_eq!(into.to_address(), "module.compute.aws_instance.web");
+
+        let outof = resource.with_direction(Direction::Outof);
+        assert_eq!(outof.from_address(), "module.compute.aws_instance.web");
+        assert_eq!(outof.to_address(), "aws_instance.web");
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_resource_nested_module_name() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_instance".to_string(), "compute".to_string()];
+        let resource = MovedResource::new(labels, path, "platform.compute".to_string())?;
+        let block = resource.to_block()?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.contains("to   = module.platform.module.compute.aws_instance.compute"));
+        assert!(resource.name_collides_with_target());
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_resource_with_instance_key() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_instance".to_string(), "web".to_string()];
+        let resource = MovedResource::new(labels.clone(), path.clone(), "compute".to_string())?
+            .with_instance_key(AddressIndex::Number(0));
+        let block = resource.to_block()?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.contains("from = aws_instance.web[0]"));
+        assert!(output.contains("to   = module.compute.aws_instance.web[0]"));
+
+        let resource = MovedResource::new(labels, path, "compute".to_string())?
+            .with_instance_key(AddressIndex::String("a".to_string()));
+        assert_eq!(
+            resource.to_expression()?.to_string().trim(),
+            r#"module.compute.aws_instance.web["a"]"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_resource_non_identifier_labels() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_instance".to_string(), "1web".to_string()];
+        let resource = MovedResource::new(labels, path.clone(), "compute".to_string())?;
+        assert_eq!(resource.from_address(), r#"aws_instance["1web"]"#);
+        assert_eq!(
+            resource.to_expression()?.to_string().trim(),
+            r#"module.compute.aws_instance["1web"]"#
+        );
+
+        // A type that is not an identifier cannot start an address, it fails instead of panicking
+        let labels = vec!["1aws_instance".to_string(), "web".to_string()];
+        let resource = MovedResource::new(labels, path, "compute".to_string())?;
+        assert!(resource.to_block().is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_moved_resource_to_block() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
@@ -176,6 +386,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_moved_resource_to_block_comment_with_kind() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_instance".to_string(), "web".to_string()];
+        let resource = MovedResource::new(labels, path, "compute".to_string())?;
+        let block = resource.to_block_with(&CommentOptions {
+            include_kind: true,
+            ..Default::default()
+        })?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.contains("# From: main.tf (resource)\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_resource_to_block_comment_with_provider() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_instance".to_string(), "web".to_string()];
+        let resource =
+            MovedResource::new(labels, path, "compute".to_string())?.with_meta(BlockMeta {
+                provider: Some("aws.west".to_string()),
+                ..Default::default()
+            });
+
+        let output = Body::builder()
+            .block(resource.to_block()?)
+            .build()
+            .to_string();
+        assert!(output.contains("# From: main.tf (provider aws.west)\n"));
+
+        let block = resource.to_block_with(&CommentOptions {
+            include_kind: true,
+            ..Default::default()
+        })?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.contains("# From: main.tf (resource, provider aws.west)\n"));
+        Ok(())
+    }
+
     #[test]
     fn test_moved_resource_to_block_has_indented_attributes() -> Result<()> {
         let path = std::path::PathBuf::from("main.tf");
@@ -200,7 +449,49 @@ mod tests {
         assert!(output.contains("# From: main.tf"));
         assert!(output.contains("moved {"));
         assert!(output.contains("from = aws_instance.web"));
-        assert!(output.contains("to = module.compute.aws_instance.web"));
+        assert!(output.contains("to   = module.compute.aws_instance.web"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_resource_risk() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_alb".to_string(), "web".to_string()];
+        let resource = MovedResource::new(labels.clone(), path.clone(), "compute".to_string())?;
+        assert_eq!(resource.risk(), None);
+
+        let renamed = resource.with_target_type("aws_lb".to_string());
+        assert_eq!(renamed.risk(), Some("renames resource type"));
+
+        let colliding = MovedResource::new(labels, path, "web".to_string())?;
+        assert_eq!(colliding.risk(), Some("name collides with target module"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_resource_to_block_marks_risky() -> Result<()> {
+        let path = std::path::PathBuf::from("main.tf");
+        let labels = vec!["aws_alb".to_string(), "web".to_string()];
+        let resource = MovedResource::new(labels, path, "compute".to_string())?
+            .with_target_type("aws_lb".to_string());
+        let options = CommentOptions {
+            mark_risky: true,
+            ..Default::default()
+        };
+        let output = Body::builder()
+            .block(resource.to_block_with(&options)?)
+            .build()
+            .to_string();
+        assert!(output.starts_with(
+            "# From: main.tf\n# TODO: review this move (renames resource type)\nmoved"
+        ));
+
+        // Without the option the comment is left out
+        let output = Body::builder()
+            .block(resource.to_block()?)
+            .build()
+            .to_string();
+        assert!(!output.contains("TODO"));
         Ok(())
     }
 }
@@ -16,20 +16,568 @@
 //!
 //! This module provides functions to format the final output from moved blocks.
 
-use hcl::edit::structure::{Block, Body};
+use crate::address::{AddressBuilder, check_terraform11_address};
+use crate::cli::{AddressStyle, OutputFormat};
+use crate::existing_moves::ExistingMoves;
+use crate::moved_block::MovedBlock;
+use crate::structured::MovedBlockRecord;
+use crate::terraform_files::Extensions;
+use crate::to_moved_block::TO_KEY_SUFFIX;
+use anyhow::{Context, Result};
+use hcl::edit::expr::Expression;
+use hcl::edit::parser::parse_body;
+use hcl::edit::structure::{Attribute, Block, Body};
+use hcl::edit::{Decorate, Ident};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 /// Build the output Body from collected moved blocks
+///
+/// Blocks after the first get a blank line above their comment, like `terraform fmt` spacing.
 pub fn build_output_body(blocks: &[Block]) -> Body {
     let mut builder = Body::builder();
+    for (index, block) in blocks.iter().enumerate() {
+        let mut block = block.clone();
+        if index > 0 {
+            prepend_prefix(&mut block, "\n");
+        }
+        builder = builder.block(block);
+    }
+    builder.build()
+}
+
+/// Writes HCL blocks one at a time as they are generated, for large configurations
+///
+/// The written text is the same as `build_output_body` followed by `with_trailing_newline`
+/// for the same blocks, but the body is never held in memory as a whole. Callers still keep
+/// whatever they need per block, e.g. addresses for the duplicate check.
+pub struct BlockStream<W: Write> {
+    out: W,
+    written: usize,
+    validate: bool,
+}
+
+impl<W: Write> BlockStream<W> {
+    /// Write to `out`, checking each block with `validate_hcl` when `validate` is set
+    pub fn new(out: W, validate: bool) -> Self {
+        Self {
+            out,
+            written: 0,
+            validate,
+        }
+    }
+
+    /// Render and write one block, with a blank line above it unless it is the first
+    pub fn write(&mut self, block: &Block) -> Result<()> {
+        let mut block = block.clone();
+        if self.written > 0 {
+            prepend_prefix(&mut block, "\n");
+        }
+        let hcl = Body::builder().block(block).build().to_string();
+        if self.validate {
+            validate_hcl(&hcl)?;
+        }
+        self.out
+            .write_all(hcl.as_bytes())
+            .context("Failed to write output")?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Number of blocks written so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Flush and return the writer
+    pub fn finish(mut self) -> Result<W> {
+        self.out.flush().context("Failed to write output")?;
+        Ok(self.out)
+    }
+}
+
+/// Put `text` in front of the block's decor prefix, ahead of its comment
+fn prepend_prefix(block: &mut Block, text: &str) {
+    let existing = block
+        .decor()
+        .prefix()
+        .map(|prefix| prefix.to_string())
+        .unwrap_or_default();
+    block
+        .decor_mut()
+        .set_prefix(format!("{}{}", text, existing));
+}
+
+/// Build the header comment put above generated HCL, e.g.
+/// `# Generated by moved_maker for module "compute" (3 blocks)`
+///
+/// Names every target module once, so templated module names list each resolved name.
+pub fn build_header(blocks: &[(MovedBlock, Block)]) -> String {
+    let modules: BTreeSet<String> = blocks
+        .iter()
+        .map(|(moved_block, _)| format!("\"{}\"", moved_block.target_module_name()))
+        .collect();
+    let modules: Vec<String> = modules.into_iter().collect();
+    format!(
+        "# Generated by moved_maker for {} {} ({} {})\n\n",
+        if modules.len() == 1 {
+            "module"
+        } else {
+            "modules"
+        },
+        modules.join(", "),
+        blocks.len(),
+        if blocks.len() == 1 { "block" } else { "blocks" }
+    )
+}
+
+/// Build the `module` block stub that the moves target
+///
+/// ```hcl
+/// module "compute" {
+///   source = "./modules/compute"
+/// }
+/// ```
+pub fn build_module_stub(module_name: &str, source: &str) -> Block {
+    let mut source_attr = Attribute::new(Ident::new("source"), Expression::from(source));
+    source_attr.decor_mut().set_prefix("  ");
+    Block::builder(Ident::new("module"))
+        .label(module_name)
+        .attribute(source_attr)
+        .build()
+}
+
+/// Build the output Body with blocks ordered by group
+///
+/// `key` maps each block to a group. Groups are emitted in key order, blocks keep
+/// their relative order within a group, and the first block of every group gets a
+/// `# <key>` header comment above its own comment.
+pub fn build_output_body_grouped<K, F>(blocks: &[Block], key: F) -> Body
+where
+    K: Ord + Display,
+    F: Fn(&Block) -> K,
+{
+    let mut groups: BTreeMap<K, Vec<Block>> = BTreeMap::new();
     for block in blocks {
-        builder = builder.block(block.clone());
+        groups.entry(key(block)).or_default().push(block.clone());
+    }
+    build_sections(groups, |group| format!("# {}\n", group))
+}
+
+/// Build the output Body in sections per source directory
+///
+/// Each section starts with a `# Directory: modules/network` comment, directories
+/// being relative to the first of `roots` that contains them (`.` for a root itself).
+/// Sections come in directory order, blocks keep their relative order within one and
+/// their own `# From:` comments.
+pub fn build_output_body_by_dir(blocks: &[(MovedBlock, Block)], roots: &[PathBuf]) -> Body {
+    let mut groups: BTreeMap<String, Vec<Block>> = BTreeMap::new();
+    for (moved_block, block) in blocks {
+        groups
+            .entry(source_dir(moved_block.file_path(), roots))
+            .or_default()
+            .push(block.clone());
+    }
+    build_sections(groups, |dir| format!("# Directory: {}\n", dir))
+}
+
+/// Directory of `file` relative to the first root containing it, joined with `/`
+fn source_dir(file: &Path, roots: &[PathBuf]) -> String {
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let relative = roots
+        .iter()
+        .find_map(|root| dir.strip_prefix(root).ok())
+        .unwrap_or(dir);
+    let relative = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if relative.is_empty() {
+        ".".to_string()
+    } else {
+        relative
+    }
+}
+
+/// Emit the groups in order, putting `header` above the first block of each
+///
+/// Blocks are separated by blank lines like in `build_output_body()`.
+fn build_sections<K>(groups: BTreeMap<K, Vec<Block>>, header: impl Fn(&K) -> String) -> Body {
+    let mut builder = Body::builder();
+    let mut first = true;
+    for (group, group_blocks) in groups {
+        for (index, mut block) in group_blocks.into_iter().enumerate() {
+            if index == 0 {
+                prepend_prefix(&mut block, &header(&group));
+            }
+            if !first {
+                prepend_prefix(&mut block, "\n");
+            }
+            first = false;
+            builder = builder.block(block);
+        }
     }
     builder.build()
 }
 
+/// Build the Terraform JSON configuration for collected moved blocks
+///
+/// Produces the `{"moved": [{"from": ..., "to": ...}]}` shape accepted in `.tf.json` files.
+pub fn build_tfjson_output(moved_blocks: &[MovedBlock]) -> Value {
+    let moved: Vec<Value> = moved_blocks
+        .iter()
+        .map(|m| json!({ "from": m.from_address(), "to": m.to_address() }))
+        .collect();
+    json!({ "moved": moved })
+}
+
+/// Quote a string for POSIX shells, e.g. `a'b` becomes `'a'\''b'`
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Build a shell script with one `terraform state mv` command per moved block
+///
+/// With [`AddressStyle::Terraform11`] every address must be one Terraform 0.11 can run.
+pub fn build_statemv_output(
+    moved_blocks: &[MovedBlock],
+    address_style: AddressStyle,
+) -> Result<String> {
+    let mut script = String::from("#!/bin/sh\nset -e\n");
+    for moved_block in moved_blocks {
+        let from = moved_block.from_address();
+        let to = moved_block.to_address();
+        if address_style == AddressStyle::Terraform11 {
+            check_terraform11_address(&from)?;
+            check_terraform11_address(&to)?;
+        }
+        script.push_str(&format!(
+            "terraform state mv {} {}\n",
+            shell_quote(&from),
+            shell_quote(&to)
+        ));
+    }
+    Ok(script)
+}
+
+/// Banner at the top of the rollback file
+const ROLLBACK_BANNER: &str = "# Rollback moves generated by moved_maker: the forward moves with from/to swapped.\n# Apply them instead of the forward moves to undo the migration.\n";
+
+/// Build the rollback file for collected moved blocks, in HCL
+///
+/// Every move is reversed (`from` and `to` swapped) and keeps its `# From:` comment.
+pub fn build_rollback_output(blocks: &[(MovedBlock, Block)]) -> Result<String> {
+    let attribute = |key: &str, address: &str| -> Result<Attribute> {
+        let mut attr = Attribute::new(Ident::new(key), AddressBuilder::new().parse(address)?);
+        attr.decor_mut().set_prefix("  ");
+        if key == "to" {
+            attr.key.decor_mut().set_suffix(TO_KEY_SUFFIX);
+        }
+        Ok(attr)
+    };
+    let rollback_blocks = blocks
+        .iter()
+        .map(|(moved_block, block)| {
+            let mut rollback = Block::builder(Ident::new("moved"))
+                .attribute(attribute("from", &moved_block.to_address())?)
+                .attribute(attribute("to", &moved_block.from_address())?)
+                .build();
+            if let Some(comment) = block.decor().prefix() {
+                rollback.decor_mut().set_prefix(comment.to_string());
+            }
+            Ok(rollback)
+        })
+        .collect::<Result<Vec<Block>>>()?;
+    Ok(format!(
+        "{}{}",
+        ROLLBACK_BANNER,
+        build_output_body(&rollback_blocks)
+    ))
+}
+
+/// One-line summary of what a run would generate, for `--dry-run`
+///
+/// E.g. `Would generate 12 moved blocks (9 resources, 3 modules) from 4 files`.
+/// Data sources are only listed when there are any.
+pub fn build_dry_run_summary<'a>(moved_blocks: impl Iterator<Item = &'a MovedBlock>) -> String {
+    let mut total = 0;
+    let mut resources = 0;
+    let mut modules = 0;
+    let mut data_sources = 0;
+    let mut files = BTreeSet::new();
+    for moved_block in moved_blocks {
+        match moved_block {
+            MovedBlock::Resource(_) => resources += 1,
+            MovedBlock::Module(_) => modules += 1,
+            MovedBlock::Data(_) => data_sources += 1,
+        }
+        files.insert(moved_block.file_path());
+        total += 1;
+    }
+    let plural = |count: usize, singular: &str, plural: &str| {
+        format!("{} {}", count, if count == 1 { singular } else { plural })
+    };
+    let mut counts = vec![
+        plural(resources, "resource", "resources"),
+        plural(modules, "module", "modules"),
+    ];
+    if data_sources > 0 {
+        counts.push(plural(data_sources, "data source", "data sources"));
+    }
+    format!(
+        "Would generate {} ({}) from {}",
+        plural(total, "moved block", "moved blocks"),
+        counts.join(", "),
+        plural(files.len(), "file", "files")
+    )
+}
+
+/// Render collected moved blocks in the requested output format
+pub fn render_output(
+    format: OutputFormat,
+    blocks: &[(MovedBlock, Block)],
+    address_style: AddressStyle,
+) -> Result<String> {
+    match format {
+        OutputFormat::Hcl => {
+            let hcl_blocks: Vec<Block> = blocks.iter().map(|(_, b)| b.clone()).collect();
+            Ok(build_output_body(&hcl_blocks).to_string())
+        }
+        OutputFormat::Tfjson => {
+            let moved_blocks: Vec<MovedBlock> = blocks.iter().map(|(m, _)| m.clone()).collect();
+            Ok(serde_json::to_string_pretty(&build_tfjson_output(
+                &moved_blocks,
+            ))?)
+        }
+        OutputFormat::Statemv => {
+            let moved_blocks: Vec<MovedBlock> = blocks.iter().map(|(m, _)| m.clone()).collect();
+            build_statemv_output(&moved_blocks, address_style)
+        }
+        OutputFormat::Json => {
+            let records: Vec<MovedBlockRecord> = blocks
+                .iter()
+                .map(|(m, _)| MovedBlockRecord::from(m))
+                .collect();
+            Ok(serde_json::to_string_pretty(&records)?)
+        }
+    }
+}
+
+/// End rendered output with exactly one newline, like `terraform fmt`
+///
+/// Empty output stays empty, so nothing is printed when there is nothing to move.
+pub fn with_trailing_newline(output: &str) -> String {
+    let output = output.trim_end_matches('\n');
+    if output.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", output)
+    }
+}
+
+/// Check that rendered HCL output parses back to the same text
+///
+/// Guards against address or label escaping bugs producing output that Terraform
+/// would reject.
+pub fn validate_hcl(hcl: &str) -> Result<()> {
+    let body = parse_body(hcl).context("Generated output is not valid HCL")?;
+    if body.to_string() != hcl {
+        anyhow::bail!("Generated output does not round-trip through the HCL parser");
+    }
+    Ok(())
+}
+
+/// Canonical, content-only form of rendered HCL output
+///
+/// Keeps only the `from`/`to` of each `moved` block: comments and other blocks are
+/// dropped, whitespace is normalized, duplicates are removed and blocks are sorted
+/// by `from`. Two outputs describing the same moves canonicalize to the same text.
+pub fn canonicalize(hcl: &str) -> Result<String> {
+    let body = parse_body(hcl).context("Failed to parse output for canonicalization")?;
+    Ok(render_moves(&ExistingMoves::from_body(&body), ""))
+}
+
+/// Normalize a hand-maintained file of `moved` blocks
+///
+/// Blocks and attributes other than `moved` are kept as written, ahead of the moves.
+/// The `moved` blocks are deduplicated, sorted by `from` and rewritten with consistent
+/// indentation, separated by a blank line. Comments on `moved` blocks are dropped.
+pub fn format_moved_file(hcl: &str) -> Result<String> {
+    let body = parse_body(hcl).context("Failed to parse moved file")?;
+    let moves = render_moves(&ExistingMoves::from_body(&body), "\n");
+    let others: Body = body
+        .iter()
+        .filter(|structure| {
+            structure
+                .as_block()
+                .is_none_or(|block| block.ident.value().as_str() != "moved")
+        })
+        .cloned()
+        .collect();
+    let others = others.to_string();
+    let others = others.trim();
+    Ok(match (others.is_empty(), moves.is_empty()) {
+        (true, _) => moves,
+        (false, true) => format!("{}\n", others),
+        (false, false) => format!("{}\n\n{}", others, moves),
+    })
+}
+
+/// Append generated HCL to the content of an existing file, e.g. a hand-maintained `moved.tf`
+///
+/// The existing content is kept as written and the generated blocks follow after a
+/// blank line. When nothing was generated the existing content is returned unchanged.
+pub fn merge_output(existing: &str, generated: &str) -> String {
+    let generated = generated.trim();
+    if generated.is_empty() {
+        return existing.to_string();
+    }
+    let existing = existing.trim_end();
+    if existing.is_empty() {
+        format!("{}\n", generated)
+    } else {
+        format!("{}\n\n{}\n", existing, generated)
+    }
+}
+
+/// Unified-diff-like listing of the moves a re-run would add to or drop from an existing file
+///
+/// Moves only in `generated` are prefixed with `+`, moves only in `existing` with `-`.
+/// Moves in both are left out; the result is empty when there is no difference.
+pub fn build_diff_output(
+    existing: &ExistingMoves,
+    generated: &ExistingMoves,
+    name: &str,
+) -> String {
+    let added = generated.difference(existing);
+    let removed = existing.difference(generated);
+    if added.is_empty() && removed.is_empty() {
+        return String::new();
+    }
+    let prefix_lines = |moves: &ExistingMoves, marker: char| -> String {
+        render_moves(moves, "")
+            .lines()
+            .map(|line| format!("{}{}\n", marker, line))
+            .collect()
+    };
+    format!(
+        "--- {}\n+++ generated\n{}{}",
+        name,
+        prefix_lines(&removed, '-'),
+        prefix_lines(&added, '+')
+    )
+}
+
+/// Render moves as `moved` blocks sorted by `from`, joined by `separator`
+fn render_moves(moves: &ExistingMoves, separator: &str) -> String {
+    moves
+        .sorted()
+        .into_iter()
+        .map(|(from, to)| {
+            format!(
+                "moved {{\n  from = {}\n  to{}= {}\n}}\n",
+                from, TO_KEY_SUFFIX, to
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Group collected blocks per source file, keyed by output file name
+///
+/// Blocks from `main.tf` or `main.tf.json` end up in `moved_main.tf` (or `moved_main.tf.json`,
+/// `moved_main.sh`, `moved_main.moves.json`), preserving their order. The Terraform
+/// extension of the source is replaced by the one of the output format.
+///
+/// Files in a subdirectory of one of `roots` get the directory in their name, joined
+/// with `_`, so `network/main.tf` ends up in `moved_network_main.tf` instead of sharing
+/// a file with `main.tf`. Two source files that still map to the same name, e.g.
+/// `network/main.tf` and `network_main.tf`, are an error instead of being merged.
+pub fn group_by_source_file(
+    blocks: Vec<(MovedBlock, Block)>,
+    format: OutputFormat,
+    extensions: &Extensions,
+    roots: &[PathBuf],
+) -> Result<BTreeMap<String, Vec<(MovedBlock, Block)>>> {
+    let mut grouped: BTreeMap<String, Vec<(MovedBlock, Block)>> = BTreeMap::new();
+    let mut sources: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for (moved_block, block) in blocks {
+        let file_path = moved_block.file_path();
+        let file_name = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file_path.display().to_string());
+        let stem = extensions.file_stem(&file_name).unwrap_or(&file_name);
+        let dirs: Vec<String> = file_path
+            .parent()
+            .and_then(|dir| roots.iter().find_map(|root| dir.strip_prefix(root).ok()))
+            .map(|relative| {
+                relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let stem = dirs
+            .into_iter()
+            .chain(std::iter::once(stem.to_string()))
+            .collect::<Vec<_>>()
+            .join("_");
+        let output_name = match format {
+            OutputFormat::Hcl => format!("moved_{}.tf", stem),
+            OutputFormat::Tfjson => format!("moved_{}.tf.json", stem),
+            OutputFormat::Statemv => format!("moved_{}.sh", stem),
+            OutputFormat::Json => format!("moved_{}.moves.json", stem),
+        };
+        let source = sources
+            .entry(output_name.clone())
+            .or_insert_with(|| file_path.to_path_buf());
+        if source != file_path {
+            anyhow::bail!(
+                "{} and {} would both be written to {}, rename one of them or use --output",
+                source.display(),
+                file_path.display(),
+                output_name
+            );
+        }
+        grouped
+            .entry(output_name)
+            .or_default()
+            .push((moved_block, block));
+    }
+    Ok(grouped)
+}
+
+/// Order blocks by `from` address
+///
+/// Makes the output independent of file discovery order, which varies across
+/// platforms.
+pub fn sort_by_address(blocks: &mut [(MovedBlock, Block)]) {
+    blocks.sort_by_cached_key(|(moved_block, _)| moved_block.from_address());
+}
+
+/// Order blocks by source file, then by `from` address within each file
+///
+/// Keeps each file's moves contiguous while making the order independent of
+/// file discovery and block order.
+pub fn sort_by_file_then_address(blocks: &mut [(MovedBlock, Block)]) {
+    blocks.sort_by_cached_key(|(moved_block, _)| {
+        (
+            moved_block.file_path().to_path_buf(),
+            moved_block.from_address(),
+        )
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::address::AddressIndex;
+    use crate::moved_module::MovedModule;
     use crate::moved_resource::MovedResource;
     use crate::to_moved_block::ToMovedBlock;
     use anyhow::Result;
@@ -71,6 +619,181 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_build_output_body_separates_blocks() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?.1,
+            moved_resource(["aws_s3_bucket", "data"], "main.tf")?.1,
+        ];
+        let output = build_output_body(&blocks).to_string();
+
+        assert!(output.starts_with("# From: main.tf\nmoved {"));
+        assert!(output.contains("}\n\n# From: main.tf\nmoved {"));
+        assert_eq!(output.matches("\n\n").count(), 1);
+        Ok(())
+    }
+
+    fn moved_resource(labels: [&str; 2], file: &str) -> Result<(MovedBlock, Block)> {
+        let resource = MovedResource::new(
+            labels.iter().map(|l| l.to_string()).collect(),
+            PathBuf::from(file),
+            "compute".to_string(),
+        )?;
+        let block = resource.to_block()?;
+        Ok((MovedBlock::Resource(resource), block))
+    }
+
+    #[test]
+    fn test_group_by_source_file() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?,
+            moved_resource(["aws_s3_bucket", "data"], "storage.tf")?,
+            moved_resource(["aws_instance", "api"], "main.tf")?,
+        ];
+        let grouped = group_by_source_file(blocks, OutputFormat::Hcl, &Extensions::default(), &[])?;
+
+        let names: Vec<&String> = grouped.keys().collect();
+        assert_eq!(names, vec!["moved_main.tf", "moved_storage.tf"]);
+        assert_eq!(grouped["moved_main.tf"].len(), 2);
+        assert_eq!(grouped["moved_storage.tf"].len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_source_file_tfjson_names() -> Result<()> {
+        let blocks = vec![moved_resource(["aws_instance", "web"], "main.tf")?];
+        let grouped =
+            group_by_source_file(blocks, OutputFormat::Tfjson, &Extensions::default(), &[])?;
+        assert!(grouped.contains_key("moved_main.tf.json"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_source_file_nested_same_name() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "infra/main.tf")?,
+            moved_resource(["aws_vpc", "main"], "infra/a/main.tf")?,
+            moved_resource(["aws_s3_bucket", "logs"], "infra/b/c/main.tf")?,
+        ];
+        let grouped = group_by_source_file(
+            blocks,
+            OutputFormat::Hcl,
+            &Extensions::default(),
+            &[PathBuf::from("infra")],
+        )?;
+        let names: Vec<&String> = grouped.keys().collect();
+        assert_eq!(
+            names,
+            vec!["moved_a_main.tf", "moved_b_c_main.tf", "moved_main.tf"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_source_file_name_clash() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_vpc", "main"], "infra/network/main.tf")?,
+            moved_resource(["aws_instance", "web"], "infra/network_main.tf")?,
+        ];
+        let error = group_by_source_file(
+            blocks,
+            OutputFormat::Hcl,
+            &Extensions::default(),
+            &[PathBuf::from("infra")],
+        )
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("would both be written to moved_network_main.tf")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_source_file_tfjson_source() -> Result<()> {
+        let extensions = Extensions::default();
+        let names = |format: OutputFormat| -> Result<Vec<String>> {
+            let blocks = vec![moved_resource(["aws_instance", "web"], "main.tf.json")?];
+            Ok(group_by_source_file(blocks, format, &extensions, &[])?
+                .into_keys()
+                .collect())
+        };
+        assert_eq!(names(OutputFormat::Hcl)?, vec!["moved_main.tf"]);
+        assert_eq!(names(OutputFormat::Tfjson)?, vec!["moved_main.tf.json"]);
+        assert_eq!(names(OutputFormat::Statemv)?, vec!["moved_main.sh"]);
+        assert_eq!(names(OutputFormat::Json)?, vec!["moved_main.moves.json"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_tfjson_shape() -> Result<()> {
+        let module = MovedModule::new(
+            vec!["web_server".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let module_block = module.to_block()?;
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?,
+            (MovedBlock::Module(module), module_block),
+        ];
+        let output = render_output(OutputFormat::Tfjson, &blocks, AddressStyle::Modern)?;
+        let value: Value = serde_json::from_str(&output)?;
+
+        assert_eq!(
+            value,
+            json!({
+                "moved": [
+                    { "from": "aws_instance.web", "to": "module.compute.aws_instance.web" },
+                    { "from": "module.web_server", "to": "module.compute.module.web_server" },
+                ]
+            })
+        );
+        // Only the `moved` key is emitted, so the document is a valid .tf.json body
+        assert_eq!(value.as_object().map(|o| o.len()), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_output_body_grouped_headers_and_order() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?.1,
+            moved_resource(["aws_s3_bucket", "data"], "main.tf")?.1,
+            moved_resource(["aws_instance", "api"], "main.tf")?.1,
+        ];
+        // Synthetic key: the resource type in the block's "from" address
+        let body = build_output_body_grouped(&blocks, |block| {
+            let from = block
+                .body
+                .get_attribute("from")
+                .map(|attr| attr.value.to_string())
+                .unwrap_or_default();
+            from.trim()
+                .split('.')
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        });
+        let output = body.to_string();
+
+        assert_eq!(body.blocks().count(), 3);
+        assert_eq!(output.matches("# aws_instance\n").count(), 1);
+        assert_eq!(output.matches("# aws_s3_bucket\n").count(), 1);
+
+        let instance_header = output.find("# aws_instance\n").unwrap();
+        let web = output.find("from = aws_instance.web").unwrap();
+        let api = output.find("from = aws_instance.api").unwrap();
+        let bucket_header = output.find("# aws_s3_bucket\n").unwrap();
+        let data = output.find("from = aws_s3_bucket.data").unwrap();
+        assert!(instance_header < web && web < api && api < bucket_header);
+        assert!(bucket_header < data);
+
+        // The header sits above the block's own source comment
+        assert!(output.contains("# aws_instance\n# From: main.tf\nmoved {"));
+        Ok(())
+    }
+
     #[test]
     fn test_body_to_string_conversion() -> Result<()> {
         let path = PathBuf::from("test.tf");
@@ -166,9 +889,478 @@ mod tests {
         // Verify structure
         assert_eq!(output.matches("moved {").count(), 2);
         assert!(output.contains("from = aws_instance.web"));
-        assert!(output.contains("to = module.compute.aws_instance.web"));
+        assert!(output.contains("to   = module.compute.aws_instance.web"));
         assert!(output.contains("from = aws_s3_bucket.data"));
-        assert!(output.contains("to = module.compute.aws_s3_bucket.data"));
+        assert!(output.contains("to   = module.compute.aws_s3_bucket.data"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_by_file_then_address() -> Result<()> {
+        let mut blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?,
+            moved_resource(["aws_s3_bucket", "logs"], "storage.tf")?,
+            moved_resource(["aws_instance", "api"], "main.tf")?,
+            moved_resource(["aws_s3_bucket", "data"], "storage.tf")?,
+        ];
+        sort_by_file_then_address(&mut blocks);
+        let order: Vec<String> = blocks
+            .iter()
+            .map(|(m, _)| format!("{} {}", m.file_path().display(), m.from_address()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                "main.tf aws_instance.api",
+                "main.tf aws_instance.web",
+                "storage.tf aws_s3_bucket.data",
+                "storage.tf aws_s3_bucket.logs",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_by_address_orders_output_by_from() -> Result<()> {
+        let mut blocks = vec![
+            moved_resource(["aws_s3_bucket", "logs"], "storage.tf")?,
+            moved_resource(["aws_instance", "web"], "main.tf")?,
+            moved_resource(["aws_s3_bucket", "data"], "main.tf")?,
+            moved_resource(["aws_instance", "api"], "storage.tf")?,
+        ];
+        sort_by_address(&mut blocks);
+        let output = render_output(OutputFormat::Hcl, &blocks, AddressStyle::Modern)?;
+        let froms: Vec<&str> = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("from = "))
+            .collect();
+        let mut sorted = froms.clone();
+        sorted.sort();
+        assert_eq!(froms, sorted);
+        assert_eq!(
+            froms,
+            vec![
+                "from = aws_instance.api",
+                "from = aws_instance.web",
+                "from = aws_s3_bucket.data",
+                "from = aws_s3_bucket.logs",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("aws_instance.web"), "'aws_instance.web'");
+        assert_eq!(
+            shell_quote(r#"aws_instance.web["it's"]"#),
+            r#"'aws_instance.web["it'\''s"]'"#
+        );
+    }
+
+    #[test]
+    fn test_render_statemv() -> Result<()> {
+        let module = MovedModule::new(
+            vec!["web_server".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let module_block = module.to_block()?;
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?,
+            (MovedBlock::Module(module), module_block),
+        ];
+        let output = render_output(OutputFormat::Statemv, &blocks, AddressStyle::Modern)?;
+        assert_eq!(
+            output,
+            "#!/bin/sh\nset -e\n\
+             terraform state mv 'aws_instance.web' 'module.compute.aws_instance.web'\n\
+             terraform state mv 'module.web_server' 'module.compute.module.web_server'\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_statemv_terraform11() -> Result<()> {
+        let blocks = vec![moved_resource(["aws_instance", "web"], "main.tf")?];
+        assert_eq!(
+            render_output(OutputFormat::Statemv, &blocks, AddressStyle::Terraform11)?,
+            render_output(OutputFormat::Statemv, &blocks, AddressStyle::Modern)?
+        );
+
+        let resource = MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?
+        .with_instance_key(AddressIndex::String("blue".to_string()));
+        let blocks = vec![(MovedBlock::Resource(resource.clone()), resource.to_block()?)];
+        let error =
+            render_output(OutputFormat::Statemv, &blocks, AddressStyle::Terraform11).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Terraform 0.11 does not support")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_output_body_by_dir() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_vpc", "main"], "infra/modules/network/main.tf")?,
+            moved_resource(["aws_instance", "web"], "infra/main.tf")?,
+            moved_resource(["aws_subnet", "a"], "infra/modules/network/subnets.tf")?,
+        ];
+        let output = build_output_body_by_dir(&blocks, &[PathBuf::from("infra")]).to_string();
+
+        let root = output
+            .find("# Directory: .\n# From: main.tf\nmoved")
+            .unwrap();
+        let network = output
+            .find("# Directory: modules/network\n# From: main.tf\nmoved")
+            .unwrap();
+        assert!(root < network);
+        assert_eq!(output.matches("# Directory:").count(), 2);
+        assert!(output.contains("# From: subnets.tf\nmoved"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_header_precedes_moves() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?,
+            moved_resource(["aws_s3_bucket", "data"], "main.tf")?,
+        ];
+        let output = format!(
+            "{}{}",
+            build_header(&blocks),
+            render_output(OutputFormat::Hcl, &blocks, AddressStyle::Modern)?
+        );
+        assert!(output.starts_with(
+            "# Generated by moved_maker for module \"compute\" (2 blocks)\n\n# From: main.tf\nmoved {"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_json() -> Result<()> {
+        let module = MovedModule::new(
+            vec!["web_server".to_string()],
+            PathBuf::from("modules.tf"),
+            "compute".to_string(),
+        )?;
+        let module_block = module.to_block()?;
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?,
+            (MovedBlock::Module(module), module_block),
+        ];
+        let output: Value = serde_json::from_str(&render_output(
+            OutputFormat::Json,
+            &blocks,
+            AddressStyle::Modern,
+        )?)?;
+        assert_eq!(
+            output,
+            json!([
+                {
+                    "kind": "resource",
+                    "from": "aws_instance.web",
+                    "to": "module.compute.aws_instance.web",
+                    "source_file": "main.tf"
+                },
+                {
+                    "kind": "module",
+                    "from": "module.web_server",
+                    "to": "module.compute.module.web_server",
+                    "source_file": "modules.tf"
+                }
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_module_stub() {
+        let stub = build_module_stub("compute", "./modules/compute");
+        let output = build_output_body(&[stub]).to_string();
+        assert_eq!(
+            output,
+            "module \"compute\" {\n  source = \"./modules/compute\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_build_module_stub_escapes_source() -> Result<()> {
+        let stub = build_module_stub("compute", r#"./modules/"odd"/${x}"#);
+        let output = build_output_body(&[stub]).to_string();
+        let body = hcl::edit::parser::parse_body(&output)?;
+        let block = body.blocks().next().expect("Expected a block");
+        let source = block.body.attributes().next().expect("Expected source");
+        assert_eq!(source.value.as_str(), Some(r#"./modules/"odd"/${x}"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_hcl_tricky_labels() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_instance", "1web"], "main.tf")?,
+            moved_resource(["aws_instance", "my web"], "main.tf")?,
+            moved_resource(["aws_s3_bucket", "logs-\"prod\""], "main.tf")?,
+        ];
+        let output = render_output(OutputFormat::Hcl, &blocks, AddressStyle::Modern)?;
+        validate_hcl(&format!("{}{}", build_header(&blocks), output))?;
+        assert!(output.contains(r#"from = aws_instance["1web"]"#));
+
+        let error = validate_hcl("moved {\n  from = aws_instance.\n}\n").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Generated output is not valid HCL")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonicalize_ignores_comments_order_and_spacing() -> Result<()> {
+        let first = r#"# From: main.tf
+moved {
+  from = aws_instance.web
+  to = module.compute.aws_instance.web
+}
+# From: storage.tf (resource)
+moved {
+  from = aws_s3_bucket.data
+  to = module.compute.aws_s3_bucket.data
+}
+"#;
+        let second = r#"
+// generated elsewhere
+moved {
+    from   = aws_s3_bucket.data # bucket
+    to     = module.compute.aws_s3_bucket.data
+}
+
+/* web */
+moved {
+  from = aws_instance.web
+  to   = module.compute.aws_instance.web
+}
+"#;
+        assert_eq!(canonicalize(first)?, canonicalize(second)?);
+        assert_eq!(
+            canonicalize(first)?,
+            "moved {\n  from = aws_instance.web\n  to   = module.compute.aws_instance.web\n}\n\
+             moved {\n  from = aws_s3_bucket.data\n  to   = module.compute.aws_s3_bucket.data\n}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonicalize_differs_for_different_moves() -> Result<()> {
+        let first = "moved {\n  from = aws_instance.web\n  to = module.a.aws_instance.web\n}\n";
+        let second = "moved {\n  from = aws_instance.web\n  to = module.b.aws_instance.web\n}\n";
+        assert_ne!(canonicalize(first)?, canonicalize(second)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_is_fmt_aligned_with_single_trailing_newline() -> Result<()> {
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?.1,
+            moved_resource(["aws_s3_bucket", "data"], "storage.tf")?.1,
+        ];
+        let output = with_trailing_newline(&build_output_body(&blocks).to_string());
+        assert!(output.ends_with("}\n"));
+        assert!(!output.ends_with("\n\n"));
+
+        let lines: Vec<&str> = output.lines().collect();
+        for (index, line) in lines.iter().enumerate() {
+            if line.trim_start().starts_with("from") {
+                let to = lines[index + 1];
+                assert!(to.trim_start().starts_with("to"));
+                assert_eq!(line.find('='), to.find('='), "{}\n{}", line, to);
+            }
+        }
+        assert_eq!(with_trailing_newline("moved {}\n\n\n"), "moved {}\n");
+        assert_eq!(with_trailing_newline(""), "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_output() {
+        let existing =
+            "# Hand-written\nmoved {\n  from = aws_instance.old\n  to   = aws_instance.new\n}\n";
+        let generated =
+            "moved {\n  from = aws_instance.web\n  to = module.compute.aws_instance.web\n}\n";
+        assert_eq!(
+            merge_output(existing, generated),
+            format!("{}\n{}", existing, generated)
+        );
+        assert_eq!(merge_output(existing, ""), existing);
+        assert_eq!(merge_output("", generated), generated);
+    }
+
+    #[test]
+    fn test_block_stream_matches_buffered_output() -> Result<()> {
+        let blocks = ["web", "api", "db"]
+            .into_iter()
+            .map(|name| {
+                MovedResource::new(
+                    vec!["aws_instance".to_string(), name.to_string()],
+                    PathBuf::from("main.tf"),
+                    "compute".to_string(),
+                )?
+                .to_block()
+            })
+            .collect::<Result<Vec<Block>>>()?;
+
+        let mut stream = BlockStream::new(Vec::new(), true);
+        for block in &blocks {
+            stream.write(block)?;
+        }
+        assert_eq!(stream.written(), 3);
+        let streamed = String::from_utf8(stream.finish()?)?;
+        assert_eq!(
+            streamed,
+            with_trailing_newline(&build_output_body(&blocks).to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_diff_output() {
+        let existing: ExistingMoves = [
+            (
+                "aws_instance.web".to_string(),
+                "module.compute.aws_instance.web".to_string(),
+            ),
+            (
+                "aws_instance.old".to_string(),
+                "module.compute.aws_instance.old".to_string(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let generated: ExistingMoves = [
+            (
+                "aws_instance.web".to_string(),
+                "module.compute.aws_instance.web".to_string(),
+            ),
+            (
+                "aws_instance.api".to_string(),
+                "module.compute.aws_instance.api".to_string(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            build_diff_output(&existing, &generated, "moved.tf"),
+            "--- moved.tf\n+++ generated\n\
+             -moved {\n-  from = aws_instance.old\n-  to   = module.compute.aws_instance.old\n-}\n\
+             +moved {\n+  from = aws_instance.api\n+  to   = module.compute.aws_instance.api\n+}\n"
+        );
+        assert_eq!(build_diff_output(&existing, &existing, "moved.tf"), "");
+    }
+
+    #[test]
+    fn test_format_moved_file() -> Result<()> {
+        let messy = r#"terraform {
+  required_version = ">= 1.1"
+}
+# old comment
+moved {
+      from = module.web_server
+  to=module.compute.module.web_server
+}
+moved {
+from = aws_instance.web
+        to = module.compute.aws_instance.web
+}
+
+
+moved {
+  from = aws_instance.web
+  to   = module.compute.aws_instance.web
+}
+"#;
+        assert_eq!(
+            format_moved_file(messy)?,
+            r#"terraform {
+  required_version = ">= 1.1"
+}
+
+moved {
+  from = aws_instance.web
+  to   = module.compute.aws_instance.web
+}
+
+moved {
+  from = module.web_server
+  to   = module.compute.module.web_server
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_rollback_output_swaps_addresses() -> Result<()> {
+        let module = MovedModule::new(
+            vec!["web_server".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let module_block = module.to_block()?;
+        let blocks = vec![
+            moved_resource(["aws_instance", "web"], "main.tf")?,
+            (MovedBlock::Module(module), module_block),
+        ];
+        let rollback = build_rollback_output(&blocks)?;
+        assert!(rollback.starts_with(ROLLBACK_BANNER));
+
+        let forward = ExistingMoves::from_body(&parse_body(&render_output(
+            OutputFormat::Hcl,
+            &blocks,
+            AddressStyle::Modern,
+        )?)?);
+        let reversed = ExistingMoves::from_body(&parse_body(&rollback)?);
+        let mut swapped: Vec<(String, String)> = forward
+            .sorted()
+            .into_iter()
+            .map(|(from, to)| (to, from))
+            .collect();
+        swapped.sort();
+        assert_eq!(reversed.sorted(), swapped);
+        assert_eq!(swapped.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_dry_run_summary() -> Result<()> {
+        let module = MovedModule::new(
+            vec!["web_server".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let moved_blocks = [
+            moved_resource(["aws_instance", "web"], "main.tf")?.0,
+            moved_resource(["aws_instance", "api"], "api.tf")?.0,
+            MovedBlock::Module(module),
+        ];
+        assert_eq!(
+            build_dry_run_summary(moved_blocks.iter()),
+            "Would generate 3 moved blocks (2 resources, 1 module) from 2 files"
+        );
+        assert_eq!(
+            build_dry_run_summary(moved_blocks[..1].iter()),
+            "Would generate 1 moved block (1 resource, 0 modules) from 1 file"
+        );
+        assert_eq!(
+            build_dry_run_summary(std::iter::empty()),
+            "Would generate 0 moved blocks (0 resources, 0 modules) from 0 files"
+        );
         Ok(())
     }
 }
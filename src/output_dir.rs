@@ -0,0 +1,216 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Output directory handling for multi-file output modes.
+//!
+//! `OutputDir` is the single place that creates the output directory and
+//! writes files into it, so every mode that produces several files gets the
+//! same create/conflict/overwrite behavior. `write_file` is shared with the
+//! single-file outputs, so `--output-mode` applies to every written file.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory that output files are written into
+pub struct OutputDir {
+    path: PathBuf,
+    force: bool,
+    mode: Option<u32>,
+}
+
+/// Write `contents` to `path`, then set its permission bits to `mode` if given
+///
+/// The mode only applies on Unix; elsewhere the platform default is kept.
+pub fn write_file(path: &Path, contents: &str, mode: Option<u32>) -> std::io::Result<()> {
+    fs::write(path, contents)?;
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    Ok(())
+}
+
+impl OutputDir {
+    /// Create the directory (and parents) if missing
+    ///
+    /// Fails if `path` exists but is not a directory.
+    /// `force` allows existing files in the directory to be overwritten.
+    pub fn create(path: &Path, force: bool) -> Result<Self> {
+        if path.exists() && !path.is_dir() {
+            anyhow::bail!(
+                "Output path exists and is not a directory: {}",
+                path.display()
+            );
+        }
+        fs::create_dir_all(path)
+            .with_context(|| format!("Failed to create output directory: {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            force,
+            mode: None,
+        })
+    }
+
+    /// Set the permission bits applied to written files
+    ///
+    /// `None` keeps the platform default.
+    pub fn with_mode(mut self, mode: Option<u32>) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Fail if any of `file_names` already exists in the directory, unless created with `force`
+    ///
+    /// Called with every name of a run before the first write, so a refused run leaves
+    /// the directory as it was instead of half written.
+    pub fn check_conflicts<'a>(&self, file_names: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        if self.force {
+            return Ok(());
+        }
+        let existing: Vec<String> = file_names
+            .into_iter()
+            .map(|file_name| self.path.join(file_name))
+            .filter(|target| target.exists())
+            .map(|target| target.display().to_string())
+            .collect();
+        if !existing.is_empty() {
+            anyhow::bail!(
+                "Refusing to overwrite existing file{}: {} (use --force to overwrite)",
+                if existing.len() == 1 { "" } else { "s" },
+                existing.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Write `contents` to `file_name` inside the directory
+    ///
+    /// Refuses to overwrite an existing file unless created with `force`.
+    pub fn write(&self, file_name: &str, contents: &str) -> Result<PathBuf> {
+        let target = self.path.join(file_name);
+        if target.exists() && !self.force {
+            anyhow::bail!(
+                "Refusing to overwrite existing file: {} (use --force to overwrite)",
+                target.display()
+            );
+        }
+        write_file(&target, contents, self.mode)
+            .with_context(|| format!("Failed to write file: {}", target.display()))?;
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_output_dir_creates_missing_directory_with_parents() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("nested").join("out");
+        let output_dir = OutputDir::create(&path, false)?;
+        assert!(path.is_dir());
+
+        let written = output_dir.write("moved.tf", "moved {}\n")?;
+        assert_eq!(fs::read_to_string(written)?, "moved {}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_dir_path_is_a_file_error() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("out");
+        fs::write(&path, "not a directory")?;
+
+        let result = OutputDir::create(&path, false);
+        assert!(result.is_err());
+        let error_msg = result.err().unwrap().to_string();
+        assert!(error_msg.contains("Output path exists and is not a directory"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_dir_existing_file_without_force_error() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("moved.tf"), "original")?;
+
+        let output_dir = OutputDir::create(temp_dir.path(), false)?;
+        let result = output_dir.write("moved.tf", "replacement");
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Refusing to overwrite existing file"));
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("moved.tf"))?,
+            "original"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_dir_check_conflicts_before_writing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("moved_b.tf"), "original")?;
+
+        let output_dir = OutputDir::create(temp_dir.path(), false)?;
+        let error = output_dir
+            .check_conflicts(["moved_a.tf", "moved_b.tf"])
+            .unwrap_err();
+        assert!(error.to_string().contains("moved_b.tf"));
+        assert!(!error.to_string().contains("moved_a.tf"));
+        assert!(output_dir.check_conflicts(["moved_a.tf"]).is_ok());
+
+        let forced = OutputDir::create(temp_dir.path(), true)?;
+        assert!(forced.check_conflicts(["moved_b.tf"]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_dir_existing_file_with_force_overwrites() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("moved.tf"), "original")?;
+
+        let output_dir = OutputDir::create(temp_dir.path(), true)?;
+        output_dir.write("moved.tf", "replacement")?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("moved.tf"))?,
+            "replacement"
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_dir_write_applies_mode() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new()?;
+        let output_dir = OutputDir::create(temp_dir.path(), false)?.with_mode(Some(0o664));
+        let target = output_dir.write("moved_main.tf", "content")?;
+        let mode = fs::metadata(&target)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o664);
+
+        // Single files written with --output, --merge-into or --with-rollback
+        let output = temp_dir.path().join("moved.tf");
+        write_file(&output, "content", Some(0o640))?;
+        let mode = fs::metadata(&output)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+        Ok(())
+    }
+}
@@ -12,18 +12,214 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::error::MovedMakerError;
 use anyhow::{Context, Result};
+use hcl::edit::expr::Expression;
 use hcl::edit::parser::parse_body;
-use hcl::edit::structure::Body;
+use hcl::edit::structure::{Attribute, Block, Body};
+use hcl::edit::{Decorate, Ident};
+use regex::Regex;
+use serde_json::Value;
 use std::fs;
 use std::path::Path;
 
+/// Comment marker that excludes a whole file from processing
+pub const IGNORE_FILE_MARKER: &str = "moved_maker:ignore-file";
+
 /// Parse a Terraform file and return the HCL Body structure
-pub fn parse_terraform_file(path: &Path) -> Result<Body> {
-    let content = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+pub fn parse_terraform_file(path: &Path) -> Result<Body, MovedMakerError> {
+    let content = read_terraform_file(path)?;
+    parse_terraform_source(&content, path)
+}
+
+/// Read the raw contents of a Terraform file
+pub fn read_terraform_file(path: &Path) -> Result<String, MovedMakerError> {
+    fs::read_to_string(path).map_err(|source| MovedMakerError::Read {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Whether the path is a Terraform JSON configuration file (`.tf.json`, or
+/// `.tofu.json` for OpenTofu)
+pub fn is_terraform_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+        && path
+            .file_stem()
+            .map(Path::new)
+            .and_then(Path::extension)
+            .is_some_and(|ext| ext == "tf" || ext == "tofu")
+}
+
+/// Parse contents read from `path` into the HCL Body structure
+///
+/// `.tf.json` files are read as Terraform JSON configuration, see `parse_terraform_json()`.
+/// `path` is otherwise only used in the error message.
+pub fn parse_terraform_source(content: &str, path: &Path) -> Result<Body, MovedMakerError> {
+    let parsed: Result<Body, Box<dyn std::error::Error + Send + Sync>> = if is_terraform_json(path)
+    {
+        parse_terraform_json(content).map_err(Into::into)
+    } else {
+        parse_body(content).map_err(Into::into)
+    };
+    parsed.map_err(|source| MovedMakerError::Parse {
+        path: path.to_path_buf(),
+        source,
+    })
+}
 
-    parse_body(&content).with_context(|| format!("Failed to parse HCL file: {}", path.display()))
+/// Recover the headers of `resource`, `data` and `module` blocks from HCL that does not parse
+///
+/// Fallback for files `parse_body()` rejects, e.g. with an unterminated heredoc or syntax
+/// newer than `hcl-edit` understands. Addresses only need the block type and labels, so
+/// each header becomes a block with an empty body; meta-arguments such as `count` are lost.
+/// Only headers at the start of a line are found, as `terraform fmt` writes them, which
+/// may include text inside a heredoc. Comments at the top of the file are kept above the
+/// first header, so `has_ignore_file_marker()` still sees them. `None` when no header is found.
+pub fn parse_block_headers(content: &str) -> Option<Body> {
+    let header_pattern = Regex::new(
+        r#"(?m)^(resource|data|module)((?:[ \t]+(?:"[^"\n]*"|[A-Za-z_][A-Za-z0-9_-]*))+)[ \t]*\{"#,
+    )
+    .expect("Block header regex is valid");
+    let label_pattern =
+        Regex::new(r#""([^"\n]*)"|([A-Za-z_][A-Za-z0-9_-]*)"#).expect("Label regex is valid");
+    let leading: String = content
+        .lines()
+        .take_while(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with('#') || line.starts_with("//")
+        })
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let mut body = Body::builder();
+    let mut found = false;
+    for captures in header_pattern.captures_iter(content) {
+        let mut builder = Block::builder(Ident::new(&captures[1]));
+        for label in label_pattern.captures_iter(&captures[2]) {
+            // Quoted labels without their quotes, bare identifiers as written
+            let text = label
+                .get(1)
+                .or_else(|| label.get(2))
+                .map_or("", |m| m.as_str());
+            builder = builder.label(text);
+        }
+        let mut block = builder.build();
+        if !found && !leading.is_empty() {
+            block.decor_mut().set_prefix(leading.clone());
+        }
+        body = body.block(block);
+        found = true;
+    }
+    found.then(|| body.build())
+}
+
+/// Meta-arguments copied from JSON blocks, the only body content later steps read
+const JSON_META_ARGUMENTS: [&str; 3] = ["count", "for_each", "provider"];
+
+/// Parse Terraform JSON configuration into the equivalent HCL Body
+///
+/// Only `resource`, `data` and `module` blocks are converted, with their labels and
+/// meta-arguments, so they produce the same moves as when written in HCL. Block
+/// types and labels may map to an object or to an array of objects. Blocks come out
+/// ordered by block type and labels, JSON objects being read without key order.
+pub fn parse_terraform_json(content: &str) -> Result<Body> {
+    let root: Value = serde_json::from_str(content)?;
+    let root = root
+        .as_object()
+        .context("Expected a JSON object at the top level")?;
+    let mut body = Body::builder();
+    for (ident, value) in root {
+        let label_count = match ident.as_str() {
+            "resource" | "data" => 2,
+            "module" => 1,
+            _ => continue, // Other block types and attributes do not produce moves
+        };
+        for (labels, block_body) in json_blocks(value, label_count)? {
+            body = body.block(json_block(ident, &labels, block_body)?);
+        }
+    }
+    Ok(body.build())
+}
+
+/// Walk `label_count` levels of nested objects, returning the labels and body of each block
+fn json_blocks(value: &Value, label_count: usize) -> Result<Vec<(Vec<String>, &Value)>> {
+    if label_count == 0 {
+        return Ok(match value {
+            Value::Array(bodies) => bodies.iter().map(|body| (Vec::new(), body)).collect(),
+            body => vec![(Vec::new(), body)],
+        });
+    }
+    let objects: Vec<&Value> = match value {
+        Value::Array(objects) => objects.iter().collect(),
+        object => vec![object],
+    };
+    let mut blocks = Vec::new();
+    for object in objects {
+        let object = object
+            .as_object()
+            .context("Expected a JSON object of block labels")?;
+        for (label, nested) in object {
+            for (mut labels, body) in json_blocks(nested, label_count - 1)? {
+                labels.insert(0, label.clone());
+                blocks.push((labels, body));
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+/// Build an HCL block with the labels and meta-arguments of a JSON block body
+fn json_block(ident: &str, labels: &[String], body: &Value) -> Result<Block> {
+    let mut builder = Block::builder(Ident::new(ident));
+    for label in labels {
+        builder = builder.label(label.as_str());
+    }
+    for name in JSON_META_ARGUMENTS {
+        let Some(value) = body.get(name) else {
+            continue;
+        };
+        // `provider` is a bare reference such as `aws.west`, other values are expressions
+        let expression = match value {
+            Value::String(reference) if name == "provider" => reference.clone(),
+            value => value.to_string(),
+        };
+        let mut attribute = Attribute::new(Ident::new(name), parse_expression(&expression)?);
+        attribute.decor_mut().set_prefix("  ");
+        builder = builder.attribute(attribute);
+    }
+    Ok(builder.build())
+}
+
+/// Parse a single HCL expression
+fn parse_expression(expression: &str) -> Result<Expression> {
+    let body = parse_body(&format!("x = {}", expression))
+        .with_context(|| format!("Invalid expression: {}", expression))?;
+    body.attributes()
+        .next()
+        .map(|attribute| attribute.value.clone())
+        .context("Expected an attribute")
+}
+
+/// Check whether the comments at the top of the file contain the ignore-file marker
+///
+/// Only comments before the first attribute or block are considered.
+pub fn has_ignore_file_marker(body: &Body) -> bool {
+    // Leading comments are attached to the first structure; in a file without
+    // structures they end up in the body suffix
+    let leading = match body.iter().next() {
+        Some(structure) => structure.decor().prefix(),
+        None => body.decor().suffix(),
+    };
+    leading.is_some_and(|text| {
+        text.lines().any(|line| {
+            let line = line.trim();
+            let comment = line
+                .strip_prefix('#')
+                .or_else(|| line.strip_prefix("//"))
+                .map(str::trim);
+            comment == Some(IGNORE_FILE_MARKER)
+        })
+    })
 }
 
 #[cfg(test)]
@@ -89,6 +285,76 @@ data "aws_ami" "example" {
         assert_eq!(blocks[0].ident.value().to_string(), "data");
     }
 
+    #[test]
+    fn test_is_terraform_json() {
+        assert!(is_terraform_json(Path::new("main.tf.json")));
+        assert!(is_terraform_json(Path::new("dir/generated.tf.json")));
+        assert!(is_terraform_json(Path::new("main.tofu.json")));
+        assert!(!is_terraform_json(Path::new("main.tf")));
+        assert!(!is_terraform_json(Path::new("data.json")));
+        assert!(!is_terraform_json(Path::new(".tf.json")));
+    }
+
+    #[test]
+    fn test_parse_tf_json_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("main.tf.json");
+        fs::write(
+            &file,
+            r#"{
+  "resource": {
+    "aws_instance": {
+      "web": { "ami": "ami-12345", "count": 2 },
+      "api": { "provider": "aws.west" }
+    }
+  },
+  "module": { "network": [{ "source": "./network" }] },
+  "variable": { "region": {} }
+}"#,
+        )?;
+
+        let body = parse_terraform_file(&file)?;
+        let blocks: Vec<(String, Vec<String>)> = body
+            .blocks()
+            .map(|block| {
+                (
+                    block.ident.value().to_string(),
+                    block
+                        .labels
+                        .iter()
+                        .map(|label| label.as_str().to_string())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                ("module".to_string(), vec!["network".to_string()]),
+                (
+                    "resource".to_string(),
+                    vec!["aws_instance".to_string(), "api".to_string()]
+                ),
+                (
+                    "resource".to_string(),
+                    vec!["aws_instance".to_string(), "web".to_string()]
+                ),
+            ]
+        );
+        let web = body.blocks().last().expect("Expected a block");
+        assert!(web.body.get_attribute("count").is_some());
+        assert!(web.body.get_attribute("ami").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_tf_json_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("main.tf.json");
+        fs::write(&file, r#"{ "resource": "#).unwrap();
+        assert!(parse_terraform_file(&file).is_err());
+    }
+
     #[test]
     fn test_handle_invalid_hcl_syntax() {
         let temp_dir = TempDir::new().unwrap();
@@ -103,6 +369,36 @@ data "aws_ami" "example" {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_block_headers_after_unterminated_heredoc() -> Result<()> {
+        let content = "resource \"aws_instance\" \"web\" {\n  user_data = <<-EOT\n    #!/bin/bash\n    echo hello\n}\n\nmodule network {\n  source = \"./network\"\n}\n";
+        assert!(parse_terraform_source(content, Path::new("main.tf")).is_err());
+
+        let body = parse_block_headers(content).expect("Expected block headers");
+        let headers: Vec<String> = body
+            .blocks()
+            .map(|block| {
+                let labels: Vec<&str> = block.labels.iter().map(|label| label.as_str()).collect();
+                format!("{} {}", block.ident.value(), labels.join(" "))
+            })
+            .collect();
+        assert_eq!(headers, vec!["resource aws_instance web", "module network"]);
+        assert!(parse_block_headers("locals {\n  a = <<EOT\n").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_block_headers_keeps_ignore_file_marker() {
+        let content =
+            "# moved_maker:ignore-file\n\nresource \"aws_instance\" \"web\" {\n  a = <<EOT\n}\n";
+        let body = parse_block_headers(content).expect("Expected block headers");
+        assert!(has_ignore_file_marker(&body));
+
+        let body = parse_block_headers("resource \"aws_instance\" \"web\" {\n  a = <<EOT\n}\n")
+            .expect("Expected block headers");
+        assert!(!has_ignore_file_marker(&body));
+    }
+
     #[test]
     fn test_handle_empty_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -126,4 +422,43 @@ data "aws_ami" "example" {
         let body = result.unwrap();
         assert_eq!(body.blocks().count(), 0);
     }
+
+    #[test]
+    fn test_ignore_file_marker_at_top_of_file() -> Result<()> {
+        let body =
+            parse_body("# moved_maker:ignore-file\n\nresource \"aws_instance\" \"web\" {}\n")?;
+        assert!(has_ignore_file_marker(&body));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_file_marker_with_slash_comment() -> Result<()> {
+        let body =
+            parse_body("// moved_maker:ignore-file\nresource \"aws_instance\" \"web\" {}\n")?;
+        assert!(has_ignore_file_marker(&body));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_file_marker_in_comment_only_file() -> Result<()> {
+        let body = parse_body("# moved_maker:ignore-file\n")?;
+        assert!(has_ignore_file_marker(&body));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_file_marker_absent() -> Result<()> {
+        let body = parse_body("# Regular comment\nresource \"aws_instance\" \"web\" {}\n")?;
+        assert!(!has_ignore_file_marker(&body));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_file_marker_below_first_block_is_ignored() -> Result<()> {
+        let body = parse_body(
+            "resource \"aws_instance\" \"web\" {}\n# moved_maker:ignore-file\nresource \"aws_instance\" \"api\" {}\n",
+        )?;
+        assert!(!has_ignore_file_marker(&body));
+        Ok(())
+    }
 }
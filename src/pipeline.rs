@@ -17,25 +17,180 @@
 //! This module provides iterator adapters and a builder that orchestrate
 //! the transformation from Terraform files to moved blocks.
 
-use crate::moved_block::MovedBlock;
-use crate::parser::parse_terraform_file;
-use crate::terraform_files::TerraformFiles;
+use crate::allowlist::Allowlist;
+use crate::diagnostics::{Diagnostics, ErrorFormat, WarningKind};
+use crate::error::MovedMakerError;
+use crate::existing_moves::ExistingMoves;
+use crate::moved_block::{MovePrefixes, MovedBlock, is_skipped_block_type};
+use crate::moved_resource::MovedResource;
+use crate::parser::{
+    IGNORE_FILE_MARKER, has_ignore_file_marker, is_terraform_json, parse_block_headers,
+    parse_terraform_source, read_terraform_file,
+};
+use crate::rename_map::RenameMap;
+use crate::structured::{UnmovedBlockRecord, UnmovedReason};
+use crate::terraform_files::{Extensions, IgnoreGlobs, TerraformFiles};
+use crate::to_moved_block::Direction;
+use crate::type_filter::{NameFilter, TypeFilter};
 use anyhow::Result;
 use hcl::edit::structure::{Block, Body};
-use std::path::PathBuf;
+use rayon::prelude::*;
+use regex::Regex;
+use std::path::{Path, PathBuf};
 
 /// Adapter that converts file results to parsed bodies
 /// Owns TerraformFiles
 pub struct ParsedFiles {
     files: Box<dyn Iterator<Item = Result<PathBuf>>>,
+    content_matches: Option<Regex>,
+    /// Recover block headers from files that do not parse
+    lenient_parse: bool,
+    jobs: usize,
+    /// Files loaded up front, on a thread pool when `jobs` > 1 or from content that
+    /// is not in a file, in discovery order
+    loaded: Option<std::vec::IntoIter<Result<LoadedFile>>>,
+    /// Files taken from discovery so far, whether or not they parsed
+    files_seen: usize,
+    diagnostics: Diagnostics,
+    unmoved: Vec<UnmovedBlockRecord>,
 }
 
 impl ParsedFiles {
     pub fn new(files: TerraformFiles) -> Self {
         Self {
             files: Box::new(files.into_iter()),
+            content_matches: None,
+            lenient_parse: false,
+            jobs: 1,
+            loaded: None,
+            files_seen: 0,
+            diagnostics: Diagnostics::new(),
+            unmoved: Vec::new(),
         }
     }
+
+    /// Parse already discovered files, discovery errors being reported as warnings
+    fn from_discovered(files: Vec<Result<PathBuf>>) -> Self {
+        Self {
+            files: Box::new(files.into_iter()),
+            content_matches: None,
+            lenient_parse: false,
+            jobs: 1,
+            loaded: None,
+            files_seen: 0,
+            diagnostics: Diagnostics::new(),
+            unmoved: Vec::new(),
+        }
+    }
+
+    /// Parse an externally supplied stream of file paths, skipping discovery
+    pub fn from_paths(paths: impl Iterator<Item = PathBuf> + 'static) -> Self {
+        Self {
+            files: Box::new(paths.map(Ok)),
+            content_matches: None,
+            lenient_parse: false,
+            jobs: 1,
+            loaded: None,
+            files_seen: 0,
+            diagnostics: Diagnostics::new(),
+            unmoved: Vec::new(),
+        }
+    }
+}
+
+/// Outcome of reading and parsing a file that could be read
+enum Loaded {
+    /// The content did not match `--content-matches`
+    Skipped,
+    /// The whole file parsed
+    Parsed(Body),
+    /// The file did not parse, only its block headers were recovered
+    Degraded(Body, MovedMakerError),
+}
+
+/// A file with the outcome of reading and parsing it
+type LoadedFile = (PathBuf, Result<Loaded, MovedMakerError>);
+
+/// Read and parse one file, without touching any iterator state
+fn load_file(file: PathBuf, content_matches: Option<&Regex>, lenient_parse: bool) -> LoadedFile {
+    log::info!("Parsing {}", file.display());
+    match read_terraform_file(&file) {
+        Ok(content) => load_content(file, &content, content_matches, lenient_parse),
+        Err(e) => (file, Err(e)),
+    }
+}
+
+/// Parse `content` read from `file`, unless it does not match `content_matches`
+///
+/// With `lenient_parse`, HCL that does not parse falls back to its block headers.
+fn load_content(
+    file: PathBuf,
+    content: &str,
+    content_matches: Option<&Regex>,
+    lenient_parse: bool,
+) -> LoadedFile {
+    if let Some(regex) = content_matches
+        && !regex.is_match(content)
+    {
+        return (file, Ok(Loaded::Skipped));
+    }
+    let parsed = match parse_terraform_source(content, &file) {
+        Ok(body) => Ok(Loaded::Parsed(body)),
+        Err(e) if lenient_parse && !is_terraform_json(&file) => {
+            match parse_block_headers(content) {
+                Some(body) => Ok(Loaded::Degraded(body, e)),
+                None => Err(e),
+            }
+        }
+        Err(e) => Err(e),
+    };
+    (file, parsed)
+}
+
+/// Load all files on a pool of `jobs` threads
+///
+/// The results keep the order of `files`, so the output does not depend on `jobs`.
+fn load_files_parallel(
+    files: Vec<Result<PathBuf>>,
+    content_matches: Option<&Regex>,
+    lenient_parse: bool,
+    jobs: usize,
+) -> Vec<Result<LoadedFile>> {
+    let load = || {
+        files
+            .into_par_iter()
+            .map(|file| file.map(|file| load_file(file, content_matches, lenient_parse)))
+            .collect::<Vec<_>>()
+    };
+    match rayon::ThreadPoolBuilder::new().num_threads(jobs).build() {
+        Ok(pool) => pool.install(load),
+        // Without a dedicated pool the files are still loaded on the global one
+        Err(_) => load(),
+    }
+}
+
+impl ParsedFiles {
+    /// Next discovered file, read and parsed
+    fn next_loaded(&mut self) -> Option<Result<LoadedFile>> {
+        if self.jobs <= 1 && self.loaded.is_none() {
+            let file = self.files.next()?;
+            return Some(
+                file.map(|file| load_file(file, self.content_matches.as_ref(), self.lenient_parse)),
+            );
+        }
+        self.loaded
+            .get_or_insert_with(|| {
+                let files: Vec<Result<PathBuf>> = self.files.by_ref().collect();
+                load_files_parallel(
+                    files,
+                    self.content_matches.as_ref(),
+                    self.lenient_parse,
+                    self.jobs,
+                )
+                .into_iter()
+            })
+            .next()
+    }
 }
 
 impl Iterator for ParsedFiles {
@@ -43,20 +198,63 @@ impl Iterator for ParsedFiles {
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let file_result = self.files.next()?;
-
-            let file = match file_result {
-                Ok(f) => f,
+            let (file, loaded) = match self.next_loaded()? {
+                Ok(loaded) => {
+                    self.files_seen += 1;
+                    loaded
+                }
                 Err(e) => {
-                    eprintln!("Warning: Failed to discover file: {}", e);
+                    self.diagnostics.warn(
+                        WarningKind::DiscoveryFailed,
+                        format_args!("Failed to discover file: {}", e),
+                    );
                     continue; // Skip this file and try next
                 }
             };
 
-            match parse_terraform_file(&file) {
-                Ok(body) => return Some(Ok((file, body))),
+            match loaded {
+                Ok(Loaded::Skipped) => continue, // Content does not match, skip silently
+                Ok(Loaded::Parsed(body) | Loaded::Degraded(body, _))
+                    if has_ignore_file_marker(&body) =>
+                {
+                    log::info!(
+                        "Skipping {} ({} marker)",
+                        file.display(),
+                        IGNORE_FILE_MARKER
+                    );
+                    self.unmoved
+                        .extend(body.blocks().filter(|block| is_movable_kind(block)).map(
+                            |block| {
+                                UnmovedBlockRecord::new(block, &file, UnmovedReason::IgnoredFile)
+                            },
+                        ));
+                    continue; // File opted out of processing
+                }
+                Ok(Loaded::Parsed(body)) => {
+                    log::info!(
+                        "Parsed {}: {} blocks",
+                        file.display(),
+                        body.blocks().count()
+                    );
+                    return Some(Ok((file, body)));
+                }
+                Ok(Loaded::Degraded(body, e)) => {
+                    self.diagnostics.warn_in(
+                        WarningKind::DegradedParse,
+                        &file,
+                        format_args!(
+                            "{}; read block headers only, so count, for_each and provider are not known",
+                            e
+                        ),
+                    );
+                    return Some(Ok((file, body)));
+                }
                 Err(e) => {
-                    eprintln!("Warning: Failed to parse {}: {}", file.display(), e);
+                    self.diagnostics.warn_in(
+                        WarningKind::ParseFailed,
+                        &file,
+                        format_args!("Failed to parse {}: {}", file.display(), e),
+                    );
                     continue; // Skip this file and try next
                 }
             }
@@ -64,11 +262,29 @@ impl Iterator for ParsedFiles {
     }
 }
 
+/// Whether the block is of a kind that produces moves (resource or module)
+fn is_movable_kind(block: &Block) -> bool {
+    matches!(block.ident.value().as_str(), "resource" | "module")
+}
+
 /// Adapter that converts blocks to MovedBlocks, managing body iteration internally
 /// Owns ParsedFiles
 pub struct MovedBlocks {
     parsed: ParsedFiles,
     module_name: String,
+    warn_name_collision: bool,
+    include_data: bool,
+    resource_name_regex: Option<Regex>,
+    type_filter: Option<TypeFilter>,
+    name_filter: Option<NameFilter>,
+    rename_map: Option<RenameMap>,
+    skip_existing: Option<ExistingMoves>,
+    allowlist: Option<Allowlist>,
+    moved_targets: ExistingMoves,
+    prefixes: Option<MovePrefixes>,
+    direction: Direction,
+    diagnostics: Diagnostics,
+    unmoved: Vec<UnmovedBlockRecord>,
     current_file: Option<PathBuf>,
     current_body: Option<Body>, // Keeps body alive for block references
     current_blocks: Vec<Block>, // Store blocks as owned values to avoid lifetime issues
@@ -80,6 +296,19 @@ impl MovedBlocks {
         Self {
             parsed,
             module_name,
+            warn_name_collision: false,
+            include_data: false,
+            resource_name_regex: None,
+            type_filter: None,
+            name_filter: None,
+            rename_map: None,
+            skip_existing: None,
+            allowlist: None,
+            moved_targets: ExistingMoves::default(),
+            prefixes: None,
+            direction: Direction::Into,
+            diagnostics: Diagnostics::new(),
+            unmoved: Vec::new(),
             current_file: None,
             current_body: None,
             current_blocks: Vec::new(),
@@ -87,6 +316,59 @@ impl MovedBlocks {
         }
     }
 
+    /// Warnings collected so far, including those from file parsing
+    pub fn diagnostics(&self) -> Diagnostics {
+        let mut diagnostics = self.parsed.diagnostics.clone();
+        diagnostics.merge(&self.diagnostics);
+        diagnostics
+    }
+
+    /// Number of Terraform files found so far, including those that failed to parse
+    ///
+    /// Counts every file once the iterator is exhausted.
+    pub fn files_seen(&self) -> usize {
+        self.parsed.files_seen
+    }
+
+    /// Resource/module blocks seen so far that did not produce a move, grouped by file
+    pub fn unmoved(&self) -> Vec<UnmovedBlockRecord> {
+        let mut unmoved: Vec<UnmovedBlockRecord> = self
+            .parsed
+            .unmoved
+            .iter()
+            .chain(self.unmoved.iter())
+            .cloned()
+            .collect();
+        unmoved.sort_by(|a, b| a.file.cmp(&b.file));
+        unmoved
+    }
+
+    /// Rename the resource type in the target address if the rename map has an entry
+    fn apply_rename(
+        rename_map: Option<&RenameMap>,
+        diagnostics: &mut Diagnostics,
+        resource: MovedResource,
+        file_path: &Path,
+    ) -> MovedResource {
+        let Some(new_type) = rename_map.and_then(|map| map.get(resource.resource_type())) else {
+            return resource;
+        };
+        diagnostics.warn_in(
+            WarningKind::TypeRename,
+            file_path,
+            format_args!(
+                "{}.{} in {} is renamed to type {}. Moving across resource types \
+                 destroys and recreates the resource unless the provider supports the move",
+                resource.resource_type(),
+                resource.resource_name(),
+                file_path.display(),
+                new_type
+            ),
+        );
+        let new_type = new_type.to_string();
+        resource.with_target_type(new_type)
+    }
+
     /// Load blocks from the next body into current_blocks vector
     /// Sets up iteration over all blocks (filtering happens in Iterator::next())
     fn load_next_body(&mut self) -> bool {
@@ -107,7 +389,7 @@ impl MovedBlocks {
                     return true;
                 }
                 Some(Err(e)) => {
-                    eprintln!("Warning: {}", e);
+                    self.diagnostics.warn(WarningKind::ParseFailed, e);
                     continue; // Try next file instead of recursing
                 }
                 None => {
@@ -133,11 +415,128 @@ impl Iterator for MovedBlocks {
                     .as_ref()
                     .expect("file_path should be set when blocks exist");
 
+                if !self.include_data && block.ident.value().as_str() == "data" {
+                    // Data blocks are only moved with --include-data
+                    self.diagnostics.record(WarningKind::UnsupportedBlock);
+                    continue;
+                }
+
                 match MovedBlock::from_block(block, file_path, &self.module_name) {
-                    None => continue, // Unsupported block type, skip silently
-                    Some(Ok(moved_block)) => return Some(Ok(moved_block)),
+                    None => {
+                        // Known non-movable types are skipped without a warning
+                        if !is_skipped_block_type(block.ident.value().as_str()) {
+                            self.diagnostics.record(WarningKind::UnsupportedBlock);
+                        }
+                        continue;
+                    }
+                    Some(Ok(moved_block)) => {
+                        if let Some(regex) = &self.resource_name_regex
+                            && let MovedBlock::Resource(resource) = &moved_block
+                            && !regex.is_match(resource.resource_name())
+                        {
+                            self.unmoved.push(UnmovedBlockRecord::new(
+                                block,
+                                file_path,
+                                UnmovedReason::Filtered,
+                            ));
+                            continue; // Filtered out by name, skip silently
+                        }
+                        if let Some(type_filter) = &self.type_filter
+                            && let MovedBlock::Resource(resource) = &moved_block
+                            && !type_filter.is_match(resource.resource_type())
+                        {
+                            self.unmoved.push(UnmovedBlockRecord::new(
+                                block,
+                                file_path,
+                                UnmovedReason::Filtered,
+                            ));
+                            continue; // Filtered out by type, skip silently
+                        }
+                        if let Some(name_filter) = &self.name_filter
+                            && let Some(name) = match &moved_block {
+                                MovedBlock::Resource(resource) => Some(resource.resource_name()),
+                                MovedBlock::Module(module) => Some(module.module_name_local()),
+                                MovedBlock::Data(_) => None,
+                            }
+                            && !name_filter.is_match(name)
+                        {
+                            self.unmoved.push(UnmovedBlockRecord::new(
+                                block,
+                                file_path,
+                                UnmovedReason::Filtered,
+                            ));
+                            continue; // Filtered out by name, skip silently
+                        }
+                        let moved_block = match moved_block {
+                            MovedBlock::Resource(resource) => {
+                                MovedBlock::Resource(Self::apply_rename(
+                                    self.rename_map.as_ref(),
+                                    &mut self.diagnostics,
+                                    resource,
+                                    file_path,
+                                ))
+                            }
+                            other => other,
+                        };
+                        let moved_block = match &self.prefixes {
+                            Some(prefixes) => moved_block.with_prefixes(prefixes),
+                            None => moved_block,
+                        };
+                        let moved_block = moved_block.with_direction(self.direction);
+                        if let Some(allowlist) = &self.allowlist
+                            && !allowlist.contains(&moved_block.from_address())
+                        {
+                            self.unmoved.push(UnmovedBlockRecord::new(
+                                block,
+                                file_path,
+                                UnmovedReason::Filtered,
+                            ));
+                            continue; // Not in the allowlist, skip silently
+                        }
+                        if let Some(existing) = &self.skip_existing
+                            && existing
+                                .contains(&moved_block.from_address(), &moved_block.to_address())
+                        {
+                            self.unmoved.push(UnmovedBlockRecord::new(
+                                block,
+                                file_path,
+                                UnmovedReason::AlreadyMoved,
+                            ));
+                            continue; // Already recorded, skip silently
+                        }
+                        if self
+                            .moved_targets
+                            .contains_target(&moved_block.to_address())
+                        {
+                            self.unmoved.push(UnmovedBlockRecord::new(
+                                block,
+                                file_path,
+                                UnmovedReason::AlreadyMoved,
+                            ));
+                            continue; // A moved block already targets it, skip silently
+                        }
+                        if self.warn_name_collision && moved_block.name_collides_with_target() {
+                            self.diagnostics.warn_in(
+                                WarningKind::NameCollision,
+                                file_path,
+                                format_args!(
+                                    "{} in {} has the same name as target module '{}'",
+                                    moved_block.from_address(),
+                                    file_path.display(),
+                                    moved_block.target_module_name()
+                                ),
+                            );
+                        }
+                        return Some(Ok(moved_block));
+                    }
                     Some(Err(e)) => {
-                        eprintln!("Warning: {}", e);
+                        self.diagnostics
+                            .warn_in(WarningKind::InvalidBlock, file_path, e);
+                        self.unmoved.push(UnmovedBlockRecord::new(
+                            block,
+                            file_path,
+                            UnmovedReason::InvalidLabels,
+                        ));
                         continue; // Invalid block, warn and skip
                     }
                 }
@@ -157,21 +556,280 @@ impl Iterator for MovedBlocks {
     }
 }
 
+/// Where the builder gets its files from
+enum FileSource {
+    /// Discover `.tf` files in one or more directories
+    Directories(Vec<PathBuf>),
+    /// A list of files and directories, directories being searched for `.tf` files
+    Listed(Vec<PathBuf>),
+    /// Use an externally supplied stream of file paths
+    Paths(Box<dyn Iterator<Item = PathBuf>>),
+    /// Configuration text that is not in a file, named for comments and warnings
+    Content { name: PathBuf, content: String },
+}
+
 /// Main builder that composes the pipeline
 pub struct MovedBlockBuilder {
-    src: PathBuf,
+    source: FileSource,
     module_name: String,
+    warn_name_collision: bool,
+    include_data: bool,
+    resource_name_regex: Option<Regex>,
+    type_filter: Option<TypeFilter>,
+    name_filter: Option<NameFilter>,
+    rename_map: Option<RenameMap>,
+    skip_existing: Option<ExistingMoves>,
+    existing_targets: Option<ExistingMoves>,
+    allowlist: Option<Allowlist>,
+    content_matches: Option<Regex>,
+    lenient_parse: bool,
+    prefixes: Option<MovePrefixes>,
+    direction: Direction,
+    error_format: ErrorFormat,
+    recursive: bool,
+    ignore: IgnoreGlobs,
+    extensions: Extensions,
+    jobs: usize,
 }
 
 impl MovedBlockBuilder {
     pub fn new(src: PathBuf, module_name: String) -> Self {
-        Self { src, module_name }
+        Self::from_dirs(vec![src], module_name)
+    }
+
+    /// Discover files in all given directories, processing each physical file once
+    pub fn from_dirs(srcs: Vec<PathBuf>, module_name: String) -> Self {
+        Self::with_source(FileSource::Directories(srcs), module_name)
+    }
+
+    /// Process the given file paths instead of discovering files in a directory
+    ///
+    /// Paths can come from any file-finding tool (`find`, `fd`, `git ls-files`, ...).
+    pub fn from_paths(paths: impl Iterator<Item = PathBuf> + 'static, module_name: String) -> Self {
+        Self::with_source(FileSource::Paths(Box::new(paths)), module_name)
+    }
+
+    /// Process a list of file and directory paths, e.g. read from stdin
+    ///
+    /// Directories are searched like in `from_dirs()`, files are used as given.
+    pub fn from_listed_paths(paths: Vec<PathBuf>, module_name: String) -> Self {
+        Self::with_source(FileSource::Listed(paths), module_name)
+    }
+
+    /// Process configuration text that is not in a file, e.g. read from stdin
+    ///
+    /// `name` stands in for the file path, e.g. `<stdin>` gives `# From: <stdin>`.
+    pub fn from_content(name: PathBuf, content: String, module_name: String) -> Self {
+        Self::with_source(FileSource::Content { name, content }, module_name)
+    }
+
+    fn with_source(source: FileSource, module_name: String) -> Self {
+        Self {
+            source,
+            module_name,
+            warn_name_collision: false,
+            include_data: false,
+            resource_name_regex: None,
+            type_filter: None,
+            name_filter: None,
+            rename_map: None,
+            skip_existing: None,
+            existing_targets: None,
+            allowlist: None,
+            content_matches: None,
+            lenient_parse: false,
+            prefixes: None,
+            direction: Direction::Into,
+            error_format: ErrorFormat::Human,
+            recursive: false,
+            ignore: IgnoreGlobs::default(),
+            extensions: Extensions::default(),
+            jobs: 1,
+        }
+    }
+
+    /// Warn when a resource/module name equals the target module name
+    pub fn warn_name_collision(mut self, enabled: bool) -> Self {
+        self.warn_name_collision = enabled;
+        self
+    }
+
+    /// Also move `data` blocks, which are skipped by default
+    pub fn include_data(mut self, enabled: bool) -> Self {
+        self.include_data = enabled;
+        self
+    }
+
+    /// Only move resources whose name (labels[1]) matches the regex
+    ///
+    /// Module blocks are not affected by this filter.
+    pub fn resource_name_regex(mut self, regex: Option<Regex>) -> Self {
+        self.resource_name_regex = regex;
+        self
+    }
+
+    /// Only move resources whose type (labels[0]) passes the include/exclude globs
+    ///
+    /// Module blocks are not affected by this filter.
+    pub fn type_filter(mut self, type_filter: Option<TypeFilter>) -> Self {
+        self.type_filter = type_filter;
+        self
+    }
+
+    /// Only move resources and modules whose name (the resource's labels[1] or the
+    /// module's label) passes the exclude globs
+    ///
+    /// Combines with the other filters, a block is moved only when it passes all of them.
+    /// Data blocks are not affected by this filter.
+    pub fn name_filter(mut self, name_filter: Option<NameFilter>) -> Self {
+        self.name_filter = name_filter;
+        self
+    }
+
+    /// Rename resource types in target addresses
+    pub fn rename_map(mut self, rename_map: Option<RenameMap>) -> Self {
+        self.rename_map = rename_map;
+        self
+    }
+
+    /// Skip moves that are already recorded in `existing`
+    pub fn skip_existing(mut self, existing: Option<ExistingMoves>) -> Self {
+        self.skip_existing = existing;
+        self
+    }
+
+    /// Skip blocks whose target address is the `to` of a move in `existing`
+    ///
+    /// `moved` blocks in the processed files are always taken into account, this
+    /// adds moves kept elsewhere, e.g. in a separate `moved.tf`.
+    pub fn existing_targets(mut self, existing: Option<ExistingMoves>) -> Self {
+        self.existing_targets = existing;
+        self
+    }
+
+    /// Only move blocks whose `from` address is in the allowlist
+    pub fn allowlist(mut self, allowlist: Option<Allowlist>) -> Self {
+        self.allowlist = allowlist;
+        self
+    }
+
+    /// Only process files whose raw content matches the regex
+    pub fn content_matches(mut self, regex: Option<Regex>) -> Self {
+        self.content_matches = regex;
+        self
+    }
+
+    /// Recover the block headers of HCL files that do not parse, instead of skipping them
+    ///
+    /// Each recovered file is reported as a `degraded-parse` warning.
+    pub fn lenient_parse(mut self, enabled: bool) -> Self {
+        self.lenient_parse = enabled;
+        self
+    }
+
+    /// Replace the default `""` -> `module.<name>` address prefixes
+    pub fn prefixes(mut self, prefixes: Option<MovePrefixes>) -> Self {
+        self.prefixes = prefixes;
+        self
+    }
+
+    /// Move blocks out of the target module instead of into it with `Direction::Outof`
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Also discover files in subdirectories of the source directories
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Skip discovered files matching `ignore`; files passed by path are always processed
+    pub fn ignore(mut self, ignore: IgnoreGlobs) -> Self {
+        self.ignore = ignore;
+        self
+    }
+
+    /// Discover files with these extensions, e.g. OpenTofu's `.tofu`, instead of `.tf` and `.tf.json`
+    pub fn extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Read and parse files on `jobs` threads; 1 (the default) parses them one by one
+    ///
+    /// The generated blocks and warnings come in the same order for any number of jobs.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Print warnings in `format`
+    pub fn error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_format = format;
+        self
     }
 
     pub fn moved_blocks(self) -> MovedBlocks {
-        let files = TerraformFiles::new(self.src);
-        let parsed = ParsedFiles::new(files);
-        MovedBlocks::new(parsed, self.module_name)
+        // Files are discovered up front, the moved blocks in all of them are read
+        // before the first block is converted
+        let mut loaded_content = None;
+        let files: Vec<Result<PathBuf>> = match self.source {
+            FileSource::Directories(srcs) => TerraformFiles::from_dirs(srcs)
+                .recursive(self.recursive)
+                .ignore(self.ignore)
+                .extensions(self.extensions)
+                .into_iter()
+                .collect(),
+            FileSource::Listed(paths) => TerraformFiles::from_listed_paths(paths)
+                .recursive(self.recursive)
+                .ignore(self.ignore)
+                .extensions(self.extensions)
+                .into_iter()
+                .collect(),
+            FileSource::Paths(paths) => paths.map(Ok).collect(),
+            FileSource::Content { name, content } => {
+                loaded_content = Some(load_content(
+                    name,
+                    &content,
+                    self.content_matches.as_ref(),
+                    self.lenient_parse,
+                ));
+                Vec::new()
+            }
+        };
+        for file in files.iter().flatten() {
+            log::info!("Found {}", file.display());
+        }
+        let mut moved_targets =
+            ExistingMoves::from_files(files.iter().filter_map(|file| file.as_deref().ok()));
+        if let Some((_, Ok(Loaded::Parsed(body) | Loaded::Degraded(body, _)))) = &loaded_content {
+            moved_targets.merge(&ExistingMoves::from_body(body));
+        }
+        if let Some(existing) = &self.existing_targets {
+            moved_targets.merge(existing);
+        }
+        let mut parsed = ParsedFiles::from_discovered(files);
+        parsed.loaded = loaded_content.map(|loaded| vec![Ok(loaded)].into_iter());
+        parsed.content_matches = self.content_matches;
+        parsed.lenient_parse = self.lenient_parse;
+        parsed.jobs = self.jobs;
+        parsed.diagnostics = Diagnostics::with_format(self.error_format);
+        let mut moved_blocks = MovedBlocks::new(parsed, self.module_name);
+        moved_blocks.warn_name_collision = self.warn_name_collision;
+        moved_blocks.include_data = self.include_data;
+        moved_blocks.resource_name_regex = self.resource_name_regex;
+        moved_blocks.type_filter = self.type_filter;
+        moved_blocks.name_filter = self.name_filter;
+        moved_blocks.rename_map = self.rename_map;
+        moved_blocks.skip_existing = self.skip_existing;
+        moved_blocks.allowlist = self.allowlist;
+        moved_blocks.moved_targets = moved_targets;
+        moved_blocks.prefixes = self.prefixes;
+        moved_blocks.direction = self.direction;
+        moved_blocks.diagnostics = Diagnostics::with_format(self.error_format);
+        moved_blocks
     }
 }
 
@@ -212,6 +870,50 @@ resource "aws_s3_bucket" "data" {}
         Ok(())
     }
 
+    #[test]
+    fn test_moved_block_builder_from_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let main = temp_dir.path().join("main.tf");
+        let storage = temp_dir.path().join("storage.tf");
+        let skipped = temp_dir.path().join("skipped.tf");
+        fs::write(&main, r#"resource "aws_instance" "web" {}"#)?;
+        fs::write(&storage, r#"resource "aws_s3_bucket" "data" {}"#)?;
+        fs::write(&skipped, r#"resource "aws_instance" "api" {}"#)?;
+
+        // Only the supplied paths are processed, in the supplied order
+        let paths = vec![storage, main];
+        let builder = MovedBlockBuilder::from_paths(paths.into_iter(), "compute".to_string());
+        let addresses: Vec<String> = builder
+            .moved_blocks()
+            .map(|m| m.map(|m| m.to_address()))
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            addresses,
+            vec![
+                "module.compute.aws_s3_bucket.data",
+                "module.compute.aws_instance.web"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_builder_from_paths_missing_file_warns() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let main = temp_dir.path().join("main.tf");
+        fs::write(&main, r#"resource "aws_instance" "web" {}"#)?;
+
+        let paths = vec![temp_dir.path().join("missing.tf"), main];
+        let mut moved_blocks =
+            MovedBlockBuilder::from_paths(paths.into_iter(), "compute".to_string()).moved_blocks();
+        assert_eq!(moved_blocks.by_ref().count(), 1);
+        assert_eq!(
+            moved_blocks.diagnostics().count(WarningKind::ParseFailed),
+            1
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_moved_blocks_empty_dir() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -251,6 +953,534 @@ resource "aws_s3_bucket" "data" {}
         Ok(())
     }
 
+    #[test]
+    fn test_moved_blocks_resource_name_regex() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("main.tf");
+        fs::write(
+            &file,
+            r#"
+resource "aws_instance" "web" {}
+resource "aws_instance" "web_backup" {}
+resource "aws_instance" "api" {}
+module "web_server" {}
+"#,
+        )?;
+
+        let builder = MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+            .resource_name_regex(Some(Regex::new("^web.*")?));
+        let addresses: Vec<String> = builder
+            .moved_blocks()
+            .map(|m| m.map(|m| m.from_address()))
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            addresses,
+            vec![
+                "aws_instance.web",
+                "aws_instance.web_backup",
+                "module.web_server"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_type_filter() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {}
+resource "aws_s3_bucket" "logs" {}
+resource "google_compute_instance" "vm" {}
+resource "google_storage_bucket" "assets" {}
+module "web_server" {}
+"#,
+        )?;
+
+        let addresses = |include: &[&str], exclude: &[&str]| -> Result<Vec<String>> {
+            let to_strings = |patterns: &[&str]| -> Vec<String> {
+                patterns.iter().map(|p| p.to_string()).collect()
+            };
+            let type_filter = TypeFilter::new(&to_strings(include), &to_strings(exclude))?;
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .type_filter(Some(type_filter))
+                .moved_blocks()
+                .map(|m| m.map(|m| m.from_address()))
+                .collect()
+        };
+        assert_eq!(
+            addresses(&["aws_instance"], &[])?,
+            vec!["aws_instance.web", "module.web_server"]
+        );
+        assert_eq!(
+            addresses(&["aws_*"], &["aws_s3_*"])?,
+            vec!["aws_instance.web", "module.web_server"]
+        );
+        assert_eq!(
+            addresses(&[], &["google_*"])?,
+            vec![
+                "aws_instance.web",
+                "aws_s3_bucket.logs",
+                "module.web_server"
+            ]
+        );
+        assert_eq!(
+            addresses(&["google_*", "aws_s3_bucket"], &["google_compute_*"])?,
+            vec![
+                "aws_s3_bucket.logs",
+                "google_storage_bucket.assets",
+                "module.web_server"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_name_filter() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {}
+resource "aws_instance" "web_legacy" {}
+resource "aws_db_instance" "db" {}
+resource "aws_s3_bucket" "db" {}
+module "network_legacy" {}
+"#,
+        )?;
+
+        let name_filter = NameFilter::new(&["*_legacy".to_string()])?;
+        let addresses: Vec<String> =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .name_filter(Some(name_filter.clone()))
+                .moved_blocks()
+                .map(|m| m.map(|m| m.from_address()))
+                .collect::<Result<_>>()?;
+        assert_eq!(
+            addresses,
+            vec!["aws_instance.web", "aws_db_instance.db", "aws_s3_bucket.db"]
+        );
+
+        // Combined with a type filter, both must pass
+        let type_filter = TypeFilter::new(&["aws_*_instance".to_string()], &[])?;
+        let addresses: Vec<String> =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .name_filter(Some(name_filter))
+                .type_filter(Some(type_filter))
+                .moved_blocks()
+                .map(|m| m.map(|m| m.from_address()))
+                .collect::<Result<_>>()?;
+        assert_eq!(addresses, vec!["aws_db_instance.db"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_allowlist() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {}
+resource "aws_instance" "api" {}
+resource "aws_s3_bucket" "logs" {}
+"#,
+        )?;
+
+        let mut moved_blocks =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .allowlist(Some(Allowlist::parse(
+                    "# Stage 1
+aws_instance.web
+",
+                )))
+                .moved_blocks();
+        let addresses: Vec<String> = moved_blocks
+            .by_ref()
+            .map(|m| m.map(|m| m.from_address()))
+            .collect::<Result<_>>()?;
+        assert_eq!(addresses, vec!["aws_instance.web"]);
+        assert_eq!(moved_blocks.unmoved().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_diagnostics() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {}
+resource "aws_instance" {}
+variable "region" {}
+output "id" {}
+moved {}
+provider "aws" {}
+"#,
+        )?;
+        fs::write(temp_dir.path().join("broken.tf"), "resource {")?;
+
+        let builder = MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string());
+        let mut moved_blocks = builder.moved_blocks();
+        assert_eq!(moved_blocks.by_ref().count(), 1);
+
+        let diagnostics = moved_blocks.diagnostics();
+        assert_eq!(diagnostics.count(WarningKind::ParseFailed), 1);
+        assert_eq!(diagnostics.count(WarningKind::InvalidBlock), 1);
+        assert_eq!(diagnostics.count(WarningKind::UnsupportedBlock), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_skip_targets_of_existing_moved_blocks() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {}
+resource "aws_instance" "api" {}
+"#,
+        )?;
+        fs::write(
+            temp_dir.path().join("moved.tf"),
+            r#"
+moved {
+  from = aws_instance.web
+  to   = module.compute.aws_instance.web
+}
+"#,
+        )?;
+
+        let mut moved_blocks =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .moved_blocks();
+        let addresses: Vec<String> = moved_blocks
+            .by_ref()
+            .map(|m| m.map(|m| m.from_address()))
+            .collect::<Result<_>>()?;
+        assert_eq!(addresses, vec!["aws_instance.api"]);
+        let unmoved = moved_blocks.unmoved();
+        assert_eq!(unmoved.len(), 1);
+        assert_eq!(unmoved[0].labels, vec!["aws_instance", "web"]);
+        assert_eq!(unmoved[0].reason, UnmovedReason::AlreadyMoved);
+
+        let existing: ExistingMoves = [(
+            "aws_instance.api".to_string(),
+            "module.compute.aws_instance.api".to_string(),
+        )]
+        .into_iter()
+        .collect();
+        let addresses: Vec<String> =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .existing_targets(Some(existing))
+                .moved_blocks()
+                .map(|m| m.map(|m| m.from_address()))
+                .collect::<Result<_>>()?;
+        assert!(addresses.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_include_data() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {}
+data "aws_ami" "ubuntu" {}
+"#,
+        )?;
+
+        let skipped: Vec<String> =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .moved_blocks()
+                .map(|m| m.map(|m| m.to_address()))
+                .collect::<Result<_>>()?;
+        assert_eq!(skipped, vec!["module.compute.aws_instance.web"]);
+
+        let included: Vec<String> =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .include_data(true)
+                .moved_blocks()
+                .map(|m| m.map(|m| m.to_address()))
+                .collect::<Result<_>>()?;
+        assert_eq!(
+            included,
+            vec![
+                "module.compute.aws_instance.web",
+                "module.compute.data.aws_ami.ubuntu"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_rename_map() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_alb" "web" {}
+resource "aws_instance" "api" {}
+"#,
+        )?;
+
+        let builder = MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+            .rename_map(Some(RenameMap::parse("aws_alb=aws_lb")?));
+        let mut moved_blocks = builder.moved_blocks();
+        let targets: Vec<String> = moved_blocks
+            .by_ref()
+            .map(|m| m.map(|m| m.to_address()))
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            targets,
+            vec![
+                "module.compute.aws_lb.web",
+                "module.compute.aws_instance.api"
+            ]
+        );
+        assert_eq!(moved_blocks.diagnostics().count(WarningKind::TypeRename), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_skip_existing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {}
+resource "aws_instance" "api" {}
+"#,
+        )?;
+        let snapshot = hcl::edit::parser::parse_body(
+            "moved {\n  from = aws_instance.web\n  to = module.compute.aws_instance.web\n}\n",
+        )?;
+
+        let builder = MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+            .skip_existing(Some(ExistingMoves::from_body(&snapshot)));
+        let addresses: Vec<String> = builder
+            .moved_blocks()
+            .map(|m| m.map(|m| m.from_address()))
+            .collect::<Result<_>>()?;
+        assert_eq!(addresses, vec!["aws_instance.api"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_builder_from_content() -> Result<()> {
+        let content = r#"
+resource "aws_instance" "web" {}
+resource "aws_s3_bucket" "logs" {}
+
+moved {
+  from = aws_s3_bucket.logs
+  to   = module.compute.aws_s3_bucket.logs
+}
+"#;
+        let addresses: Vec<(PathBuf, String)> = MovedBlockBuilder::from_content(
+            PathBuf::from("<stdin>"),
+            content.to_string(),
+            "compute".to_string(),
+        )
+        .moved_blocks()
+        .map(|m| m.map(|m| (m.file_path().to_path_buf(), m.from_address())))
+        .collect::<Result<_>>()?;
+        // The moved block in the content already covers the bucket
+        assert_eq!(
+            addresses,
+            vec![(PathBuf::from("<stdin>"), "aws_instance.web".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_builder_jobs_keep_order() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        for index in 0..40 {
+            fs::write(
+                temp_dir.path().join(format!("file_{:02}.tf", index)),
+                format!(
+                    "resource \"aws_instance\" \"web_{index}\" {{}}\nmodule \"mod_{index}\" {{}}\n"
+                ),
+            )?;
+        }
+        fs::write(temp_dir.path().join("file_20_invalid.tf"), "resource {")?;
+
+        let run = |jobs: usize| -> Result<(Vec<String>, usize)> {
+            let mut moved_blocks =
+                MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                    .jobs(jobs)
+                    .moved_blocks();
+            let addresses = moved_blocks
+                .by_ref()
+                .map(|m| m.map(|m| m.from_address()))
+                .collect::<Result<_>>()?;
+            let failed = moved_blocks.diagnostics().count(WarningKind::ParseFailed);
+            Ok((addresses, failed))
+        };
+        let (sequential, failed) = run(1)?;
+        assert_eq!(sequential.len(), 80);
+        assert_eq!(failed, 1);
+        for jobs in [2, 4, 16] {
+            assert_eq!(run(jobs)?, (sequential.clone(), 1), "jobs {}", jobs);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_builder_content_matches() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("a.tf"),
+            "# team: payments\nresource \"aws_instance\" \"web\" {}\n",
+        )?;
+        fs::write(
+            temp_dir.path().join("b.tf"),
+            r#"resource "aws_s3_bucket" "data" {}"#,
+        )?;
+        fs::write(
+            temp_dir.path().join("c.tf"),
+            "module \"payments\" {\n  source = \"./payments\"\n}\n# team: payments\n",
+        )?;
+
+        let builder = MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+            .content_matches(Some(Regex::new("team: payments")?));
+        let mut addresses: Vec<String> = builder
+            .moved_blocks()
+            .map(|m| m.map(|m| m.from_address()))
+            .collect::<Result<_>>()?;
+        addresses.sort();
+        assert_eq!(addresses, vec!["aws_instance.web", "module.payments"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_builder_lenient_parse() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            "resource \"aws_instance\" \"web\" {\n  user_data = <<-EOT\n    echo hello\n}\n",
+        )?;
+
+        let mut moved_blocks =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .moved_blocks();
+        assert_eq!(moved_blocks.by_ref().count(), 0);
+        assert_eq!(
+            moved_blocks.diagnostics().count(WarningKind::ParseFailed),
+            1
+        );
+
+        let mut moved_blocks =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .lenient_parse(true)
+                .moved_blocks();
+        let addresses: Vec<String> = moved_blocks
+            .by_ref()
+            .map(|m| m.map(|m| m.from_address()))
+            .collect::<Result<_>>()?;
+        assert_eq!(addresses, vec!["aws_instance.web"]);
+        let diagnostics = moved_blocks.diagnostics();
+        assert_eq!(diagnostics.count(WarningKind::ParseFailed), 0);
+        assert_eq!(diagnostics.count(WarningKind::DegradedParse), 1);
+
+        // The ignore-file marker still opts a degraded file out
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            "# moved_maker:ignore-file\nresource \"aws_instance\" \"web\" {\n  user_data = <<-EOT\n}\n",
+        )?;
+        let mut moved_blocks =
+            MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+                .lenient_parse(true)
+                .moved_blocks();
+        assert_eq!(moved_blocks.by_ref().count(), 0);
+        assert_eq!(moved_blocks.unmoved().len(), 1);
+        assert!(!moved_blocks.diagnostics().has_warnings());
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_builder_prefixes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("main.tf"),
+            r#"
+resource "aws_instance" "web" {}
+module "network" {}
+"#,
+        )?;
+
+        let prefixes = MovePrefixes {
+            from: "module.old".to_string(),
+            to: "module.new".to_string(),
+        };
+        let builder = MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "new".to_string())
+            .prefixes(Some(prefixes));
+        let addresses: Vec<(String, String)> = builder
+            .moved_blocks()
+            .map(|m| m.map(|m| (m.from_address(), m.to_address())))
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            addresses,
+            vec![
+                (
+                    "module.old.aws_instance.web".to_string(),
+                    "module.new.aws_instance.web".to_string()
+                ),
+                (
+                    "module.old.module.network".to_string(),
+                    "module.new.module.network".to_string()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_blocks_unmoved_with_reasons() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("a.tf"),
+            r#"
+resource "aws_instance" "web" {}
+resource "aws_instance" "api" {}
+resource "aws_instance" {}
+variable "region" {}
+"#,
+        )?;
+        fs::write(
+            temp_dir.path().join("b.tf"),
+            "# moved_maker:ignore-file\nmodule \"network\" {}\n",
+        )?;
+
+        let builder = MovedBlockBuilder::new(temp_dir.path().to_path_buf(), "compute".to_string())
+            .resource_name_regex(Some(Regex::new("^web$")?));
+        let mut moved_blocks = builder.moved_blocks();
+        assert_eq!(moved_blocks.by_ref().count(), 1);
+
+        let unmoved = moved_blocks.unmoved();
+        let summary: Vec<(Vec<String>, UnmovedReason)> = unmoved
+            .into_iter()
+            .map(|record| (record.labels, record.reason))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    vec!["aws_instance".to_string(), "api".to_string()],
+                    UnmovedReason::Filtered
+                ),
+                (
+                    vec!["aws_instance".to_string()],
+                    UnmovedReason::InvalidLabels
+                ),
+                (vec!["network".to_string()], UnmovedReason::IgnoredFile),
+            ]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_moved_blocks_mixed() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -270,4 +1500,29 @@ resource "aws_s3_bucket" "data" {}
         assert_eq!(count, 3);
         Ok(())
     }
+
+    #[test]
+    fn test_moved_blocks_module_name_template() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let network = temp_dir.path().join("network");
+        let storage = temp_dir.path().join("storage");
+        fs::create_dir(&network)?;
+        fs::create_dir(&storage)?;
+        fs::write(network.join("main.tf"), r#"resource "aws_vpc" "main" {}"#)?;
+        fs::write(storage.join("main.tf"), r#"module "bucket" {}"#)?;
+
+        let builder = MovedBlockBuilder::from_dirs(vec![network, storage], "mod_{dir}".to_string());
+        let targets: Vec<String> = builder
+            .moved_blocks()
+            .map(|m| m.map(|m| m.to_address()))
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            targets,
+            vec![
+                "module.mod_network.aws_vpc.main",
+                "module.mod_storage.module.bucket"
+            ]
+        );
+        Ok(())
+    }
 }
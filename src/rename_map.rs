@@ -0,0 +1,122 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resource type rename map.
+//!
+//! A rename map file contains `old_type=new_type` lines. Blank lines and
+//! lines starting with `#` are ignored.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Mapping from original resource type to the type used in the target address
+#[derive(Debug, Default, Clone)]
+pub struct RenameMap {
+    renames: HashMap<String, String>,
+}
+
+impl RenameMap {
+    /// Read and parse a rename map file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rename map: {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse rename map: {}", path.display()))
+    }
+
+    /// Parse `old_type=new_type` lines
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut renames = HashMap::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (old_type, new_type) = line
+                .split_once('=')
+                .map(|(old, new)| (old.trim(), new.trim()))
+                .filter(|(old, new)| !old.is_empty() && !new.is_empty())
+                .with_context(|| {
+                    format!(
+                        "Line {}: expected old_type=new_type, got: {}",
+                        index + 1,
+                        line
+                    )
+                })?;
+            if renames
+                .insert(old_type.to_string(), new_type.to_string())
+                .is_some()
+            {
+                anyhow::bail!("Line {}: duplicate entry for {}", index + 1, old_type);
+            }
+        }
+        Ok(Self { renames })
+    }
+
+    /// New type for `resource_type`, if it is renamed
+    pub fn get(&self, resource_type: &str) -> Option<&str> {
+        self.renames.get(resource_type).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rename_map_parse() -> Result<()> {
+        let map = RenameMap::parse(
+            "# Provider migration\n\naws_alb = aws_lb\naws_alb_listener=aws_lb_listener\n",
+        )?;
+        assert_eq!(map.get("aws_alb"), Some("aws_lb"));
+        assert_eq!(map.get("aws_alb_listener"), Some("aws_lb_listener"));
+        assert_eq!(map.get("aws_instance"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_map_parse_malformed_line() {
+        let result = RenameMap::parse("aws_alb=aws_lb\naws_alb_listener\n");
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Line 2"));
+    }
+
+    #[test]
+    fn test_rename_map_parse_empty_side() {
+        assert!(RenameMap::parse("aws_alb=\n").is_err());
+        assert!(RenameMap::parse("=aws_lb\n").is_err());
+    }
+
+    #[test]
+    fn test_rename_map_parse_duplicate() {
+        let result = RenameMap::parse("aws_alb=aws_lb\naws_alb=aws_nlb\n");
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("duplicate entry for aws_alb"));
+    }
+
+    #[test]
+    fn test_rename_map_from_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = RenameMap::from_file(&temp_dir.path().join("missing.map"));
+        assert!(result.is_err());
+        let error_msg = result.unwrap_err().to_string();
+        assert!(error_msg.contains("Failed to read rename map"));
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structured (JSON) representation of generated moves.
+//!
+//! These serde types describe one record per moved block and a summary of
+//! the run. The JSON Schema published with `--json-schema` is derived from
+//! the same types, so it cannot drift from the serialized output.
+
+use crate::diagnostics::{Diagnostics, WarningKind};
+use crate::moved_block::{MovedBlock, label_values};
+use hcl::edit::structure::Block;
+use schemars::{JsonSchema, schema_for};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Kind of block a move was generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Resource,
+    Module,
+    Data,
+}
+
+/// A single generated move
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MovedBlockRecord {
+    /// Kind of the moved block
+    pub kind: BlockKind,
+    /// Address before the move
+    pub from: String,
+    /// Address after the move
+    pub to: String,
+    /// Source file the block was read from
+    pub source_file: String,
+}
+
+impl From<&MovedBlock> for MovedBlockRecord {
+    fn from(moved_block: &MovedBlock) -> Self {
+        let kind = match moved_block {
+            MovedBlock::Resource(_) => BlockKind::Resource,
+            MovedBlock::Module(_) => BlockKind::Module,
+            MovedBlock::Data(_) => BlockKind::Data,
+        };
+        Self {
+            kind,
+            from: moved_block.from_address(),
+            to: moved_block.to_address(),
+            source_file: moved_block.file_path().display().to_string(),
+        }
+    }
+}
+
+/// Summary of a whole run
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RunSummaryRecord {
+    /// Number of moved blocks generated
+    pub blocks: usize,
+    /// Number of warnings per category
+    pub warnings: BTreeMap<WarningKind, usize>,
+}
+
+impl RunSummaryRecord {
+    pub fn new(blocks: usize, diagnostics: &Diagnostics) -> Self {
+        Self {
+            blocks,
+            warnings: diagnostics.counts().clone(),
+        }
+    }
+}
+
+/// Why a resource/module block did not produce a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnmovedReason {
+    /// The file has the ignore-file marker
+    IgnoredFile,
+    /// The block has invalid labels
+    InvalidLabels,
+    /// The block did not match a filter
+    Filtered,
+    /// The move is already recorded (`--only-changed`, `--ledger`) or a `moved` block targets it
+    AlreadyMoved,
+}
+
+/// A resource/module block that did not produce a move
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UnmovedBlockRecord {
+    /// Source file the block was read from
+    pub file: String,
+    /// Block type, e.g. "resource"
+    pub ident: String,
+    /// Block labels
+    pub labels: Vec<String>,
+    /// Why no move was generated
+    pub reason: UnmovedReason,
+}
+
+impl UnmovedBlockRecord {
+    pub fn new(block: &Block, file: &Path, reason: UnmovedReason) -> Self {
+        Self {
+            file: file.display().to_string(),
+            ident: block.ident.value().to_string(),
+            labels: label_values(block),
+            reason,
+        }
+    }
+}
+
+/// JSON Schema for the structured output, one definition per record type
+pub fn output_schema() -> Value {
+    json!({
+        "moved_block": schema_for!(MovedBlockRecord),
+        "run_summary": schema_for!(RunSummaryRecord),
+        "unmoved_block": schema_for!(UnmovedBlockRecord),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moved_module::MovedModule;
+    use crate::moved_resource::MovedResource;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_moved_block_record_from_resource() -> Result<()> {
+        let resource = MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let record = MovedBlockRecord::from(&MovedBlock::Resource(resource));
+        assert_eq!(
+            serde_json::to_value(&record)?,
+            json!({
+                "kind": "resource",
+                "from": "aws_instance.web",
+                "to": "module.compute.aws_instance.web",
+                "source_file": "main.tf",
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_block_record_from_module() -> Result<()> {
+        let module = MovedModule::new(
+            vec!["web_server".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let record = MovedBlockRecord::from(&MovedBlock::Module(module));
+        assert_eq!(record.kind, BlockKind::Module);
+        assert_eq!(record.to, "module.compute.module.web_server");
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_summary_record_serialization() -> Result<()> {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.record(WarningKind::ParseFailed);
+        let record = RunSummaryRecord::new(2, &diagnostics);
+        assert_eq!(
+            serde_json::to_value(&record)?,
+            json!({ "blocks": 2, "warnings": { "parse_failed": 1 } })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unmoved_block_record_serialization() -> Result<()> {
+        let body = hcl::edit::parser::parse_body(r#"resource "aws_instance" "web" {}"#)?;
+        let block = body.blocks().next().expect("Expected a block");
+        let record = UnmovedBlockRecord::new(block, Path::new("main.tf"), UnmovedReason::Filtered);
+        assert_eq!(
+            serde_json::to_value(&record)?,
+            json!({
+                "file": "main.tf",
+                "ident": "resource",
+                "labels": ["aws_instance", "web"],
+                "reason": "filtered",
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_schema_has_expected_properties() {
+        let schema = output_schema();
+        let block_properties = &schema["moved_block"]["properties"];
+        for name in ["kind", "from", "to", "source_file"] {
+            assert!(
+                block_properties.get(name).is_some(),
+                "Missing property {} in moved_block schema",
+                name
+            );
+        }
+        let summary_properties = &schema["run_summary"]["properties"];
+        for name in ["blocks", "warnings"] {
+            assert!(
+                summary_properties.get(name).is_some(),
+                "Missing property {} in run_summary schema",
+                name
+            );
+        }
+    }
+}
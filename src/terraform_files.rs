@@ -15,68 +15,427 @@
 //! Terraform file discovery.
 //!
 //! `TerraformFiles` encapsulates the logic for finding and iterating over
-//! Terraform files in a directory. File discovery is a private implementation
-//! detail - external code uses `TerraformFiles::new()` and `into_iter()`.
+//! Terraform files in one or more directories. File discovery is a private
+//! implementation detail - external code uses `TerraformFiles::new()` (or
+//! `from_dirs()`, `from_listed_paths()`) and `into_iter()`.
+//! `IgnoreGlobs` leaves discovered files out by their path below the searched directory.
 
+use crate::error::MovedMakerError;
+use crate::parser::is_terraform_json;
 use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::BufRead;
 use std::path::{Path, PathBuf};
 
+/// Whether the file name ends in `.tf` (HCL) or `.tf.json` (JSON configuration)
+pub fn is_terraform_file_name(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "tf") || is_terraform_json(path)
+}
+
+/// File name suffixes that count as Terraform files when searching directories
+///
+/// The default is `tf` and `tf.json`. OpenTofu files are found by adding `tofu`
+/// and `tofu.json`, e.g. `--extensions tf,tf.json,tofu,tofu.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extensions {
+    suffixes: Vec<String>,
+}
+
+impl Default for Extensions {
+    fn default() -> Self {
+        Self {
+            suffixes: vec![".tf".to_string(), ".tf.json".to_string()],
+        }
+    }
+}
+
+impl Extensions {
+    /// Parse the `--extensions` list; a leading `.` is optional
+    pub fn new(extensions: &[String]) -> Result<Self> {
+        let suffixes = extensions
+            .iter()
+            .map(|extension| {
+                let extension = extension.trim().trim_start_matches('.');
+                if extension.is_empty() {
+                    anyhow::bail!("Invalid --extensions: empty extension");
+                }
+                Ok(format!(".{}", extension))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if suffixes.is_empty() {
+            anyhow::bail!("Invalid --extensions: no extensions given");
+        }
+        Ok(Self { suffixes })
+    }
+
+    /// Whether the file name ends in one of the extensions, after a non-empty stem
+    pub fn is_match(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+            return false;
+        };
+        self.suffixes
+            .iter()
+            .any(|suffix| name.len() > suffix.len() && name.ends_with(suffix.as_str()))
+    }
+
+    /// File name without the longest matching extension, e.g. `main` for `main.tf.json`
+    pub fn file_stem<'a>(&self, file_name: &'a str) -> Option<&'a str> {
+        self.suffixes
+            .iter()
+            .filter(|suffix| file_name.len() > suffix.len())
+            .filter_map(|suffix| file_name.strip_suffix(suffix.as_str()))
+            .min_by_key(|stem| stem.len())
+    }
+}
+
+/// Read a newline-delimited list of paths, e.g. the output of `fd -e tf`
+///
+/// Surrounding whitespace is trimmed and blank lines are ignored.
+pub fn read_path_list(reader: impl BufRead) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for line in reader.lines() {
+        let line = line.context("Failed to read path list")?;
+        let line = line.trim();
+        if !line.is_empty() {
+            paths.push(PathBuf::from(line));
+        }
+    }
+    Ok(paths)
+}
+
+/// File name of the ignore file read from each searched directory
+pub const TERRAFORM_IGNORE_FILE: &str = ".terraformignore";
+
+/// Globs for discovered files to leave out, e.g. `**/examples/*.tf`
+///
+/// Patterns are matched against the path relative to the searched directory, joined
+/// with `/` on every platform. `*` does not match `/`, `**/` matches any number of
+/// directories.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreGlobs {
+    patterns: Vec<String>,
+    set: GlobSet,
+}
+
+impl IgnoreGlobs {
+    /// Compile the `--ignore` globs
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(
+                GlobBuilder::new(pattern)
+                    .literal_separator(true)
+                    .build()
+                    .with_context(|| format!("Invalid --ignore: {}", pattern))?,
+            );
+        }
+        Ok(Self {
+            patterns: patterns.to_vec(),
+            set: builder.build().context("Invalid --ignore")?,
+        })
+    }
+
+    /// Add the patterns of the `.terraformignore` file in `dir`, if there is one
+    ///
+    /// Blank lines and `#` comments are skipped and a trailing `/` ignores everything
+    /// below that directory. Negated (`!`) patterns are not supported and skipped with a note.
+    fn with_ignore_file(&self, dir: &Path) -> Result<Self> {
+        let ignore_file = dir.join(TERRAFORM_IGNORE_FILE);
+        if !ignore_file.is_file() {
+            return Ok(self.clone());
+        }
+        let content = fs::read_to_string(&ignore_file)
+            .with_context(|| format!("Failed to read {}", ignore_file.display()))?;
+        let mut patterns = self.patterns.clone();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('!') {
+                eprintln!(
+                    "Note: Skipping negated pattern '{}' in {}",
+                    line,
+                    ignore_file.display()
+                );
+                continue;
+            }
+            let pattern = line.trim_start_matches('/');
+            patterns.push(match pattern.strip_suffix('/') {
+                Some(dir) => format!("{}/**", dir),
+                None => pattern.to_string(),
+            });
+        }
+        Self::new(&patterns).with_context(|| format!("Invalid {}", ignore_file.display()))
+    }
+
+    /// Whether `file`, found below `dir`, is ignored
+    pub fn is_match(&self, dir: &Path, file: &Path) -> bool {
+        let Ok(relative) = file.strip_prefix(dir) else {
+            return false;
+        };
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.set.is_match(relative)
+    }
+}
+
 /// Encapsulates Terraform file discovery and iteration
 pub struct TerraformFiles {
-    src: PathBuf,
+    srcs: Vec<PathBuf>,
+    /// Whether `srcs` may also name files, which are yielded as is
+    allow_files: bool,
+    /// Whether subdirectories are searched too
+    recursive: bool,
+    /// Files below a searched directory to leave out
+    ignore: IgnoreGlobs,
+    /// File name suffixes of the files to find
+    extensions: Extensions,
 }
 
 impl TerraformFiles {
     /// Create a new TerraformFiles instance for the given directory
     pub fn new(src: PathBuf) -> Self {
-        Self { src }
+        Self::from_dirs(vec![src])
     }
 
-    /// Convert into an iterator over discovered Terraform files
-    pub fn into_iter(self) -> impl Iterator<Item = Result<PathBuf>> {
-        match Self::find_terraform_files(&self.src) {
-            Ok(files) => files.into_iter().map(Ok).collect::<Vec<_>>().into_iter(),
-            Err(e) => vec![Err(e)].into_iter(),
+    /// Create a TerraformFiles instance that discovers files in all given directories
+    ///
+    /// A path to a regular file, e.g. `main.tf`, is yielded as is instead of searched.
+    pub fn from_dirs(srcs: Vec<PathBuf>) -> Self {
+        Self {
+            srcs,
+            allow_files: false,
+            recursive: false,
+            ignore: IgnoreGlobs::default(),
+            extensions: Extensions::default(),
         }
     }
 
-    /// Find all `.tf` files in the source directory (non-recursive, only direct children)
+    /// Create a TerraformFiles instance for a list of file and directory paths
+    ///
+    /// Directories are searched like in `from_dirs()`. Any other path is yielded as is,
+    /// so a missing file is reported when it is read.
+    pub fn from_listed_paths(paths: Vec<PathBuf>) -> Self {
+        Self {
+            srcs: paths,
+            allow_files: true,
+            recursive: false,
+            ignore: IgnoreGlobs::default(),
+            extensions: Extensions::default(),
+        }
+    }
+
+    /// Also search subdirectories, skipping hidden ones such as `.terraform`
+    ///
+    /// Symlinked directories are followed, but each directory is searched once,
+    /// so a link back to a parent cannot form a loop.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Leave out files in searched directories that match `ignore`, or the
+    /// `.terraformignore` file of the directory
+    ///
+    /// Paths given as files are always yielded.
+    pub fn ignore(mut self, ignore: IgnoreGlobs) -> Self {
+        self.ignore = ignore;
+        self
+    }
+
+    /// Find files with these extensions instead of `.tf` and `.tf.json`
+    ///
+    /// Paths given as files are always yielded.
+    pub fn extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Find all regular `.tf` files in the source directory, and its subdirectories
+    /// when `recursive` is set (otherwise only direct children)
     ///
     /// This is a private method - file discovery is an implementation detail
     /// of the TerraformFiles struct.
-    fn find_terraform_files(src: &Path) -> Result<Vec<PathBuf>> {
+    ///
+    /// Entries that cannot be inspected, e.g. broken symlinks, are returned as errors
+    /// next to the found files, so they are reported as discovery warnings.
+    fn find_terraform_files(
+        src: &Path,
+        recursive: bool,
+        extensions: &Extensions,
+    ) -> Result<Vec<Result<PathBuf>>, MovedMakerError> {
+        Self::search_dir(src, recursive, extensions, &mut HashSet::new())
+    }
+
+    /// Search one directory, skipping it if its canonical path is in `visited`
+    fn search_dir(
+        src: &Path,
+        recursive: bool,
+        extensions: &Extensions,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Result<Vec<Result<PathBuf>>, MovedMakerError> {
         let mut files = Vec::new();
 
-        let entries = fs::read_dir(src)
-            .with_context(|| format!("Failed to read directory: {}", src.display()))?;
+        if !visited.insert(Self::file_key(src)) {
+            eprintln!(
+                "Note: Skipping {} (directory already searched)",
+                src.display()
+            );
+            return Ok(files);
+        }
+
+        let entries = fs::read_dir(src).map_err(|source| MovedMakerError::Discovery {
+            path: src.to_path_buf(),
+            source,
+        })?;
 
         for entry in entries {
             let entry = match entry {
                 Ok(entry) => entry,
                 Err(e) => {
-                    eprintln!("Warning: Failed to read directory entry: {}", e);
+                    files.push(Err(anyhow::anyhow!(
+                        "Failed to read directory entry in {}: {}",
+                        src.display(),
+                        e
+                    )));
                     continue;
                 }
             };
 
             let path = entry.path();
 
-            // Only process files, not directories
-            if !path.is_file() {
+            let file_type = match Self::resolved_file_type(&entry) {
+                Ok(file_type) => file_type,
+                // Only links that could have been Terraform files are worth a warning
+                Err(_) if !extensions.is_match(&path) => continue,
+                Err(e)
+                    if e.kind() == std::io::ErrorKind::NotFound
+                        && entry
+                            .file_type()
+                            .is_ok_and(|file_type| file_type.is_symlink()) =>
+                {
+                    files.push(Err(anyhow::anyhow!(
+                        "{} is a broken symlink",
+                        path.display()
+                    )));
+                    continue;
+                }
+                Err(e) => {
+                    files.push(Err(anyhow::anyhow!(
+                        "Failed to read file type: {}: {}",
+                        path.display(),
+                        e
+                    )));
+                    continue;
+                }
+            };
+
+            // Descend into non-hidden subdirectories, symlinked or not
+            if file_type.is_dir() {
+                if recursive && !entry.file_name().to_string_lossy().starts_with('.') {
+                    files.extend(Self::search_dir(&path, recursive, extensions, visited)?);
+                }
+                continue;
+            }
+
+            // Check if file has a .tf or .tf.json extension, or one given with --extensions
+            if !extensions.is_match(&path) {
                 continue;
             }
 
-            // Check if file has .tf extension
-            if let Some(ext) = path.extension()
-                && ext == "tf"
-            {
-                files.push(path);
+            // Only process regular files, not special files
+            if !file_type.is_file() {
+                eprintln!("Note: Skipping {} (not a regular file)", path.display());
+                continue;
             }
+
+            files.push(Ok(path));
         }
 
         Ok(files)
     }
+
+    /// Key identifying the physical file behind a path, for skipping repeats
+    ///
+    /// Paths are canonicalized. On case-insensitive platforms (macOS, Windows) the key
+    /// is also lowercased, since `Main.tf` and `main.tf` are the same file there.
+    fn file_key(file: &Path) -> PathBuf {
+        // Files that cannot be canonicalized are kept, reading them reports the error
+        let canonical = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        if cfg!(any(target_os = "macos", target_os = "windows")) {
+            PathBuf::from(canonical.to_string_lossy().to_lowercase())
+        } else {
+            canonical
+        }
+    }
+
+    /// File type of the entry, following symlinks to their target
+    fn resolved_file_type(entry: &fs::DirEntry) -> std::io::Result<fs::FileType> {
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            Ok(fs::metadata(entry.path())?.file_type())
+        } else {
+            Ok(file_type)
+        }
+    }
+}
+
+impl IntoIterator for TerraformFiles {
+    type Item = Result<PathBuf>;
+    type IntoIter = std::vec::IntoIter<Result<PathBuf>>;
+
+    /// Convert into an iterator over discovered Terraform files
+    ///
+    /// Each physical file is yielded once, even if several directories (or
+    /// symlinks) lead to it. Later occurrences are skipped with a note.
+    fn into_iter(self) -> Self::IntoIter {
+        let mut seen: HashMap<PathBuf, PathBuf> = HashMap::new();
+        let mut results = Vec::new();
+        for src in &self.srcs {
+            let files = if (self.allow_files && !src.is_dir()) || src.is_file() {
+                Ok(vec![Ok(src.clone())])
+            } else {
+                self.ignore.with_ignore_file(src).and_then(|ignore| {
+                    let mut files =
+                        Self::find_terraform_files(src, self.recursive, &self.extensions)?;
+                    files
+                        .retain(|file| !file.as_ref().is_ok_and(|file| ignore.is_match(src, file)));
+                    Ok(files)
+                })
+            };
+            match files {
+                Ok(files) => {
+                    for file in files {
+                        let file = match file {
+                            Ok(file) => file,
+                            Err(e) => {
+                                results.push(Err(e));
+                                continue;
+                            }
+                        };
+                        let key = Self::file_key(&file);
+                        if let Some(first) = seen.get(&key) {
+                            eprintln!(
+                                "Note: Skipping {} (same file as {})",
+                                file.display(),
+                                first.display()
+                            );
+                            continue;
+                        }
+                        seen.insert(key, file.clone());
+                        results.push(Ok(file));
+                    }
+                }
+                Err(e) => results.push(Err(e)),
+            }
+        }
+        results.into_iter()
+    }
 }
 
 #[cfg(test)]
@@ -90,7 +449,7 @@ mod tests {
     fn test_terraform_files_new() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
         let tf_files = TerraformFiles::new(temp_dir.path().to_path_buf());
-        assert_eq!(tf_files.src, temp_dir.path());
+        assert_eq!(tf_files.srcs, vec![temp_dir.path().to_path_buf()]);
         Ok(())
     }
 
@@ -111,6 +470,61 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_terraform_files_single_file() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("main.tf");
+        fs::write(&file, "resource \"aws_instance\" \"test\" {}").unwrap();
+        fs::write(temp_dir.path().join("variables.tf"), "variable \"test\" {}").unwrap();
+
+        let files = TerraformFiles::new(file.clone())
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(files, vec![file]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_globs_exclude_matching_files() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let examples = temp_dir.path().join("modules").join("examples");
+        fs::create_dir_all(&examples).unwrap();
+        let main = temp_dir.path().join("main.tf");
+        let module = temp_dir.path().join("modules").join("main.tf");
+        let example = examples.join("basic.tf");
+        for file in [&main, &module, &example] {
+            fs::write(file, "resource \"aws_instance\" \"test\" {}").unwrap();
+        }
+
+        let mut files: Vec<PathBuf> = TerraformFiles::new(temp_dir.path().to_path_buf())
+            .recursive(true)
+            .ignore(IgnoreGlobs::new(&["**/examples/*.tf".to_string()])?)
+            .into_iter()
+            .collect::<Result<_>>()?;
+        files.sort();
+        assert_eq!(files, vec![main.clone(), module.clone()]);
+
+        // The same exclusion read from .terraformignore
+        fs::write(
+            temp_dir.path().join(TERRAFORM_IGNORE_FILE),
+            "# vendored examples\nmodules/examples/\n",
+        )
+        .unwrap();
+        let mut files: Vec<PathBuf> = TerraformFiles::new(temp_dir.path().to_path_buf())
+            .recursive(true)
+            .into_iter()
+            .collect::<Result<_>>()?;
+        files.sort();
+        assert_eq!(files, vec![main, module]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_globs_invalid_pattern() {
+        let error = IgnoreGlobs::new(&["examples/[".to_string()]).unwrap_err();
+        assert!(error.to_string().contains("Invalid --ignore: examples/["));
+    }
+
     #[test]
     fn test_find_tf_files_in_directory() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
@@ -157,6 +571,75 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_tofu_files_found_only_with_extensions() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let tf_file = temp_dir.path().join("main.tf");
+        let tofu_file = temp_dir.path().join("network.tofu");
+        let tofu_json_file = temp_dir.path().join("generated.tofu.json");
+        fs::write(&tf_file, "").unwrap();
+        fs::write(&tofu_file, "").unwrap();
+        fs::write(&tofu_json_file, "{}").unwrap();
+        fs::write(temp_dir.path().join(".tofu"), "").unwrap();
+
+        let find = |extensions: Extensions| -> Result<Vec<PathBuf>> {
+            let mut files: Vec<PathBuf> = TerraformFiles::new(temp_dir.path().to_path_buf())
+                .extensions(extensions)
+                .into_iter()
+                .collect::<Result<_>>()?;
+            files.sort();
+            Ok(files)
+        };
+        assert_eq!(find(Extensions::default())?, vec![tf_file.clone()]);
+
+        let tofu = Extensions::new(&[
+            "tf".to_string(),
+            ".tofu".to_string(),
+            "tofu.json".to_string(),
+        ])?;
+        assert_eq!(find(tofu)?, vec![tofu_json_file, tf_file, tofu_file]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extensions_file_stem() -> Result<()> {
+        let extensions =
+            Extensions::new(&["tf".to_string(), "json".to_string(), "tf.json".to_string()])?;
+        assert_eq!(extensions.file_stem("main.tf"), Some("main"));
+        assert_eq!(extensions.file_stem("main.tf.json"), Some("main"));
+        assert_eq!(extensions.file_stem(".tf"), None);
+        assert_eq!(extensions.file_stem("main.tofu"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extensions_invalid() {
+        assert!(Extensions::new(&[]).is_err());
+        let error = Extensions::new(&["tf".to_string(), " ".to_string()]).unwrap_err();
+        assert!(error.to_string().contains("Invalid --extensions"));
+    }
+
+    #[test]
+    fn test_find_tf_json_files() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("main.tf"), "").unwrap();
+        fs::write(temp_dir.path().join("generated.tf.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join("data.json"), "{}").unwrap();
+
+        let mut files: Vec<PathBuf> = TerraformFiles::new(temp_dir.path().to_path_buf())
+            .into_iter()
+            .collect::<Result<_>>()?;
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                temp_dir.path().join("generated.tf.json"),
+                temp_dir.path().join("main.tf")
+            ]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_ignore_subdirectories() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
@@ -194,4 +677,200 @@ mod tests {
         assert_eq!(files.len(), 0);
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_skip_fifo_with_tf_extension() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let tf_file = temp_dir.path().join("main.tf");
+        let fifo = temp_dir.path().join("x.tf");
+        fs::write(&tf_file, "resource \"aws_instance\" \"test\" {}").unwrap();
+        let status = std::process::Command::new("mkfifo").arg(&fifo).status()?;
+        assert!(status.success(), "mkfifo failed");
+
+        let tf_files = TerraformFiles::new(temp_dir.path().to_path_buf());
+        let files: Vec<Result<PathBuf>> = tf_files.into_iter().collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].as_ref().unwrap(), &tf_file);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_tf_file_is_found() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let shared_dir = TempDir::new().unwrap();
+        let shared = shared_dir.path().join("providers.tf");
+        fs::write(&shared, "provider \"aws\" {}").unwrap();
+        let link = temp_dir.path().join("providers.tf");
+        std::os::unix::fs::symlink(&shared, &link).unwrap();
+        std::os::unix::fs::symlink(
+            temp_dir.path().join("missing.tf"),
+            temp_dir.path().join("broken.tf"),
+        )
+        .unwrap();
+
+        // The broken link is returned as an error, reported as a discovery warning
+        let (files, errors): (Vec<_>, Vec<_>) = TerraformFiles::new(temp_dir.path().to_path_buf())
+            .into_iter()
+            .partition(|file| file.is_ok());
+        let files: Vec<PathBuf> = files.into_iter().collect::<Result<_>>()?;
+        assert_eq!(files, vec![link]);
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .as_ref()
+                .unwrap_err()
+                .to_string()
+                .contains("broken.tf is a broken symlink")
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_recursive_follows_symlinked_directories_once() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let shared_dir = TempDir::new().unwrap();
+        let top = temp_dir.path().join("main.tf");
+        let shared = shared_dir.path().join("main.tf");
+        fs::write(&top, "resource \"aws_instance\" \"test\" {}").unwrap();
+        fs::write(&shared, "resource \"aws_vpc\" \"test\" {}").unwrap();
+        std::os::unix::fs::symlink(shared_dir.path(), temp_dir.path().join("shared")).unwrap();
+        // Links back to the searched directory would otherwise recurse forever
+        std::os::unix::fs::symlink(temp_dir.path(), temp_dir.path().join("loop")).unwrap();
+
+        let mut files: Vec<PathBuf> = TerraformFiles::new(temp_dir.path().to_path_buf())
+            .recursive(true)
+            .into_iter()
+            .collect::<Result<_>>()?;
+        files.sort();
+        assert_eq!(
+            files,
+            vec![top, temp_dir.path().join("shared").join("main.tf")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_dirs_skips_file_discovered_twice() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let other_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("main.tf");
+        let other = other_dir.path().join("other.tf");
+        fs::write(&file, "resource \"aws_instance\" \"test\" {}").unwrap();
+        fs::write(&other, "resource \"aws_s3_bucket\" \"test\" {}").unwrap();
+
+        // The same directory reached through two different paths
+        let dirs = vec![
+            temp_dir.path().to_path_buf(),
+            other_dir.path().to_path_buf(),
+            temp_dir.path().join(".").join("."),
+        ];
+        let files: Vec<PathBuf> = TerraformFiles::from_dirs(dirs)
+            .into_iter()
+            .collect::<Result<_>>()?;
+        assert_eq!(files, vec![file, other]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_dirs_skips_file_reached_through_symlink() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let real_dir = temp_dir.path().join("real");
+        let link_dir = temp_dir.path().join("link");
+        fs::create_dir(&real_dir).unwrap();
+        std::os::unix::fs::symlink(&real_dir, &link_dir).unwrap();
+        let file = real_dir.join("main.tf");
+        fs::write(&file, "resource \"aws_instance\" \"test\" {}").unwrap();
+
+        let files: Vec<PathBuf> = TerraformFiles::from_dirs(vec![real_dir, link_dir])
+            .into_iter()
+            .collect::<Result<_>>()?;
+        assert_eq!(files, vec![file]);
+        Ok(())
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    #[test]
+    fn test_from_dirs_skips_file_reached_with_different_case() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("Stack");
+        fs::create_dir(&dir).unwrap();
+        let file = dir.join("main.tf");
+        fs::write(&file, "resource \"aws_instance\" \"test\" {}").unwrap();
+
+        let files: Vec<PathBuf> =
+            TerraformFiles::from_dirs(vec![dir, temp_dir.path().join("STACK")])
+                .into_iter()
+                .collect::<Result<_>>()?;
+        assert_eq!(files, vec![file]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_path_list_ignores_blank_lines() -> Result<()> {
+        let input = "a/main.tf\n\n  b  \n\t\nc.tf";
+        assert_eq!(
+            read_path_list(input.as_bytes())?,
+            vec![
+                PathBuf::from("a/main.tf"),
+                PathBuf::from("b"),
+                PathBuf::from("c.tf")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_listed_paths_mixes_files_and_directories() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("stack");
+        fs::create_dir(&dir).unwrap();
+        let in_dir = dir.join("main.tf");
+        let file = temp_dir.path().join("other.tf");
+        let missing = temp_dir.path().join("missing.tf");
+        fs::write(&in_dir, "resource \"aws_instance\" \"test\" {}").unwrap();
+        fs::write(&file, "resource \"aws_s3_bucket\" \"test\" {}").unwrap();
+
+        let files: Vec<PathBuf> =
+            TerraformFiles::from_listed_paths(vec![file.clone(), dir, missing.clone()])
+                .into_iter()
+                .collect::<Result<_>>()?;
+        assert_eq!(files, vec![file, in_dir, missing]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recursive_finds_nested_files() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let nested_dir = temp_dir.path().join("modules").join("network");
+        let hidden_dir = temp_dir.path().join(".terraform");
+        fs::create_dir_all(&nested_dir).unwrap();
+        fs::create_dir(&hidden_dir).unwrap();
+        let top = temp_dir.path().join("main.tf");
+        let nested = nested_dir.join("main.tf");
+        fs::write(&top, "resource \"aws_instance\" \"test\" {}").unwrap();
+        fs::write(&nested, "resource \"aws_vpc\" \"test\" {}").unwrap();
+        fs::write(
+            hidden_dir.join("cached.tf"),
+            "resource \"aws_vpc\" \"x\" {}",
+        )
+        .unwrap();
+
+        let mut files: Vec<PathBuf> = TerraformFiles::new(temp_dir.path().to_path_buf())
+            .recursive(true)
+            .into_iter()
+            .collect::<Result<_>>()?;
+        files.sort();
+        assert_eq!(files, vec![top.clone(), nested]);
+
+        // Without the flag only direct children are found
+        let files: Vec<PathBuf> = TerraformFiles::new(temp_dir.path().to_path_buf())
+            .into_iter()
+            .collect::<Result<_>>()?;
+        assert_eq!(files, vec![top]);
+        Ok(())
+    }
 }
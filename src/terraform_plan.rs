@@ -0,0 +1,237 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Terraform plan JSON input.
+//!
+//! `TerraformPlan` reads the output of `terraform show -json plan.tfplan` and
+//! turns its `resource_changes` into moved blocks, as an alternative to
+//! scanning configuration files.
+
+use crate::address::{AddressSegment, parse_address};
+use crate::moved_block::MovedBlock;
+use crate::moved_module::MovedModule;
+use crate::moved_resource::MovedResource;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// The parts of a Terraform plan JSON document that moves are built from
+#[derive(Debug, Deserialize)]
+pub struct TerraformPlan {
+    #[serde(default)]
+    resource_changes: Vec<ResourceChange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceChange {
+    module_address: Option<String>,
+    mode: String,
+    #[serde(rename = "type")]
+    resource_type: String,
+    name: String,
+    change: Change,
+}
+
+#[derive(Debug, Deserialize)]
+struct Change {
+    actions: Vec<String>,
+}
+
+impl TerraformPlan {
+    /// Read a plan JSON file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read plan: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse plan JSON: {}", path.display()))
+    }
+
+    /// Build moved blocks for the root-level resources and modules in the plan
+    ///
+    /// - Managed resources in the root module become resource moves
+    /// - Resources inside a module become one move for the top-level module call
+    /// - Data sources are skipped, they have no state to move
+    /// - Changes planned for deletion (including replacement) are skipped
+    /// - Instances (`count`/`for_each`) collapse to one move per resource
+    ///
+    /// `plan_path` is used for the `# From:` comment.
+    pub fn moved_blocks(&self, plan_path: &Path, module_name: &str) -> Result<Vec<MovedBlock>> {
+        let mut seen = HashSet::new();
+        let mut moved_blocks = Vec::new();
+
+        for change in &self.resource_changes {
+            if change.mode != "managed" || change.change.actions.iter().any(|a| a == "delete") {
+                continue;
+            }
+            let moved_block = match &change.module_address {
+                Some(module_address) => {
+                    let module = top_level_module(module_address).with_context(|| {
+                        format!("Invalid module address in plan: {}", module_address)
+                    })?;
+                    if !seen.insert(format!("module.{}", module)) {
+                        continue;
+                    }
+                    MovedBlock::Module(MovedModule::new(
+                        vec![module],
+                        plan_path.to_path_buf(),
+                        module_name.to_string(),
+                    )?)
+                }
+                None => {
+                    if !seen.insert(format!("{}.{}", change.resource_type, change.name)) {
+                        continue;
+                    }
+                    MovedBlock::Resource(MovedResource::new(
+                        vec![change.resource_type.clone(), change.name.clone()],
+                        plan_path.to_path_buf(),
+                        module_name.to_string(),
+                    )?)
+                }
+            };
+            moved_blocks.push(moved_block);
+        }
+
+        Ok(moved_blocks)
+    }
+}
+
+/// Name of the outermost module call in an address like `module.network["a"].module.subnets`
+fn top_level_module(module_address: &str) -> Option<String> {
+    match parse_address(module_address).ok()?.as_slice() {
+        [
+            AddressSegment::Attribute(module),
+            AddressSegment::Attribute(name),
+            ..,
+        ] if module == "module" => Some(name.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn addresses(plan: serde_json::Value) -> Result<Vec<(String, String)>> {
+        let plan: TerraformPlan = serde_json::from_value(plan)?;
+        Ok(plan
+            .moved_blocks(Path::new("plan.json"), "compute")?
+            .iter()
+            .map(|m| (m.from_address(), m.to_address()))
+            .collect())
+    }
+
+    fn change(
+        address_module: Option<&str>,
+        mode: &str,
+        resource: [&str; 2],
+        actions: &[&str],
+    ) -> serde_json::Value {
+        json!({
+            "module_address": address_module,
+            "mode": mode,
+            "type": resource[0],
+            "name": resource[1],
+            "change": { "actions": actions },
+        })
+    }
+
+    #[test]
+    fn test_plan_root_resources() -> Result<()> {
+        let plan = json!({
+            "resource_changes": [
+                change(None, "managed", ["aws_instance", "web"], &["no-op"]),
+                change(None, "managed", ["aws_s3_bucket", "data"], &["create"]),
+            ]
+        });
+        assert_eq!(
+            addresses(plan)?,
+            vec![
+                (
+                    "aws_instance.web".to_string(),
+                    "module.compute.aws_instance.web".to_string()
+                ),
+                (
+                    "aws_s3_bucket.data".to_string(),
+                    "module.compute.aws_s3_bucket.data".to_string()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_skips_data_and_deleted() -> Result<()> {
+        let plan = json!({
+            "resource_changes": [
+                change(None, "data", ["aws_ami", "ubuntu"], &["read"]),
+                change(None, "managed", ["aws_instance", "old"], &["delete"]),
+                change(None, "managed", ["aws_instance", "replaced"], &["delete", "create"]),
+            ]
+        });
+        assert_eq!(addresses(plan)?, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_collapses_instances_and_module_resources() -> Result<()> {
+        let plan = json!({
+            "resource_changes": [
+                change(None, "managed", ["aws_instance", "web"], &["no-op"]),
+                change(None, "managed", ["aws_instance", "web"], &["no-op"]),
+                change(Some("module.network"), "managed", ["aws_vpc", "main"], &["no-op"]),
+                change(Some("module.network.module.subnets[0]"), "managed", ["aws_subnet", "a"], &["create"]),
+            ]
+        });
+        assert_eq!(
+            addresses(plan)?,
+            vec![
+                (
+                    "aws_instance.web".to_string(),
+                    "module.compute.aws_instance.web".to_string()
+                ),
+                (
+                    "module.network".to_string(),
+                    "module.compute.module.network".to_string()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_invalid_json() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("plan.json");
+        fs::write(&path, "not json").unwrap();
+        let error = TerraformPlan::from_file(&path).unwrap_err();
+        assert!(error.to_string().contains("Failed to parse plan JSON"));
+    }
+
+    #[test]
+    fn test_top_level_module() {
+        assert_eq!(
+            top_level_module("module.network").as_deref(),
+            Some("network")
+        );
+        assert_eq!(
+            top_level_module("module.network[\"a\"].module.x").as_deref(),
+            Some("network")
+        );
+        assert_eq!(top_level_module("network"), None);
+    }
+}
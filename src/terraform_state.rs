@@ -0,0 +1,183 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Terraform state JSON input.
+//!
+//! `TerraformState` reads a `terraform.tfstate` file (format version 4) and
+//! answers whether an address is in state, so generated `from` addresses can
+//! be checked before the moves are applied. It never writes the state.
+
+use crate::address::{AddressIndex, AddressSegment, parse_address};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Addresses of the resources, their instances and the module calls around them in a state file
+#[derive(Debug, Default)]
+pub struct TerraformState {
+    addresses: HashSet<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateFile {
+    #[serde(default)]
+    resources: Vec<StateResource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateResource {
+    module: Option<String>,
+    mode: String,
+    #[serde(rename = "type")]
+    resource_type: String,
+    name: String,
+    #[serde(default)]
+    instances: Vec<StateInstance>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateInstance {
+    index_key: Option<Value>,
+}
+
+impl TerraformState {
+    /// Read a state file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read state: {}", path.display()))?;
+        Self::from_json(&content)
+            .with_context(|| format!("Failed to parse state JSON: {}", path.display()))
+    }
+
+    /// Parse state JSON, e.g. the output of `terraform state pull`
+    pub fn from_json(content: &str) -> Result<Self> {
+        let state: StateFile = serde_json::from_str(content)?;
+        let mut addresses = HashSet::new();
+        for resource in &state.resources {
+            let mut segments = match &resource.module {
+                Some(module) => parse_address(module)
+                    .with_context(|| format!("Invalid module address in state: {}", module))?,
+                None => Vec::new(),
+            };
+            // Every module call around the resource is in state too, with and without its key
+            for end in 1..=segments.len() {
+                add_address(&mut addresses, &segments[..end]);
+            }
+            if resource.mode == "data" {
+                segments.push(AddressSegment::Attribute("data".to_string()));
+            }
+            segments.push(AddressSegment::Attribute(resource.resource_type.clone()));
+            segments.push(AddressSegment::Attribute(resource.name.clone()));
+            add_address(&mut addresses, &segments);
+            for key in resource
+                .instances
+                .iter()
+                .filter_map(|i| i.index_key.as_ref())
+            {
+                let index = match key {
+                    Value::Number(number) => number.as_u64().map(AddressIndex::Number),
+                    Value::String(key) => Some(AddressIndex::String(key.clone())),
+                    _ => None,
+                };
+                if let Some(index) = index {
+                    let mut instance = segments.clone();
+                    instance.push(AddressSegment::Index(index));
+                    add_address(&mut addresses, &instance);
+                }
+            }
+        }
+        Ok(Self { addresses })
+    }
+
+    /// Whether the address, e.g. `aws_instance.web` or `module.network`, is in state
+    ///
+    /// A resource or module call counts as present when any of its instances is.
+    pub fn contains(&self, address: &str) -> bool {
+        match parse_address(address) {
+            Ok(segments) => self.addresses.contains(&format_segments(&segments)),
+            Err(_) => self.addresses.contains(address),
+        }
+    }
+}
+
+/// Add the address and the same address without instance keys
+fn add_address(addresses: &mut HashSet<String>, segments: &[AddressSegment]) {
+    addresses.insert(format_segments(segments));
+    let without_keys: Vec<AddressSegment> = segments
+        .iter()
+        .filter(|segment| matches!(segment, AddressSegment::Attribute(_)))
+        .cloned()
+        .collect();
+    addresses.insert(format_segments(&without_keys));
+}
+
+/// Format segments the same way for state and generated addresses, e.g. `module.a["x"].b`
+fn format_segments(segments: &[AddressSegment]) -> String {
+    let mut address = String::new();
+    for segment in segments {
+        match segment {
+            AddressSegment::Attribute(name) => {
+                if !address.is_empty() {
+                    address.push('.');
+                }
+                address.push_str(name);
+            }
+            AddressSegment::Index(index) => address.push_str(&index.to_string()),
+        }
+    }
+    address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_state_contains_resources_instances_and_modules() -> Result<()> {
+        let state = TerraformState::from_json(
+            &json!({
+                "version": 4,
+                "resources": [
+                    { "mode": "managed", "type": "aws_instance", "name": "web",
+                      "instances": [{ "index_key": 0 }, { "index_key": 1 }] },
+                    { "mode": "data", "type": "aws_ami", "name": "ubuntu",
+                      "instances": [{}] },
+                    { "module": "module.network[\"a\"]", "mode": "managed",
+                      "type": "aws_vpc", "name": "main", "instances": [{}] }
+                ]
+            })
+            .to_string(),
+        )?;
+
+        assert!(state.contains("aws_instance.web"));
+        assert!(state.contains("aws_instance.web[1]"));
+        assert!(!state.contains("aws_instance.web[2]"));
+        assert!(state.contains("data.aws_ami.ubuntu"));
+        assert!(!state.contains("aws_ami.ubuntu"));
+        assert!(state.contains("module.network"));
+        assert!(state.contains(r#"module.network["a"]"#));
+        assert!(state.contains(r#"module.network["a"].aws_vpc.main"#));
+        assert!(!state.contains("aws_instance.api"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_invalid_json() {
+        assert!(TerraformState::from_json("{ not json").is_err());
+    }
+}
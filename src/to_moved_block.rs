@@ -17,55 +17,475 @@
 //! This trait provides a shared interface for different types of moved blocks
 //! (resources, modules, etc.) to convert themselves into HCL block structures.
 
+use crate::address::AddressBuilder;
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use hcl::edit::expr::Expression;
+use hcl::edit::parser::parse_body;
 use hcl::edit::structure::{Attribute, Block};
 use hcl::edit::{Decorate, Ident};
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// HCL comment syntax used for the comment above each generated block
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CommentStyle {
+    /// `# From: main.tf`
+    #[default]
+    Hash,
+    /// `// From: main.tf`
+    Slash,
+    /// `/* From: main.tf */`
+    Block,
+}
+
+impl CommentStyle {
+    /// Wrap `text` in this comment syntax, including the trailing newline
+    pub fn format(self, text: &str) -> String {
+        match self {
+            CommentStyle::Hash => format!("# {}\n", text),
+            CommentStyle::Slash => format!("// {}\n", text),
+            CommentStyle::Block => format!("/* {} */\n", text),
+        }
+    }
+}
+
+/// How the source file is shown in the comment above each generated block
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CommentPath {
+    /// File name alone, e.g. `# From: main.tf`
+    Name,
+    /// Path below the `relative_to` directory containing the file, e.g.
+    /// `# From: network/main.tf`, or the file name when none contains it
+    #[default]
+    Relative,
+    /// Canonical absolute path, e.g. `# From: /work/infra/network/main.tf`
+    Absolute,
+}
+
+/// Kind of block generated for each source block
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BlockType {
+    /// `moved` blocks into the target module
+    #[default]
+    Moved,
+    /// `removed` blocks with `destroy = false`, dropping the source from state (Terraform 1.7+)
+    Removed,
+}
+
+/// Which way blocks move relative to the target module
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the root (or `--prefix-from`) into the target module
+    #[default]
+    Into,
+    /// Out of the target module, e.g. from `module.compute.aws_instance.web` to `aws_instance.web`
+    Outof,
+}
+
+/// Indentation of the lines inside each generated block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// This many spaces per level
+    Spaces(usize),
+    /// One tab per level
+    Tab,
+}
+
+impl Default for Indent {
+    /// Two spaces, like `terraform fmt`
+    fn default() -> Self {
+        Indent::Spaces(2)
+    }
+}
+
+impl Indent {
+    /// Whitespace for `depth` levels of nesting
+    pub fn level(self, depth: usize) -> String {
+        match self {
+            Indent::Spaces(width) => " ".repeat(width * depth),
+            Indent::Tab => "\t".repeat(depth),
+        }
+    }
+}
+
+/// Space between the `to` key and its `=`, lining it up with the `=` after `from`
+/// like `terraform fmt` aligns consecutive attributes
+pub const TO_KEY_SUFFIX: &str = "   ";
+
+/// Options for the comment placed above each generated block, and its layout
+#[derive(Debug, Default, Clone)]
+pub struct CommentOptions {
+    /// Append the block kind, e.g. `# From: main.tf (resource)`
+    pub include_kind: bool,
+    /// Comment syntax
+    pub style: CommentStyle,
+    /// Add a `# TODO: review this move (...)` comment to risky moves
+    pub mark_risky: bool,
+    /// Show the source path relative to the first of these directories that contains it,
+    /// e.g. `# From: modules/network/main.tf`, instead of the file name alone
+    pub relative_to: Vec<PathBuf>,
+    /// How the source file is shown; `relative_to` is only used for `CommentPath::Relative`
+    pub path: CommentPath,
+    /// Indentation of the attributes inside the block
+    pub indent: Indent,
+    /// Text of the comment instead of `From: <file>`, with `{file}`, `{path}`, `{type}`
+    /// and `{name}` placeholders, e.g. `moved from {file}`
+    pub template: Option<String>,
+    /// Leave the block without any comment, not even the source comments
+    pub omit: bool,
+}
 
 /// Trait for converting moved block types to HCL Block
 ///
 /// This trait follows the Template Method pattern - the default `to_block()` implementation
 /// handles the common structure (attribute creation, indentation, block building, comment),
-/// while implementors provide the block-specific expression-building logic.
+/// while implementors provide the block-specific address-formatting logic.
 pub trait ToMovedBlock {
-    /// Build the "from" expression (block-specific logic)
+    /// Format the "from" address, e.g. `aws_instance.web` (block-specific logic)
     #[allow(clippy::wrong_self_convention)]
-    fn from_expression(&self) -> Expression;
+    fn from_address(&self) -> String;
 
-    /// Build the "to" expression (block-specific logic)
-    fn to_expression(&self) -> Expression;
+    /// Format the "to" address, e.g. `module.compute.aws_instance.web` (block-specific logic)
+    fn to_address(&self) -> String;
+
+    /// Build the "from" expression
+    ///
+    /// Fails if the address is not valid HCL, e.g. a resource type starting with a digit.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_expression(&self) -> Result<Expression> {
+        AddressBuilder::new().parse(&self.from_address())
+    }
+
+    /// Build the "to" expression
+    fn to_expression(&self) -> Result<Expression> {
+        AddressBuilder::new().parse(&self.to_address())
+    }
 
     /// Get the file path for the comment (block-specific)
     fn file_path(&self) -> &Path;
 
+    /// Kind of the source block for the comment, e.g. "resource" (block-specific)
+    fn kind(&self) -> &'static str;
+
+    /// Why the move needs a human review, e.g. "renames resource type" (block-specific)
+    ///
+    /// `None` for a plain move that keeps the type and name.
+    fn risk(&self) -> Option<&'static str>;
+
+    /// Comments written above the source block, kept below the `From:` comment (block-specific)
+    fn source_comment(&self) -> Option<&str>;
+
+    /// Provider reference set on the source block, e.g. `aws.west` (block-specific)
+    fn provider(&self) -> Option<&str>;
+
+    /// Type of the source block for `{type}`, e.g. `aws_instance`, or `module` (block-specific)
+    fn source_type(&self) -> &str;
+
+    /// Name of the source block for `{name}`, e.g. `web` (block-specific)
+    fn source_name(&self) -> &str;
+
+    /// Build the moved block with the default comment
+    fn to_block(&self) -> Result<Block> {
+        self.to_block_with(&CommentOptions::default())
+    }
+
     /// Default implementation that builds the moved block
     /// This handles the common logic: attribute creation, indentation, block building, and comment
-    fn to_block(&self) -> Result<Block> {
-        let from_expr = self.from_expression();
-        let to_expr = self.to_expression();
+    fn to_block_with(&self, options: &CommentOptions) -> Result<Block> {
+        let from_expr = self.from_expression()?;
+        let to_expr = self.to_expression()?;
 
         // Create attributes with indentation
+        let indent = options.indent.level(1);
         let mut from_attr = Attribute::new(Ident::new("from"), from_expr);
-        from_attr.decor_mut().set_prefix("  ");
+        from_attr.decor_mut().set_prefix(indent.as_str());
 
         let mut to_attr = Attribute::new(Ident::new("to"), to_expr);
-        to_attr.decor_mut().set_prefix("  ");
+        to_attr.decor_mut().set_prefix(indent.as_str());
+        to_attr.key.decor_mut().set_suffix(TO_KEY_SUFFIX);
 
         let mut block = Block::builder(Ident::new("moved"))
             .attribute(from_attr)
             .attribute(to_attr)
             .build();
 
-        // Add comment with filename
-        let filename = self
+        block
+            .decor_mut()
+            .set_prefix(build_comment(self, options)?.as_str());
+
+        Ok(block)
+    }
+
+    /// Build a `removed` block that drops the source address from state without destroying it
+    ///
+    /// Terraform 1.7+ syntax:
+    /// `removed { from = aws_instance.web  lifecycle { destroy = false } }`
+    fn to_removed_block_with(&self, options: &CommentOptions) -> Result<Block> {
+        let from = self.from_address();
+        // Parsed like addresses, which keeps the nested block's indentation intact
+        let (one, two) = (options.indent.level(1), options.indent.level(2));
+        let hcl = format!(
+            "removed {{\n{one}from = {from}\n\n{one}lifecycle {{\n{two}destroy = false\n{one}}}\n}}\n"
+        );
+        let mut block = parse_body(&hcl)
+            .ok()
+            .and_then(|body| body.into_blocks().next())
+            .with_context(|| format!("Invalid address: {}", from))?;
+        block
+            .decor_mut()
+            .set_prefix(build_comment(self, options)?.as_str());
+
+        Ok(block)
+    }
+}
+
+/// Placeholder in `--comment-template` for the file name, relative when recursive
+const FILE_PLACEHOLDER: &str = "{file}";
+/// Placeholder in `--comment-template` for the full source path
+const PATH_PLACEHOLDER: &str = "{path}";
+/// Placeholder in `--comment-template` for the resource or data type, or `module`
+const TYPE_PLACEHOLDER: &str = "{type}";
+/// Placeholder in `--comment-template` for the block name
+const NAME_PLACEHOLDER: &str = "{name}";
+
+/// Comment above a generated block: `From:` line, source comments and risk note
+fn build_comment<T: ToMovedBlock + ?Sized>(moved: &T, options: &CommentOptions) -> Result<String> {
+    if options.omit {
+        return Ok(String::new());
+    }
+    let relative = match options.path {
+        CommentPath::Relative => options
+            .relative_to
+            .iter()
+            .find_map(|root| moved.file_path().strip_prefix(root).ok()),
+        CommentPath::Name | CommentPath::Absolute => None,
+    };
+    let filename = match relative {
+        // Joined with `/` on every platform, like Terraform module paths
+        Some(relative) => relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        // Names that are not files, like `<stdin>`, are shown as they are
+        None if options.path == CommentPath::Absolute => fs::canonicalize(moved.file_path())
+            .unwrap_or_else(|_| moved.file_path().to_path_buf())
+            .display()
+            .to_string(),
+        None => moved
             .file_path()
             .file_name()
-            .with_context(|| format!("Path must have filename: {}", self.file_path().display()))?
-            .to_string_lossy();
-        let comment = format!("# From: {}\n", filename);
-        block.decor_mut().set_prefix(comment.as_str());
+            .with_context(|| format!("Path must have filename: {}", moved.file_path().display()))?
+            .to_string_lossy()
+            .to_string(),
+    };
+    let mut notes = Vec::new();
+    if options.include_kind {
+        notes.push(moved.kind().to_string());
+    }
+    // The address does not name the provider, so keep the alias visible to reviewers
+    if let Some(provider) = moved.provider() {
+        notes.push(format!("provider {}", provider));
+    }
+    let text = if let Some(template) = &options.template {
+        template
+            .replace(FILE_PLACEHOLDER, &filename)
+            .replace(PATH_PLACEHOLDER, &moved.file_path().display().to_string())
+            .replace(TYPE_PLACEHOLDER, moved.source_type())
+            .replace(NAME_PLACEHOLDER, moved.source_name())
+    } else if notes.is_empty() {
+        format!("From: {}", filename)
+    } else {
+        format!("From: {} ({})", filename, notes.join(", "))
+    };
+    let mut comment = options.style.format(&text);
+    if let Some(source_comment) = moved.source_comment() {
+        comment.push_str(source_comment);
+    }
+    if options.mark_risky
+        && let Some(risk) = moved.risk()
+    {
+        comment.push_str(
+            &options
+                .style
+                .format(&format!("TODO: review this move ({})", risk)),
+        );
+    }
+    Ok(comment)
+}
 
-        Ok(block)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moved_resource::MovedResource;
+    use hcl::edit::structure::Body;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn render(style: CommentStyle) -> Result<String> {
+        let resource = MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let block = resource.to_block_with(&CommentOptions {
+            style,
+            ..Default::default()
+        })?;
+        Ok(Body::builder().block(block).build().to_string())
+    }
+
+    #[test]
+    fn test_comment_styles() -> Result<()> {
+        assert!(render(CommentStyle::Hash)?.starts_with("# From: main.tf\nmoved {"));
+        assert!(render(CommentStyle::Slash)?.starts_with("// From: main.tf\nmoved {"));
+        assert!(render(CommentStyle::Block)?.starts_with("/* From: main.tf */\nmoved {"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_styles_round_trip() -> Result<()> {
+        for style in [CommentStyle::Hash, CommentStyle::Slash, CommentStyle::Block] {
+            let output = render(style)?;
+            let body = parse_body(&output)?;
+            assert_eq!(body.blocks().count(), 1, "style {:?}", style);
+            assert_eq!(body.to_string(), output, "style {:?}", style);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_relative_to_source_directory() -> Result<()> {
+        let resource = MovedResource::new(
+            vec!["aws_vpc".to_string(), "main".to_string()],
+            PathBuf::from("stack/modules/network/main.tf"),
+            "compute".to_string(),
+        )?;
+        let block = resource.to_block_with(&CommentOptions {
+            relative_to: vec![PathBuf::from("other"), PathBuf::from("stack")],
+            ..Default::default()
+        })?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.starts_with("# From: modules/network/main.tf\nmoved"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_path_styles() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let file = temp_dir.path().join("network").join("main.tf");
+        fs::create_dir(file.parent().unwrap())?;
+        fs::write(&file, "")?;
+        let resource = MovedResource::new(
+            vec!["aws_vpc".to_string(), "main".to_string()],
+            file.clone(),
+            "compute".to_string(),
+        )?;
+        let comment = |path: CommentPath| -> Result<String> {
+            let block = resource.to_block_with(&CommentOptions {
+                relative_to: vec![temp_dir.path().to_path_buf()],
+                path,
+                ..Default::default()
+            })?;
+            let output = Body::builder().block(block).build().to_string();
+            Ok(output.lines().next().unwrap_or_default().to_string())
+        };
+        assert_eq!(comment(CommentPath::Name)?, "# From: main.tf");
+        assert_eq!(comment(CommentPath::Relative)?, "# From: network/main.tf");
+        assert_eq!(
+            comment(CommentPath::Absolute)?,
+            format!("# From: {}", fs::canonicalize(&file)?.display())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_removed_block() -> Result<()> {
+        let resource = MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let block = resource.to_removed_block_with(&CommentOptions::default())?;
+        let output = Body::builder().block(block).build().to_string();
+        assert_eq!(
+            output,
+            "# From: main.tf\nremoved {\n  from = aws_instance.web\n\n  lifecycle {\n    destroy = false\n  }\n}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_template() -> Result<()> {
+        let resource = MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("infra/main.tf"),
+            "compute".to_string(),
+        )?;
+        let render = |template: &str| -> Result<String> {
+            let block = resource.to_block_with(&CommentOptions {
+                template: Some(template.to_string()),
+                ..Default::default()
+            })?;
+            Ok(Body::builder().block(block).build().to_string())
+        };
+
+        assert!(render("moved from {file}")?.starts_with("# moved from main.tf\nmoved {"));
+        assert!(
+            render("{type}.{name} from {path}")?
+                .starts_with("# aws_instance.web from infra/main.tf\nmoved {")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_comment() -> Result<()> {
+        let resource = MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let block = resource.to_block_with(&CommentOptions {
+            omit: true,
+            ..Default::default()
+        })?;
+        let output = Body::builder().block(block).build().to_string();
+        assert!(output.starts_with("moved {"));
+        assert!(!output.contains('#'));
+        Ok(())
+    }
+
+    #[test]
+    fn test_indent() -> Result<()> {
+        let resource = MovedResource::new(
+            vec!["aws_instance".to_string(), "web".to_string()],
+            PathBuf::from("main.tf"),
+            "compute".to_string(),
+        )?;
+        let render = |indent: Indent| -> Result<(String, String)> {
+            let options = CommentOptions {
+                indent,
+                ..Default::default()
+            };
+            let moved = Body::builder()
+                .block(resource.to_block_with(&options)?)
+                .build()
+                .to_string();
+            let removed = Body::builder()
+                .block(resource.to_removed_block_with(&options)?)
+                .build()
+                .to_string();
+            Ok((moved, removed))
+        };
+
+        let (moved, removed) = render(Indent::Spaces(4))?;
+        assert!(moved.contains("\n    from = aws_instance.web\n    to   = "));
+        assert!(removed.contains("\n    lifecycle {\n        destroy = false\n    }\n"));
+
+        let (moved, removed) = render(Indent::Tab)?;
+        assert!(moved.contains("\n\tfrom = aws_instance.web\n\tto   = "));
+        assert!(removed.contains("\n\tlifecycle {\n\t\tdestroy = false\n\t}\n"));
+        Ok(())
     }
 }
@@ -0,0 +1,152 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resource type include/exclude filter and block name exclude filter.
+//!
+//! Patterns are globs (`aws_*`, `google_compute_?`, `*_legacy`) matched against
+//! the whole resource type or name; a pattern without wildcards matches exactly.
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Include and exclude globs for resource types
+///
+/// A type passes if it matches an include glob (or no include globs are given)
+/// and no exclude glob. Exclude wins when both match.
+#[derive(Debug, Clone)]
+pub struct TypeFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl TypeFilter {
+    /// Compile the `--include-type` and `--exclude-type` globs
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(include, "--include-type")?)
+        };
+        Ok(Self {
+            include,
+            exclude: build_glob_set(exclude, "--exclude-type")?,
+        })
+    }
+
+    /// Whether resources of `resource_type` should be moved
+    pub fn is_match(&self, resource_type: &str) -> bool {
+        let included = self
+            .include
+            .as_ref()
+            .is_none_or(|include| include.is_match(resource_type));
+        included && !self.exclude.is_match(resource_type)
+    }
+}
+
+/// Exclude globs for resource and module names
+///
+/// A name passes if it matches none of the globs.
+#[derive(Debug, Clone)]
+pub struct NameFilter {
+    exclude: GlobSet,
+}
+
+impl NameFilter {
+    /// Compile the `--exclude-name` globs
+    pub fn new(exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            exclude: build_glob_set(exclude, "--exclude-name")?,
+        })
+    }
+
+    /// Whether blocks named `name` should be moved
+    pub fn is_match(&self, name: &str) -> bool {
+        !self.exclude.is_match(name)
+    }
+}
+
+fn build_glob_set(patterns: &[String], option: &str) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder
+            .add(Glob::new(pattern).with_context(|| format!("Invalid {}: {}", option, pattern))?);
+    }
+    builder
+        .build()
+        .with_context(|| format!("Invalid {}", option))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_filter(include: &[&str], exclude: &[&str]) -> Result<TypeFilter> {
+        let to_strings =
+            |patterns: &[&str]| -> Vec<String> { patterns.iter().map(|p| p.to_string()).collect() };
+        TypeFilter::new(&to_strings(include), &to_strings(exclude))
+    }
+
+    #[test]
+    fn test_type_filter_empty_matches_everything() -> Result<()> {
+        let filter = type_filter(&[], &[])?;
+        assert!(filter.is_match("aws_instance"));
+        assert!(filter.is_match("google_compute_instance"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_filter_include_glob_and_exact() -> Result<()> {
+        let filter = type_filter(&["aws_*", "google_storage_bucket"], &[])?;
+        assert!(filter.is_match("aws_instance"));
+        assert!(filter.is_match("aws_s3_bucket"));
+        assert!(filter.is_match("google_storage_bucket"));
+        assert!(!filter.is_match("google_compute_instance"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_filter_exclude_wins() -> Result<()> {
+        let filter = type_filter(&["aws_*"], &["aws_s3_*"])?;
+        assert!(filter.is_match("aws_instance"));
+        assert!(!filter.is_match("aws_s3_bucket"));
+        assert!(!filter.is_match("google_compute_instance"));
+
+        let filter = type_filter(&[], &["google_*"])?;
+        assert!(filter.is_match("aws_instance"));
+        assert!(!filter.is_match("google_compute_instance"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_name_filter_exclude() -> Result<()> {
+        let filter = NameFilter::new(&["*_legacy".to_string()])?;
+        assert!(filter.is_match("web"));
+        assert!(!filter.is_match("web_legacy"));
+        assert!(filter.is_match("db"));
+
+        let error = NameFilter::new(&["web_[legacy".to_string()]).unwrap_err();
+        assert!(error.to_string().contains("Invalid --exclude-name"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_filter_invalid_glob() {
+        let error = type_filter(&["aws_[instance"], &[]).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Invalid --include-type: aws_[instance")
+        );
+    }
+}
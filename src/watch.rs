@@ -0,0 +1,108 @@
+// Copyright 2025 Nils Petzall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watch source paths for Terraform file changes.
+//!
+//! `watch` blocks and calls back at the start and once per burst of changes
+//! to Terraform files, so `--watch` can regenerate the output while the
+//! configuration is being edited.
+
+use crate::terraform_files::Extensions;
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Quiet period after the last change before regenerating
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watch `srcs` and call `on_change` once the watches are in place, then after
+/// each burst of changes to files with one of the `extensions`
+///
+/// Changes to the `ignored` files, or to anything below an `ignored` directory, e.g.
+/// the generated output inside a watched directory, are not reported. Runs until
+/// the watcher stops.
+pub fn watch(
+    srcs: &[PathBuf],
+    recursive: bool,
+    extensions: &Extensions,
+    ignored: &[PathBuf],
+    debounce: Duration,
+    mut on_change: impl FnMut(),
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    for src in srcs {
+        watcher
+            .watch(src, mode)
+            .with_context(|| format!("Failed to watch {}", src.display()))?;
+    }
+    let ignored: Vec<PathBuf> = ignored.iter().map(|path| canonical(path)).collect();
+    let is_relevant = |event: &notify::Result<notify::Event>| match event {
+        Ok(event) => {
+            !matches!(event.kind, EventKind::Access(_))
+                && event
+                    .paths
+                    .iter()
+                    .any(|path| extensions.is_match(path) && !is_ignored(&ignored, path))
+        }
+        Err(_) => false,
+    };
+
+    // Changes made while the first run is still reading files trigger another run
+    on_change();
+    while let Ok(event) = rx.recv() {
+        if !is_relevant(&event) {
+            continue;
+        }
+        // Wait until the files have been quiet for a while, editors save in several steps
+        loop {
+            match rx.recv_timeout(debounce) {
+                Ok(_) => continue,
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+        on_change();
+    }
+    Ok(())
+}
+
+/// Whether `path` is one of the `ignored` paths or lies below one of them
+fn is_ignored(ignored: &[PathBuf], path: &Path) -> bool {
+    let path = canonical(path);
+    ignored.iter().any(|ignored| path.starts_with(ignored))
+}
+
+/// Canonical path, resolved through the parent directory for a file that does not
+/// exist (yet), e.g. an output file before the first run or a deleted file
+fn canonical(path: &Path) -> PathBuf {
+    if let Ok(path) = fs::canonicalize(path) {
+        return path;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if parent.as_os_str().is_empty() => {
+            canonical(Path::new(".")).join(name)
+        }
+        (Some(parent), Some(name)) => canonical(parent).join(name),
+        _ => path.to_path_buf(),
+    }
+}
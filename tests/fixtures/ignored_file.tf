@@ -0,0 +1,10 @@
+# moved_maker:ignore-file
+# Shared resources that stay in the root module
+
+resource "aws_vpc" "main" {
+  cidr_block = "10.0.0.0/16"
+}
+
+module "network" {
+  source = "./modules/network"
+}
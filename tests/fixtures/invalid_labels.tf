@@ -0,0 +1,13 @@
+resource "aws_instance" {
+  ami = "ami-12345"
+}
+
+module {
+  source = "./modules/web"
+}
+
+provider "aws" {}
+
+resource "aws_s3_bucket" "data" {
+  bucket = "my-bucket"
+}
@@ -0,0 +1,8 @@
+resource "aws_instance" "web" {
+  ami           = "ami-12345"
+  instance_type = "t3.micro"
+}
+
+resource "aws_s3_bucket" "data" {
+  bucket = "my-bucket"
+}
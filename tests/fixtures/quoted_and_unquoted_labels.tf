@@ -0,0 +1,15 @@
+resource "aws_instance" "web" {
+  ami = "ami-12345"
+}
+
+resource aws_s3_bucket data {
+  bucket = "my-bucket"
+}
+
+resource "aws_iam_role" app {
+  name = "app"
+}
+
+module web_server {
+  source = "./modules/web"
+}
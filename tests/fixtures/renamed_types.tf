@@ -0,0 +1,7 @@
+resource "aws_alb" "web" {
+  name = "web"
+}
+
+resource "aws_instance" "api" {
+  ami = "ami-12345"
+}
@@ -1,7 +1,9 @@
 use pretty_assertions::assert_eq;
+use std::ffi::OsStr;
 use std::fs;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 use tempfile::TempDir;
 
 fn get_binary_path() -> PathBuf {
@@ -37,7 +39,119 @@ fn test_single_resource_file() {
     assert!(stdout.contains("# From: main.tf"));
     assert!(stdout.contains("moved"));
     assert!(stdout.contains("from = aws_instance.web"));
-    assert!(stdout.contains("to = module.compute.aws_instance.web"));
+    assert!(stdout.contains("to   = module.compute.aws_instance.web"));
+    // Like `terraform fmt`: `=` aligned and exactly one trailing newline
+    assert!(
+        stdout.contains("  from = aws_instance.web\n  to   = module.compute.aws_instance.web\n")
+    );
+    assert!(stdout.ends_with("}\n") && !stdout.ends_with("\n\n"));
+}
+
+#[test]
+fn test_src_single_file() {
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("single_resource.tf");
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&fixture_file)
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# From: single_resource.tf"));
+    assert!(stdout.contains("from = aws_instance.web"));
+    assert!(stdout.contains("to   = module.compute.aws_instance.web"));
+    // Only the given file is read, not the other fixtures next to it
+    assert_eq!(stdout.matches("moved {").count(), 1);
+}
+
+#[test]
+fn test_header_precedes_moves() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let run = |extra_args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .arg("--src")
+            .arg(temp_dir.path())
+            .arg("--module-name")
+            .arg("compute")
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = run(&[]);
+    let blocks = stdout.matches("moved {").count();
+    let header = format!(
+        "# Generated by moved_maker for module \"compute\" ({} blocks)\n",
+        blocks
+    );
+    assert!(stdout.starts_with(&header));
+    assert!(stdout.find(&header) < stdout.find("moved {"));
+
+    let stdout = run(&["--no-header"]);
+    assert!(!stdout.contains("# Generated by moved_maker"));
+    assert!(stdout.starts_with("# From: main.tf\nmoved {"));
+}
+
+#[test]
+fn test_direction_outof() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+    fs::copy(
+        fixtures_dir.join("mixed_resources_and_modules.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--direction")
+        .arg("outof")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("  from = module.compute.aws_instance.web\n  to   = aws_instance.web\n")
+    );
+    assert!(
+        stdout.contains("  from = module.compute.module.web_server\n  to   = module.web_server\n")
+    );
 }
 
 #[test]
@@ -63,7 +177,7 @@ fn test_multiple_resources() {
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     // Should have 3 moved blocks
-    let moved_count = stdout.matches("moved").count();
+    let moved_count = stdout.matches("moved {").count();
     assert_eq!(moved_count, 3);
 
     assert!(stdout.contains("aws_instance.web1"));
@@ -94,7 +208,7 @@ fn test_mixed_resources_and_data() {
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     // Should have 1 moved block (resource only, data blocks are ignored)
-    let moved_count = stdout.matches("moved").count();
+    let moved_count = stdout.matches("moved {").count();
     assert_eq!(moved_count, 1);
 
     assert!(stdout.contains("aws_instance.web"));
@@ -102,6 +216,35 @@ fn test_mixed_resources_and_data() {
     assert!(!stdout.contains("data.aws_ami.example"));
 }
 
+#[test]
+fn test_include_data() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_blocks.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--include-data")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 2);
+    assert!(stdout.contains("from = aws_instance.web"));
+    assert!(stdout.contains("from = data.aws_ami.example"));
+    assert!(stdout.contains("to   = module.compute.data.aws_ami.example"));
+}
+
 #[test]
 fn test_multiple_files() {
     let temp_dir = TempDir::new().unwrap();
@@ -133,7 +276,7 @@ fn test_multiple_files() {
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     // Should have 1 moved block (only from main.tf, data.tf is ignored)
-    let moved_count = stdout.matches("moved").count();
+    let moved_count = stdout.matches("moved {").count();
     assert_eq!(moved_count, 1);
 
     assert!(stdout.contains("# From: main.tf"));
@@ -226,7 +369,7 @@ fn test_resource_with_count() {
     // Count doesn't affect the address, should still generate moved block
     assert!(stdout.contains("moved"));
     assert!(stdout.contains("from = aws_instance.web"));
-    assert!(stdout.contains("to = module.compute.aws_instance.web"));
+    assert!(stdout.contains("to   = module.compute.aws_instance.web"));
 }
 
 #[test]
@@ -254,7 +397,7 @@ fn test_resource_with_for_each() {
     // for_each doesn't affect the address, should still generate moved block
     assert!(stdout.contains("moved"));
     assert!(stdout.contains("from = aws_instance.web"));
-    assert!(stdout.contains("to = module.compute.aws_instance.web"));
+    assert!(stdout.contains("to   = module.compute.aws_instance.web"));
 }
 
 #[test]
@@ -336,7 +479,7 @@ fn test_single_module_file() {
     assert!(stdout.contains("# From: main.tf"));
     assert!(stdout.contains("moved"));
     assert!(stdout.contains("from = module.web_server"));
-    assert!(stdout.contains("to = module.a.module.web_server"));
+    assert!(stdout.contains("to   = module.a.module.web_server"));
 }
 
 #[test]
@@ -362,18 +505,18 @@ fn test_multiple_modules() {
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     // Should have 3 moved blocks
-    let moved_count = stdout.matches("moved").count();
+    let moved_count = stdout.matches("moved {").count();
     assert_eq!(moved_count, 3);
 
     assert!(stdout.contains("module.web_server"));
     assert!(stdout.contains("module.database"));
     assert!(stdout.contains("module.cache"));
     assert!(stdout.contains("from = module.web_server"));
-    assert!(stdout.contains("to = module.compute.module.web_server"));
+    assert!(stdout.contains("to   = module.compute.module.web_server"));
     assert!(stdout.contains("from = module.database"));
-    assert!(stdout.contains("to = module.compute.module.database"));
+    assert!(stdout.contains("to   = module.compute.module.database"));
     assert!(stdout.contains("from = module.cache"));
-    assert!(stdout.contains("to = module.compute.module.cache"));
+    assert!(stdout.contains("to   = module.compute.module.cache"));
 }
 
 #[test]
@@ -399,24 +542,24 @@ fn test_mixed_resources_and_modules() {
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     // Should have 4 moved blocks (2 resources + 2 modules, data blocks are ignored)
-    let moved_count = stdout.matches("moved").count();
+    let moved_count = stdout.matches("moved {").count();
     assert_eq!(moved_count, 4);
 
     // Verify resources
     assert!(stdout.contains("aws_instance.web"));
     assert!(stdout.contains("aws_s3_bucket.data"));
     assert!(stdout.contains("from = aws_instance.web"));
-    assert!(stdout.contains("to = module.compute.aws_instance.web"));
+    assert!(stdout.contains("to   = module.compute.aws_instance.web"));
     assert!(stdout.contains("from = aws_s3_bucket.data"));
-    assert!(stdout.contains("to = module.compute.aws_s3_bucket.data"));
+    assert!(stdout.contains("to   = module.compute.aws_s3_bucket.data"));
 
     // Verify modules
     assert!(stdout.contains("module.web_server"));
     assert!(stdout.contains("module.database"));
     assert!(stdout.contains("from = module.web_server"));
-    assert!(stdout.contains("to = module.compute.module.web_server"));
+    assert!(stdout.contains("to   = module.compute.module.web_server"));
     assert!(stdout.contains("from = module.database"));
-    assert!(stdout.contains("to = module.compute.module.database"));
+    assert!(stdout.contains("to   = module.compute.module.database"));
 
     // Data blocks should not be processed
     assert!(!stdout.contains("data.aws_ami.example"));
@@ -471,3 +614,2863 @@ fn test_module_name_with_underscores_for_modules() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("module.my_module.module.web_server"));
 }
+
+#[test]
+fn test_warn_name_collision_flags_matching_label() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("name_collision.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("web")
+        .arg("--warn-name-collision")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("to   = module.web.aws_instance.web"));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Warning: aws_instance.web"));
+    assert!(stderr.contains("same name as target module 'web'"));
+    // Unrelated names stay quiet
+    assert!(!stderr.contains("aws_s3_bucket.data"));
+}
+
+#[test]
+fn test_name_collision_without_flag_is_quiet() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("name_collision.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("web")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("same name as target module"));
+}
+
+#[test]
+fn test_output_dir_writes_one_file_per_source() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures_dir.join("single_resource.tf"),
+        src_dir.join("main.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_dir.join("single_module.tf"),
+        src_dir.join("modules.tf"),
+    )
+    .unwrap();
+
+    let out_dir = temp_dir.path().join("out").join("moved");
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--output-dir")
+        .arg(&out_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).trim().is_empty());
+
+    let main = fs::read_to_string(out_dir.join("moved_main.tf")).unwrap();
+    assert!(main.contains("to   = module.compute.aws_instance.web"));
+    let modules = fs::read_to_string(out_dir.join("moved_modules.tf")).unwrap();
+    assert!(modules.contains("to   = module.compute.module.web_server"));
+
+    // A second run refuses to clobber the files without --force
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--output-dir")
+        .arg(&out_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Refusing to overwrite existing file"));
+}
+
+#[test]
+fn test_format_tfjson() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--format")
+        .arg("tfjson")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let moved = value["moved"].as_array().unwrap();
+    assert_eq!(moved.len(), 3);
+    assert_eq!(moved[0]["from"], "aws_instance.web1");
+    assert_eq!(moved[0]["to"], "module.compute.aws_instance.web1");
+}
+
+#[test]
+fn test_format_json_records() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let records: Vec<serde_json::Value> = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(records.len(), 4);
+    for record in &records {
+        let from = record["from"].as_str().unwrap();
+        assert_eq!(record["to"], format!("module.compute.{}", from));
+        assert!(record["source_file"].as_str().unwrap().ends_with("main.tf"));
+        let expected_kind = if from.starts_with("module.") {
+            "module"
+        } else {
+            "resource"
+        };
+        assert_eq!(record["kind"], expected_kind);
+    }
+}
+
+#[test]
+fn test_block_type_removed() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--block-type")
+        .arg("removed")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("removed {").count(), 4);
+    assert_eq!(
+        stdout
+            .matches("  lifecycle {\n    destroy = false\n  }")
+            .count(),
+        4
+    );
+    assert!(stdout.contains("removed {\n  from = aws_instance.web\n"));
+    assert!(stdout.contains("removed {\n  from = module.web_server\n"));
+    assert!(!stdout.lines().any(|line| line.starts_with("moved {")));
+    assert!(!stdout.contains("module.compute"));
+}
+
+#[test]
+fn test_indent_width_and_tabs() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("single_resource.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let run = |extra: &[&str]| {
+        Command::new(&binary)
+            .arg("--src")
+            .arg(temp_dir.path())
+            .arg("--module-name")
+            .arg("compute")
+            .args(extra)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&["--indent", "4"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\n    from = aws_instance.web\n"));
+    assert!(stdout.contains("\n    to   = module.compute.aws_instance.web\n"));
+
+    let output = run(&["--use-tabs"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\n\tfrom = aws_instance.web\n"));
+
+    let output = run(&["--use-tabs", "--indent", "4"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_resource_name_regex_selects_matching_resources() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--resource-name-regex")
+        .arg("^web.*")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 2);
+    assert!(stdout.contains("from = aws_instance.web1"));
+    assert!(stdout.contains("from = aws_instance.web2"));
+    assert!(!stdout.contains("aws_s3_bucket.data"));
+}
+
+#[test]
+fn test_resource_name_regex_invalid_pattern_errors() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--resource-name-regex")
+        .arg("web(")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid --resource-name-regex"));
+}
+
+#[test]
+fn test_summary_groups_warnings_by_reason() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures_dir.join("invalid_syntax.tf"),
+        temp_dir.path().join("broken1.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_dir.join("invalid_syntax.tf"),
+        temp_dir.path().join("broken2.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_dir.join("invalid_labels.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--summary")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("from = aws_s3_bucket.data"));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "Summary: 2 files failed to parse, 2 blocks skipped (invalid labels), 1 unsupported block type"
+        ),
+        "Unexpected summary: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_verbose_prints_summary() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("invalid_labels.tf");
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--verbose")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Summary: 2 blocks skipped (invalid labels), 1 unsupported block type"),
+        "Unexpected summary: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_output_dir_conflict_writes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    let out_dir = temp_dir.path().join("out");
+    fs::create_dir(&src_dir).unwrap();
+    fs::create_dir(&out_dir).unwrap();
+    fs::write(src_dir.join("a.tf"), "resource \"aws_instance\" \"a\" {}\n").unwrap();
+    fs::write(src_dir.join("b.tf"), "resource \"aws_instance\" \"b\" {}\n").unwrap();
+    fs::write(out_dir.join("moved_b.tf"), "original").unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--output-dir")
+        .arg(&out_dir)
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Refusing to overwrite existing file"));
+    // moved_a.tf sorts first but is not written either
+    assert!(!out_dir.join("moved_a.tf").exists());
+    assert_eq!(
+        fs::read_to_string(out_dir.join("moved_b.tf")).unwrap(),
+        "original"
+    );
+}
+
+#[test]
+fn test_max_blocks_caps_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--max-blocks")
+        .arg("2")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 2);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Stopped after 2 blocks"));
+}
+
+#[test]
+fn test_max_blocks_keeps_first_blocks_in_sorted_order() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("a.tf"),
+        "resource \"aws_instance\" \"zeta\" {}\nresource \"aws_instance\" \"yankee\" {}\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("b.tf"),
+        "resource \"aws_instance\" \"bravo\" {}\nresource \"aws_instance\" \"alpha\" {}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--max-blocks")
+        .arg("2")
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 2);
+    let alpha = stdout.find("from = aws_instance.alpha").unwrap();
+    let bravo = stdout.find("from = aws_instance.bravo").unwrap();
+    assert!(alpha < bravo);
+    assert!(!stdout.contains("zeta"));
+    assert!(!stdout.contains("yankee"));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Stopped after 2 blocks (--max-blocks), 2 more left out"));
+}
+
+#[test]
+fn test_max_blocks_above_total_is_quiet() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--max-blocks")
+        .arg("3")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 3);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("Stopped after"));
+}
+
+#[test]
+fn test_ignore_file_marker_skips_whole_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures_dir.join("ignored_file.tf"),
+        temp_dir.path().join("shared.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_dir.join("single_resource.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        // The skip note is only logged with --verbose
+        .arg("--verbose")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 1);
+    assert!(stdout.contains("from = aws_instance.web"));
+    assert!(!stdout.contains("aws_vpc.main"));
+    assert!(!stdout.contains("module.network"));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("shared.tf"));
+}
+
+#[test]
+fn test_json_schema_dump() {
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--json-schema")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    for name in ["kind", "from", "to", "source_file"] {
+        assert!(schema["moved_block"]["properties"].get(name).is_some());
+    }
+    for name in ["blocks", "warnings"] {
+        assert!(schema["run_summary"]["properties"].get(name).is_some());
+    }
+}
+
+#[test]
+fn test_missing_src_without_json_schema_errors() {
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--src"));
+}
+
+#[test]
+fn test_rename_map_rewrites_target_type_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures_dir.join("renamed_types.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--rename-map")
+        .arg(fixtures_dir.join("rename.map"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("from = aws_alb.web"));
+    assert!(stdout.contains("to   = module.compute.aws_lb.web"));
+    assert!(stdout.contains("from = aws_instance.api"));
+    assert!(stdout.contains("to   = module.compute.aws_instance.api"));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("aws_alb.web"));
+    assert!(stderr.contains("destroys and recreates"));
+}
+
+#[test]
+fn test_watch_regenerates_on_change() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(
+        src_dir.join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\n",
+    )
+    .unwrap();
+    let output_file = temp_dir.path().join("moved.tf");
+
+    let binary = get_binary_path();
+    let mut child = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--output")
+        .arg(&output_file)
+        .arg("--watch")
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute command");
+
+    let wait_for = |expected: &str| {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if fs::read_to_string(&output_file).is_ok_and(|content| content.contains(expected)) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        false
+    };
+
+    let initial = wait_for("from = aws_instance.web");
+    fs::write(
+        src_dir.join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\nresource \"aws_instance\" \"api\" {}\n",
+    )
+    .unwrap();
+    let regenerated = initial && wait_for("from = aws_instance.api");
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(initial, "Initial output was not written");
+    assert!(regenerated, "Output was not regenerated after the change");
+}
+
+#[test]
+fn test_merge_into_keeps_existing_blocks() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(
+        src_dir.join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\nresource \"aws_instance\" \"api\" {}\n",
+    )
+    .unwrap();
+    let moved_file = temp_dir.path().join("moved.tf");
+    let hand_written = "# Renamed by hand\nmoved {\n  from = aws_instance.web\n  to   = module.compute.aws_instance.web\n}\n";
+    fs::write(&moved_file, hand_written).unwrap();
+
+    let run = || {
+        let output = Command::new(get_binary_path())
+            .arg("--src")
+            .arg(&src_dir)
+            .arg("--module-name")
+            .arg("compute")
+            .arg("--merge-into")
+            .arg(&moved_file)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(output.stdout.is_empty());
+        fs::read_to_string(&moved_file).unwrap()
+    };
+
+    let merged = run();
+    assert!(merged.starts_with(hand_written));
+    assert_eq!(merged.matches("from = aws_instance.web").count(), 1);
+    assert_eq!(merged.matches("from = aws_instance.api").count(), 1);
+    assert_eq!(merged.matches("moved {").count(), 2);
+    assert!(!merged.contains("# Generated by moved_maker"));
+
+    // Running again finds nothing new and leaves the file as it is
+    assert_eq!(run(), merged);
+
+    // New resources are appended without a header of their own
+    fs::write(
+        src_dir.join("db.tf"),
+        "resource \"aws_db_instance\" \"main\" {}\n",
+    )
+    .unwrap();
+    let merged = run();
+    assert_eq!(merged.matches("moved {").count(), 3);
+    assert!(!merged.contains("# Generated by moved_maker"));
+}
+
+#[test]
+fn test_diff_shows_moves_missing_from_existing_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(
+        src_dir.join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\nresource \"aws_instance\" \"api\" {}\n",
+    )
+    .unwrap();
+    let moved_file = temp_dir.path().join("moved.tf");
+    fs::write(
+        &moved_file,
+        "moved {\n  from = aws_instance.web\n  to   = module.compute.aws_instance.web\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--diff")
+        .arg(&moved_file)
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout,
+        format!(
+            "--- {}\n+++ generated\n+moved {{\n+  from = aws_instance.api\n+  to   = module.compute.aws_instance.api\n+}}\n",
+            moved_file.display()
+        )
+    );
+}
+
+#[test]
+fn test_only_file_moves_listed_addresses() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(
+        src_dir.join("main.tf"),
+        r#"
+resource "aws_instance" "web" {}
+resource "aws_instance" "api" {}
+resource "aws_s3_bucket" "logs" {}
+"#,
+    )
+    .unwrap();
+    let only_file = temp_dir.path().join("stage1.txt");
+    fs::write(
+        &only_file,
+        "# Stage 1
+aws_instance.api
+",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--only-file")
+        .arg(&only_file)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 1);
+    assert!(stdout.contains("from = aws_instance.api"));
+    assert!(!stdout.contains("aws_instance.web"));
+    assert!(!stdout.contains("aws_s3_bucket.logs"));
+}
+
+#[test]
+fn test_only_changed_emits_only_new_moves() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("single_resource.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    // Take a snapshot of the current output
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let snapshot = temp_dir.path().join("moved.tf");
+    fs::write(&snapshot, &output.stdout).unwrap();
+
+    // A new resource appears
+    fs::write(
+        src_dir.join("storage.tf"),
+        "resource \"aws_s3_bucket\" \"data\" {}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--only-changed")
+        .arg(&snapshot)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 1);
+    assert!(stdout.contains("from = aws_s3_bucket.data"));
+    assert!(!stdout.contains("aws_instance.web"));
+}
+
+#[test]
+fn test_quoted_and_unquoted_labels_produce_identical_addresses() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("quoted_and_unquoted_labels.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        // The header names the target module in quotes
+        .arg("--no-header")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 4);
+    assert!(stdout.contains("from = aws_instance.web\n"));
+    assert!(stdout.contains("from = aws_s3_bucket.data\n"));
+    assert!(stdout.contains("from = aws_iam_role.app\n"));
+    assert!(stdout.contains("to   = module.compute.aws_iam_role.app\n"));
+    assert!(stdout.contains("from = module.web_server\n"));
+    // Labels never carry their source quotes into addresses
+    assert!(!stdout.contains('"'));
+}
+
+#[test]
+fn test_emit_comment_kind() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--emit-comment-kind")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("# From: main.tf (resource)").count(), 2);
+    assert_eq!(stdout.matches("# From: main.tf (module)").count(), 2);
+}
+
+#[test]
+fn test_comment_template() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--no-header")
+        .arg("--comment-template")
+        .arg("moved from {file}")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# moved from main.tf\nmoved {"));
+    assert!(!stdout.contains("From:"));
+}
+
+#[test]
+fn test_no_comment() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "# TODO: enable monitoring\nresource \"aws_instance\" \"web\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--no-header")
+        .arg("--no-comment")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("moved {"));
+    assert!(
+        !stdout
+            .lines()
+            .any(|line| line.trim_start().starts_with('#'))
+    );
+}
+
+#[test]
+fn test_module_directive_overrides_module_name() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\n\n# moved_maker: module=network\nresource \"aws_vpc\" \"main\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("  from = aws_instance.web\n  to   = module.compute.aws_instance.web\n")
+    );
+    assert!(stdout.contains("  from = aws_vpc.main\n  to   = module.network.aws_vpc.main\n"));
+    assert!(!stdout.contains("moved_maker: module="));
+}
+
+#[test]
+fn test_source_comments_are_preserved() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_s3_bucket\" \"logs\" {}\n\n# TODO: enable versioning\nresource \"aws_instance\" \"web\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(
+        "# From: main.tf\n# TODO: enable versioning\nmoved {\n  from = aws_instance.web\n"
+    ));
+    assert!(stdout.contains("# From: main.tf\nmoved {\n  from = aws_s3_bucket.logs\n"));
+    assert_eq!(stdout.matches("# TODO: enable versioning").count(), 1);
+}
+
+#[test]
+fn test_error_on_parse_fails_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures_dir.join("invalid_syntax.tf"),
+        temp_dir.path().join("invalid.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_dir.join("mixed_blocks.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--error-on")
+        .arg("parse,collision")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("1 file failed to parse (promoted to error by --error-on)"));
+}
+
+#[test]
+fn test_error_on_parse_leaves_unsupported_as_warning() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_blocks.tf");
+
+    // mixed_blocks.tf contains data blocks, which are unsupported block types
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--error-on")
+        .arg("parse")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("from = aws_instance.web"));
+}
+
+#[test]
+fn test_ledger_second_run_emits_nothing_new() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+    let ledger = temp_dir.path().join("ledger.jsonl");
+
+    let binary = get_binary_path();
+    let run = || {
+        Command::new(&binary)
+            .arg("--src")
+            .arg(&src_dir)
+            .arg("--module-name")
+            .arg("compute")
+            .arg("--ledger")
+            .arg(&ledger)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let first = run();
+    assert!(
+        first.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&first.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&first.stdout)
+            .matches("moved {")
+            .count(),
+        3
+    );
+    assert_eq!(fs::read_to_string(&ledger).unwrap().lines().count(), 3);
+
+    let second = run();
+    assert!(second.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&second.stdout)
+            .matches("moved {")
+            .count(),
+        0
+    );
+    assert_eq!(fs::read_to_string(&ledger).unwrap().lines().count(), 3);
+}
+
+#[test]
+fn test_report_unmoved_lists_filtered_resources() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+    let report = temp_dir.path().join("unmoved.json");
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--resource-name-regex")
+        .arg("^web")
+        .arg("--report-unmoved")
+        .arg(&report)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    let entries = report.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["ident"], "resource");
+    assert_eq!(
+        entries[0]["labels"],
+        serde_json::json!(["aws_s3_bucket", "data"])
+    );
+    assert_eq!(entries[0]["reason"], "filtered");
+    assert!(entries[0]["file"].as_str().unwrap().ends_with("main.tf"));
+}
+
+#[test]
+fn test_from_plan_generates_moves() {
+    let plan = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("plan.json");
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--from-plan")
+        .arg(&plan)
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# From: plan.json"));
+    assert!(stdout.contains("from = aws_instance.web"));
+    assert!(stdout.contains("to   = module.compute.aws_instance.web"));
+    assert!(stdout.contains("from = aws_s3_bucket.data"));
+    assert!(stdout.contains("from = module.network"));
+    assert!(!stdout.contains("aws_ami"));
+    assert_eq!(stdout.matches("moved {").count(), 3);
+}
+
+#[test]
+fn test_interactive_requires_terminal() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("single_resource.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--interactive")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--interactive requires a terminal"));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_group_by_file_orders_by_file_then_address() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(
+        src_dir.join("storage.tf"),
+        "resource \"aws_s3_bucket\" \"logs\" {}\nresource \"aws_s3_bucket\" \"data\" {}\n",
+    )
+    .unwrap();
+    fs::write(
+        src_dir.join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\nresource \"aws_instance\" \"api\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--group-by-file")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let froms: Vec<&str> = stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("from = "))
+        .collect();
+    assert_eq!(
+        froms,
+        vec![
+            "aws_instance.api",
+            "aws_instance.web",
+            "aws_s3_bucket.data",
+            "aws_s3_bucket.logs",
+        ]
+    );
+}
+
+#[test]
+fn test_statemv_mode_emits_quoted_commands() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--mode")
+        .arg("statemv")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("#!/bin/sh\n"));
+    assert!(!stdout.contains("moved {"));
+    let commands: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("terraform state mv "))
+        .collect();
+    assert!(!commands.is_empty());
+    assert!(
+        commands
+            .iter()
+            .any(|line| line.contains("'module.compute.module."))
+    );
+    for command in commands {
+        let args = command.trim_start_matches("terraform state mv ");
+        let (from, to) = args.split_once(' ').unwrap();
+        assert!(
+            from.starts_with('\'') && from.ends_with('\''),
+            "{}",
+            command
+        );
+        assert!(
+            to.starts_with("'module.compute.") && to.ends_with('\''),
+            "{}",
+            command
+        );
+    }
+}
+
+#[test]
+fn test_multiple_src_processes_shared_file_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("single_resource.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--src")
+        .arg(temp_dir.path().join("src").join("."))
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 1);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("same file as"));
+}
+
+fn run_with_prefixes(prefix_args: &[&str]) -> String {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .args(prefix_args)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_prefixes_move_in() {
+    let stdout = run_with_prefixes(&["--prefix-from", "", "--prefix-to", "module.compute"]);
+    assert!(stdout.contains("from = aws_instance.web"));
+    assert!(stdout.contains("to   = module.compute.aws_instance.web"));
+    assert!(stdout.contains("from = module.web_server"));
+    assert!(stdout.contains("to   = module.compute.module.web_server"));
+}
+
+#[test]
+fn test_prefixes_move_out() {
+    let stdout = run_with_prefixes(&["--prefix-from", "module.legacy", "--prefix-to", ""]);
+    assert!(stdout.contains("from = module.legacy.aws_instance.web"));
+    assert!(stdout.contains("to   = aws_instance.web"));
+    assert!(stdout.contains("from = module.legacy.module.web_server"));
+    assert!(stdout.contains("to   = module.web_server"));
+}
+
+#[test]
+fn test_prefixes_rehome_between_modules() {
+    let stdout = run_with_prefixes(&[
+        "--prefix-from",
+        "module.a",
+        "--prefix-to",
+        "module.b[\"eu\"]",
+    ]);
+    assert!(stdout.contains("from = module.a.aws_instance.web"));
+    assert!(stdout.contains("to   = module.b[\"eu\"].aws_instance.web"));
+    assert!(stdout.contains("from = module.a.module.web_server"));
+    assert!(stdout.contains("to   = module.b[\"eu\"].module.web_server"));
+}
+
+#[test]
+fn test_source_module_prefixes_from() {
+    let stdout = run_with_prefixes(&["--source-module", "existing", "--module-name", "compute"]);
+    assert!(stdout.contains(
+        "  from = module.existing.aws_instance.web\n  to   = module.compute.aws_instance.web\n"
+    ));
+    assert!(stdout.contains(
+        "  from = module.existing.module.web_server\n  to   = module.compute.module.web_server\n"
+    ));
+}
+
+#[test]
+fn test_error_format_json_warnings() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures.join("invalid_syntax.tf"),
+        src_dir.join("broken.tf"),
+    )
+    .unwrap();
+    fs::copy(fixtures.join("single_resource.tf"), src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--error-format")
+        .arg("json")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let warnings: Vec<serde_json::Value> = stderr
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).expect("Each diagnostic line is JSON"))
+        .collect();
+    assert!(!stderr.contains("Warning:"));
+    let parse_warning = warnings
+        .iter()
+        .find(|w| w["category"] == "parse")
+        .expect("Expected a parse warning");
+    assert_eq!(parse_warning["level"], "warning");
+    assert!(
+        parse_warning["file"]
+            .as_str()
+            .unwrap()
+            .ends_with("broken.tf")
+    );
+    assert!(
+        parse_warning["message"]
+            .as_str()
+            .unwrap()
+            .contains("Failed to parse")
+    );
+}
+
+#[test]
+fn test_error_format_json_fatal_error() {
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg("/nonexistent/path")
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--error-format")
+        .arg("json")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let error: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+    assert_eq!(error["level"], "error");
+    assert!(
+        error["message"]
+            .as_str()
+            .unwrap()
+            .contains("Source directory does not exist")
+    );
+}
+
+fn run_failure_mode(mode: Option<&str>) -> std::process::Output {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures.join("invalid_syntax.tf"),
+        src_dir.join("broken.tf"),
+    )
+    .unwrap();
+    fs::copy(fixtures.join("single_resource.tf"), src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .args(mode)
+        .output()
+        .expect("Failed to execute command")
+}
+
+#[test]
+fn test_failure_modes_exit_codes() {
+    for mode in [None, Some("--best-effort")] {
+        let output = run_failure_mode(mode);
+        assert!(output.status.success(), "{:?} should succeed", mode);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("moved {"),
+            "{:?} should produce partial output",
+            mode
+        );
+    }
+
+    for mode in ["--strict", "--fail-fast"] {
+        let output = run_failure_mode(Some(mode));
+        assert!(!output.status.success(), "{} should fail", mode);
+        assert!(
+            output.stdout.is_empty(),
+            "{} should not produce output",
+            mode
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("1 file failed to parse"),
+            "{}: {}",
+            mode,
+            stderr
+        );
+        assert!(
+            stderr.contains(&format!("({})", mode)),
+            "{}: {}",
+            mode,
+            stderr
+        );
+    }
+}
+
+#[test]
+fn test_nothing_generated_exits_with_code_4() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("invalid_syntax.tf");
+    fs::copy(&fixture_file, temp_dir.path().join("a.tf")).unwrap();
+    fs::copy(&fixture_file, temp_dir.path().join("b.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No moved blocks generated; 2 files failed"));
+}
+
+#[test]
+fn test_invalid_module_name_exits_with_code_2() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("123invalid")
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Module name must start with a letter or underscore"));
+}
+
+#[test]
+fn test_missing_source_directory_exits_with_code_3() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path().join("missing"))
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Source directory does not exist"));
+}
+
+#[test]
+fn test_scaffold_module_with_target_source() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--scaffold-module")
+        .arg("--target-source")
+        .arg("app.terraform.io/acme/compute/aws")
+        .arg("--no-header")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(
+        "module \"compute\" {\n  source = \"app.terraform.io/acme/compute/aws\"\n}\n"
+    ));
+    assert_eq!(stdout.matches("module \"compute\"").count(), 1);
+    assert!(stdout.matches("moved {").count() > 1);
+}
+
+#[test]
+fn test_options_from_environment_match_cli() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let from_cli = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--format")
+        .arg("tfjson")
+        .arg("--resource-name-regex")
+        .arg("^web$")
+        .output()
+        .expect("Failed to execute command");
+    let from_env = Command::new(&binary)
+        .env("MOVED_MAKER_SRC", &src_dir)
+        .env("MOVED_MAKER_MODULE_NAME", "compute")
+        .env("MOVED_MAKER_FORMAT", "tfjson")
+        .env("MOVED_MAKER_RESOURCE_NAME_REGEX", "^web$")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        from_env.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&from_env.stderr)
+    );
+    assert_eq!(from_env.stdout, from_cli.stdout);
+
+    // Command-line arguments take precedence over the environment
+    let overridden = Command::new(&binary)
+        .env("MOVED_MAKER_SRC", &src_dir)
+        .env("MOVED_MAKER_MODULE_NAME", "compute")
+        .env("MOVED_MAKER_FORMAT", "tfjson")
+        .arg("--format")
+        .arg("hcl")
+        .output()
+        .expect("Failed to execute command");
+    assert!(String::from_utf8_lossy(&overridden.stdout).contains("moved {"));
+}
+
+#[test]
+fn test_preview_prints_first_blocks_to_stderr() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    let out_dir = temp_dir.path().join("out");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--output-dir")
+        .arg(&out_dir)
+        .arg("--preview")
+        .arg("2")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let written = fs::read_to_string(out_dir.join("moved_main.tf")).unwrap();
+    let total = written.matches("moved {").count();
+    assert!(total > 2);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("moved {").count(), 2);
+    assert!(stderr.contains(&format!("Preview (2 of {} blocks):", total)));
+}
+
+fn run_with_duplicate_addresses(extra_args: &[&str]) -> std::process::Output {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures.join("duplicate_address_a.tf"),
+        src_dir.join("a.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures.join("duplicate_address_b.tf"),
+        src_dir.join("b.tf"),
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .args(extra_args)
+        .output()
+        .expect("Failed to execute command")
+}
+
+#[test]
+fn test_duplicate_address_across_files_warns() {
+    let output = run_with_duplicate_addresses(&[]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let warning = stderr
+        .lines()
+        .find(|line| line.contains("is defined in multiple files"))
+        .expect("Expected a duplicate address warning");
+    assert!(warning.contains("aws_instance.web"));
+    assert!(warning.contains("a.tf"));
+    assert!(warning.contains("b.tf"));
+    assert!(!stderr.contains("aws_s3_bucket.logs is defined"));
+}
+
+#[test]
+fn test_duplicate_address_across_files_strict_fails() {
+    let output = run_with_duplicate_addresses(&["--strict"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("1 address defined in multiple files (--strict)"));
+}
+
+fn run_with_missing_key(policy: &str) -> std::process::Output {
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures.join("for_each_resource.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures.join("single_module.tf"),
+        temp_dir.path().join("modules.tf"),
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--missing-key")
+        .arg(policy)
+        .output()
+        .expect("Failed to execute command")
+}
+
+#[test]
+fn test_missing_key_skip() {
+    let output = run_with_missing_key("skip");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("aws_instance.web"));
+    assert!(stdout.contains("from = module.web_server"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Skipping aws_instance.web"));
+}
+
+#[test]
+fn test_missing_key_placeholder() {
+    let output = run_with_missing_key("placeholder");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#"from = aws_instance.web["REPLACE"]"#));
+    assert!(stdout.contains(r#"to   = module.compute.aws_instance.web["REPLACE"]"#));
+    assert!(stdout.contains("from = module.web_server\n"));
+}
+
+#[test]
+fn test_missing_key_error() {
+    let output = run_with_missing_key("error");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no instance key is known"));
+}
+
+#[test]
+fn test_multiple_formats_and_outputs() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+    let hcl_output = temp_dir.path().join("moved.tf");
+    let json_output = temp_dir.path().join("moved.tf.json");
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--format")
+        .arg("hcl")
+        .arg("--output")
+        .arg(&hcl_output)
+        .arg("--format")
+        .arg("tfjson")
+        .arg("--output")
+        .arg(&json_output)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stdout.is_empty());
+
+    let hcl = fs::read_to_string(&hcl_output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json_output).unwrap()).unwrap();
+    let moves = json["moved"].as_array().unwrap();
+    assert_eq!(moves.len(), hcl.matches("moved {").count());
+    for moved in moves {
+        assert!(hcl.contains(&format!("from = {}", moved["from"].as_str().unwrap())));
+        assert!(hcl.contains(&format!("to   = {}", moved["to"].as_str().unwrap())));
+    }
+}
+
+#[test]
+fn test_mark_risky_flags_renamed_type_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+
+    fs::copy(
+        fixtures_dir.join("renamed_types.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--rename-map")
+        .arg(fixtures_dir.join("rename.map"))
+        .arg("--mark-risky")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("# TODO: review this move").count(), 1);
+    let risky = stdout
+        .split("# From:")
+        .find(|block| block.contains("# TODO: review this move"))
+        .expect("Expected a marked block");
+    assert!(risky.contains("(renames resource type)"));
+    assert!(risky.contains("from = aws_alb.web"));
+}
+
+#[test]
+fn test_src_stdin_list() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let temp_dir = TempDir::new().unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+    let main = temp_dir.path().join("main.tf");
+    let modules = temp_dir.path().join("modules.tf");
+    let skipped = temp_dir.path().join("skipped.tf");
+    fs::copy(fixtures_dir.join("single_resource.tf"), &main).unwrap();
+    fs::copy(fixtures_dir.join("single_module.tf"), &modules).unwrap();
+    fs::copy(fixtures_dir.join("multiple_resources.tf"), &skipped).unwrap();
+
+    let binary = get_binary_path();
+    let mut child = Command::new(&binary)
+        .arg("--src-stdin-list")
+        .arg("--module-name")
+        .arg("compute")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    write!(
+        child.stdin.take().unwrap(),
+        "{}\n\n{}\n",
+        main.display(),
+        modules.display()
+    )
+    .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("to   = module.compute.aws_instance.web"));
+    assert!(stdout.contains("to   = module.compute.module.web_server"));
+    assert_eq!(stdout.matches("moved {").count(), 2);
+}
+
+#[test]
+fn test_src_stdin_reads_configuration() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let binary = get_binary_path();
+    let mut child = Command::new(&binary)
+        .arg("--src")
+        .arg("-")
+        .arg("--module-name")
+        .arg("compute")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    writeln!(
+        child.stdin.take().unwrap(),
+        "resource \"aws_instance\" \"web\" {{}}"
+    )
+    .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(
+        "# From: <stdin>\nmoved {\n  from = aws_instance.web\n  to   = module.compute.aws_instance.web\n"
+    ));
+}
+
+#[test]
+fn test_src_stdin_list_missing_path_strict_fails() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let temp_dir = TempDir::new().unwrap();
+    let binary = get_binary_path();
+    let mut child = Command::new(&binary)
+        .arg("--src-stdin-list")
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--strict")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    writeln!(
+        child.stdin.take().unwrap(),
+        "{}",
+        temp_dir.path().join("missing.tf").display()
+    )
+    .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+/// `(from, to)` pairs of the moved blocks in HCL output, in order
+fn moved_pairs(hcl: &str) -> Vec<(String, String)> {
+    let value = |line: &str| line.split('=').nth(1).unwrap().trim().to_string();
+    let froms = hcl.lines().filter(|line| line.trim().starts_with("from "));
+    let tos = hcl.lines().filter(|line| line.trim().starts_with("to "));
+    froms.zip(tos).map(|(f, t)| (value(f), value(t))).collect()
+}
+
+#[test]
+fn test_with_rollback_swaps_forward_moves() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("mixed_resources_and_modules.tf");
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+    let rollback_file = temp_dir.path().join("rollback.tf");
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(&src_dir)
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--with-rollback")
+        .arg(&rollback_file)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let forward = moved_pairs(&String::from_utf8_lossy(&output.stdout));
+    let rollback = fs::read_to_string(&rollback_file).unwrap();
+    assert!(rollback.starts_with("# Rollback moves generated by moved_maker"));
+    let swapped: Vec<(String, String)> = forward
+        .iter()
+        .map(|(from, to)| (to.clone(), from.clone()))
+        .collect();
+    assert_eq!(moved_pairs(&rollback), swapped);
+    assert!(forward.iter().any(|(from, _)| from.starts_with("module.")));
+    assert!(forward.iter().any(|(from, _)| !from.starts_with("module.")));
+}
+
+#[test]
+fn test_recursive_discovers_nested_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let nested_dir = temp_dir.path().join("modules").join("network");
+    fs::create_dir_all(&nested_dir).unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+    fs::copy(
+        fixtures_dir.join("single_resource.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_dir.join("single_module.tf"),
+        nested_dir.join("main.tf"),
+    )
+    .unwrap();
+
+    let run = |extra_args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("--src")
+            .arg(temp_dir.path())
+            .arg("--module-name")
+            .arg("compute")
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&["--recursive"]);
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# From: main.tf\n"));
+    assert!(stdout.contains("# From: modules/network/main.tf\n"));
+    assert!(stdout.contains("from = module.web_server"));
+
+    // Without the flag nested files are ignored
+    let output = run(&[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("module.web_server"));
+
+    // --comment-path overrides the default for recursive runs
+    let output = run(&["--recursive", "--comment-path", "name"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("# From: main.tf\n").count(), 2);
+    assert!(!stdout.contains("modules/network"));
+
+    let output = run(&["--comment-path", "absolute"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let absolute = fs::canonicalize(temp_dir.path().join("main.tf")).unwrap();
+    assert!(stdout.contains(&format!("# From: {}\n", absolute.display())));
+}
+
+#[test]
+fn test_output_with_tricky_labels_validates() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"1web\" {}\nresource \"aws_instance\" \"my web\" {}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#"to   = module.compute.aws_instance["1web"]"#));
+    assert!(stdout.contains(r#"to   = module.compute.aws_instance["my web"]"#));
+}
+
+#[test]
+fn test_group_by_dir_sections() {
+    let temp_dir = TempDir::new().unwrap();
+    let network_dir = temp_dir.path().join("modules").join("network");
+    let storage_dir = temp_dir.path().join("modules").join("storage");
+    fs::create_dir_all(&network_dir).unwrap();
+    fs::create_dir_all(&storage_dir).unwrap();
+    fs::write(
+        network_dir.join("main.tf"),
+        "resource \"aws_vpc\" \"main\" {}\n",
+    )
+    .unwrap();
+    fs::write(
+        storage_dir.join("buckets.tf"),
+        "resource \"aws_s3_bucket\" \"logs\" {}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--recursive")
+        .arg("--group-by-dir")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let network = stdout
+        .find("# Directory: modules/network\n# From: modules/network/main.tf\nmoved {\n  from = aws_vpc.main")
+        .expect("Expected a modules/network section");
+    let storage = stdout
+        .find("# Directory: modules/storage\n# From: modules/storage/buckets.tf\nmoved {\n  from = aws_s3_bucket.logs")
+        .expect("Expected a modules/storage section");
+    assert!(network < storage);
+}
+
+#[test]
+fn test_ignore_skips_matching_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let examples_dir = temp_dir.path().join("modules").join("examples");
+    fs::create_dir_all(&examples_dir).unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+    fs::copy(
+        fixtures_dir.join("single_resource.tf"),
+        temp_dir.path().join("main.tf"),
+    )
+    .unwrap();
+    fs::copy(
+        fixtures_dir.join("single_module.tf"),
+        examples_dir.join("main.tf"),
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--recursive")
+        .arg("--ignore")
+        .arg("**/examples/*.tf")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("from = aws_instance.web"));
+    assert!(!stdout.contains("module.web_server"));
+}
+
+#[test]
+fn test_output_file_and_no_clobber() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("single_resource.tf");
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+    let output_file = temp_dir.path().join("moved.tf");
+    fs::write(&output_file, "stale").unwrap();
+
+    let run = |extra_args: &[&str]| {
+        Command::new(get_binary_path())
+            .arg("--src")
+            .arg(&src_dir)
+            .arg("--module-name")
+            .arg("compute")
+            .arg("--output")
+            .arg(&output_file)
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&[]);
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stdout.is_empty());
+    let written = fs::read_to_string(&output_file).unwrap();
+    assert!(written.contains("to   = module.compute.aws_instance.web"));
+
+    fs::write(&output_file, "kept").unwrap();
+    let output = run(&["--no-clobber"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+    assert_eq!(fs::read_to_string(&output_file).unwrap(), "kept");
+}
+
+#[test]
+fn test_dry_run_reports_counts_only() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\nmodule \"network\" {\n  source = \"./network\"\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("storage.tf"),
+        "resource \"aws_s3_bucket\" \"logs\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--dry-run")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Would generate 3 moved blocks (2 resources, 1 module) from 2 files"));
+}
+
+#[test]
+fn test_count_only_prints_number() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\nmodule \"network\" {\n  source = \"./network\"\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("storage.tf"),
+        "resource \"aws_s3_bucket\" \"logs\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--count-only")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+}
+
+#[test]
+fn test_lenient_parse_recovers_block_headers() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {\n  user_data = <<-EOT\n    echo hello\n}\n\nmodule \"network\" {\n  source = \"./network\"\n}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--lenient-parse")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("  from = aws_instance.web\n  to   = module.compute.aws_instance.web\n")
+    );
+    assert!(stdout.contains("  from = module.network\n  to   = module.compute.module.network\n"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("read block headers only"));
+}
+
+#[test]
+fn test_verbose_logs_progress() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let run = |verbose: bool| {
+        let mut command = Command::new(&binary);
+        command
+            .current_dir(temp_dir.path())
+            .env_remove("RUST_LOG")
+            .arg("--src")
+            .arg("main.tf")
+            .arg("--module-name")
+            .arg("compute");
+        if verbose {
+            command.arg("--verbose");
+        }
+        command.output().expect("Failed to execute command")
+    };
+
+    let output = run(true);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Parsing main.tf"));
+    assert!(stderr.contains("Parsed main.tf: 1 blocks"));
+    assert!(
+        stderr
+            .contains("Generated moved block: aws_instance.web -> module.compute.aws_instance.web")
+    );
+
+    let output = run(false);
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_state_warns_for_missing_from_address() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\nresource \"aws_instance\" \"api\" {}\n",
+    )
+    .unwrap();
+    let state = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("terraform.tfstate");
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--state")
+        .arg(&state)
+        .output()
+        .expect("Failed to execute command");
+
+    // Read-only check, the moves are still written
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("moved {").count(), 2);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Warning: aws_instance.api in "));
+    assert!(stderr.contains("is not in state"));
+    assert!(!stderr.contains("aws_instance.web in "));
+}
+
+#[test]
+fn test_fail_on_empty_directory() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--fail-on-empty")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!(
+        "No .tf files found in {}",
+        temp_dir.path().display()
+    )));
+
+    // Files without resources are not empty
+    fs::write(
+        temp_dir.path().join("variables.tf"),
+        "variable \"region\" {}\n",
+    )
+    .unwrap();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--fail-on-empty")
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_tf_json_matches_hcl() {
+    let hcl_dir = TempDir::new().unwrap();
+    let json_dir = TempDir::new().unwrap();
+    fs::write(
+        hcl_dir.path().join("main.tf"),
+        "module \"network\" {\n  source = \"./network\"\n}\n\nresource \"aws_instance\" \"web\" {\n  ami = \"ami-12345\"\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        json_dir.path().join("main.tf.json"),
+        r#"{
+  "module": { "network": { "source": "./network" } },
+  "resource": { "aws_instance": { "web": { "ami": "ami-12345" } } }
+}"#,
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let run = |dir: &std::path::Path| {
+        let output = Command::new(&binary)
+            .arg("--src")
+            .arg(dir)
+            .arg("--module-name")
+            .arg("compute")
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let hcl = run(hcl_dir.path());
+    let json = run(json_dir.path());
+    assert!(json.contains("from = aws_instance.web"));
+    assert!(json.contains("to   = module.compute.module.network"));
+    assert_eq!(json.replace("main.tf.json", "main.tf"), hcl);
+}
+
+#[test]
+fn test_sort_address_by_default_and_none_keeps_source_order() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\nresource \"aws_instance\" \"api\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let froms = |extra_args: &[&str]| {
+        let output = Command::new(&binary)
+            .arg("--src")
+            .arg(temp_dir.path())
+            .arg("--module-name")
+            .arg("compute")
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("from = ").map(str::to_string))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(froms(&[]), vec!["aws_instance.api", "aws_instance.web"]);
+    assert_eq!(
+        froms(&["--sort", "none"]),
+        vec!["aws_instance.web", "aws_instance.api"]
+    );
+}
+
+#[test]
+fn test_rerun_skips_resources_already_moved() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("multiple_resources.tf");
+    fs::copy(&fixture_file, src_dir.join("main.tf")).unwrap();
+
+    let binary = get_binary_path();
+    let run = |extra_args: &[&std::ffi::OsStr]| {
+        let output = Command::new(&binary)
+            .arg("--src")
+            .arg(&src_dir)
+            .arg("--module-name")
+            .arg("compute")
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let moved_file = src_dir.join("moved.tf");
+    run(&["--output".as_ref(), moved_file.as_os_str()]);
+    assert_eq!(
+        fs::read_to_string(&moved_file)
+            .unwrap()
+            .matches("moved {")
+            .count(),
+        3
+    );
+    // The second run sees the moved blocks written into --src by the first one
+    assert_eq!(run(&[]).matches("moved {").count(), 0);
+}
+
+#[test]
+fn test_nested_module_name() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("main.tf"),
+        "resource \"aws_instance\" \"web\" {}\n",
+    )
+    .unwrap();
+
+    let binary = get_binary_path();
+    let output = Command::new(&binary)
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("platform.compute")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("from = aws_instance.web"));
+    assert!(stdout.contains("to   = module.platform.module.compute.aws_instance.web"));
+}
+
+#[test]
+fn test_config_file_sets_default_options() {
+    let temp_dir = TempDir::new().unwrap();
+    let nested_dir = temp_dir.path().join("modules").join("network");
+    fs::create_dir_all(&nested_dir).unwrap();
+    fs::write(
+        nested_dir.join("main.tf"),
+        "resource \"aws_vpc\" \"main\" {}\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join(".moved_maker.toml"),
+        "module_name = \"compute\"\nrecursive = true\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("to   = module.compute.aws_vpc.main"));
+
+    // Command-line options win over the config file
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("network")
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("to   = module.network.aws_vpc.main"));
+
+    // Unknown keys are usage errors
+    fs::write(
+        temp_dir.path().join(".moved_maker.toml"),
+        "modul_name = \"compute\"\n",
+    )
+    .unwrap();
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_streamed_output_matches_buffered_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let fixtures_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+    for fixture in ["multiple_resources.tf", "mixed_resources_and_modules.tf"] {
+        fs::copy(fixtures_dir.join(fixture), src_dir.join(fixture)).unwrap();
+    }
+    let output_file = temp_dir.path().join("moved.tf");
+
+    let run = |extra_args: &[&OsStr]| {
+        let output = Command::new(get_binary_path())
+            .arg("--src")
+            .arg(&src_dir)
+            .arg("--module-name")
+            .arg("compute")
+            .args(["--sort", "none", "--no-header"])
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(
+            output.status.success(),
+            "Command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    // Printing to stdout with --sort none streams; writing a file buffers
+    let streamed = run(&[]);
+    assert!(run(&[OsStr::new("--output"), output_file.as_os_str()]).is_empty());
+    let buffered = fs::read_to_string(&output_file).unwrap();
+    assert!(streamed.contains("moved {"));
+    assert_eq!(streamed, buffered);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_mode_applies_to_output_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let fixture_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("single_resource.tf");
+    fs::copy(&fixture_file, temp_dir.path().join("main.tf")).unwrap();
+    let output_file = temp_dir.path().join("moved.tf");
+
+    let output = Command::new(get_binary_path())
+        .arg("--src")
+        .arg(temp_dir.path())
+        .arg("--module-name")
+        .arg("compute")
+        .arg("--output")
+        .arg(&output_file)
+        .arg("--output-mode")
+        .arg("640")
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mode = fs::metadata(&output_file).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+}